@@ -0,0 +1,8 @@
+-- Runtime offset ledger: skips/pauses shift only one occurrence of a schedule entry
+CREATE TABLE IF NOT EXISTS schedule_offsets (
+    schedule_id UUID NOT NULL REFERENCES schedule(id) ON DELETE CASCADE,
+    occurrence_date DATE NOT NULL,
+    offset_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (schedule_id, occurrence_date)
+);
@@ -0,0 +1,9 @@
+-- RFC 5545 style recurrence rule (e.g. FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20261231)
+-- When set it takes precedence over repeat_pattern (daily, weekly, monthly, NULL for one-time)
+ALTER TABLE schedule ADD COLUMN IF NOT EXISTS recurrence TEXT;
+
+-- One-time entries are NULL; 'none'/'' were written by older clients.
+-- Several one-time entries may share a date, so the per-date unique index has to go first.
+DROP INDEX IF EXISTS idx_schedule_unique_date;
+UPDATE schedule SET repeat_pattern = NULL WHERE repeat_pattern IN ('none', '');
+CREATE INDEX IF NOT EXISTS idx_schedule_repeat_pattern ON schedule(repeat_pattern);
@@ -0,0 +1,2 @@
+-- Priority decides which entry airs when schedule blocks overlap (higher wins, ties go to the later start)
+ALTER TABLE schedule ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
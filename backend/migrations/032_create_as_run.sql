@@ -0,0 +1,23 @@
+-- Compliance as-run log: one row per clip actually put on air
+-- Times are absolute instants so DST changes cannot fold or skip an hour
+CREATE TABLE IF NOT EXISTS as_run (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source TEXT NOT NULL DEFAULT 'schedule', -- schedule, filler
+    media_id UUID,
+    clip_path TEXT NOT NULL,
+    filename TEXT,
+    title TEXT,
+    playlist_id UUID,
+    playlist_name TEXT,
+    schedule_id UUID,
+    occurrence_date DATE,
+    planned_start TIMESTAMPTZ,
+    planned_end TIMESTAMPTZ,
+    actual_start TIMESTAMPTZ NOT NULL,
+    actual_end TIMESTAMPTZ,
+    end_reason TEXT, -- completed, skipped, interrupted, paused, stopped
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_as_run_actual_start ON as_run(actual_start);
+CREATE INDEX IF NOT EXISTS idx_as_run_schedule ON as_run(schedule_id, occurrence_date);
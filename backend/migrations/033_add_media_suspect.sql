@@ -0,0 +1,6 @@
+-- Media the master encoder repeatedly failed on (set by the engine watchdog)
+ALTER TABLE media ADD COLUMN IF NOT EXISTS is_suspect BOOLEAN DEFAULT FALSE;
+ALTER TABLE media ADD COLUMN IF NOT EXISTS suspect_reason TEXT;
+ALTER TABLE media ADD COLUMN IF NOT EXISTS suspect_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_media_is_suspect ON media(is_suspect) WHERE is_suspect = true;
@@ -0,0 +1,3 @@
+-- Channel timezone (IANA name) for schedules, broadcast days and EPG offsets
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS timezone TEXT;
+
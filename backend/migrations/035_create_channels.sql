@@ -0,0 +1,38 @@
+-- Several channels in one backend: settings, schedules and as-run rows belong to a channel,
+-- the media library and playlists stay shared
+CREATE TABLE IF NOT EXISTS channels (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug TEXT NOT NULL UNIQUE, -- used in /api/channels/{slug}/... and the HLS preview dir
+    name TEXT NOT NULL,
+    mediamtx_path TEXT NOT NULL UNIQUE, -- master feed is published on {mediamtx_path}/master
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_channels_default ON channels(is_default) WHERE is_default;
+
+-- The existing setup becomes the default channel, keeping live/master
+INSERT INTO channels (slug, name, mediamtx_path, is_default)
+SELECT 'main', COALESCE(NULLIF(channel_name, ''), 'Main'), 'live', TRUE
+FROM settings WHERE id = TRUE
+ON CONFLICT DO NOTHING;
+
+-- One settings row per channel; id = TRUE stays on the default channel's row
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS channel_id UUID REFERENCES channels(id) ON DELETE CASCADE;
+UPDATE settings SET channel_id = (SELECT id FROM channels WHERE is_default) WHERE channel_id IS NULL;
+ALTER TABLE settings ALTER COLUMN channel_id SET NOT NULL;
+ALTER TABLE settings DROP CONSTRAINT IF EXISTS single_row;
+ALTER TABLE settings DROP CONSTRAINT IF EXISTS settings_pkey;
+ALTER TABLE settings ADD PRIMARY KEY (channel_id);
+ALTER TABLE settings ALTER COLUMN id SET DEFAULT FALSE;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_settings_default_row ON settings(id) WHERE id;
+
+ALTER TABLE schedule ADD COLUMN IF NOT EXISTS channel_id UUID REFERENCES channels(id) ON DELETE CASCADE;
+UPDATE schedule SET channel_id = (SELECT id FROM channels WHERE is_default) WHERE channel_id IS NULL;
+ALTER TABLE schedule ALTER COLUMN channel_id SET NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_schedule_channel ON schedule(channel_id);
+
+-- As-run history is kept when a channel is removed
+ALTER TABLE as_run ADD COLUMN IF NOT EXISTS channel_id UUID;
+UPDATE as_run SET channel_id = (SELECT id FROM channels WHERE is_default) WHERE channel_id IS NULL;
+CREATE INDEX IF NOT EXISTS idx_as_run_channel ON as_run(channel_id, actual_start);
@@ -0,0 +1,2 @@
+-- SCTE-35 splice markers on the MPEG-TS outputs and CUE tags in HLS
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS scte35_enabled BOOLEAN NOT NULL DEFAULT FALSE;
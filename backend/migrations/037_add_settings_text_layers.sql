@@ -0,0 +1,11 @@
+-- Ticker (crawl) and static text layers drawn with drawtext
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS ticker_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS ticker_speed INTEGER DEFAULT 120;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS ticker_position VARCHAR(10) DEFAULT 'bottom';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_anchor VARCHAR(20) DEFAULT 'top-left';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_font_path TEXT;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_font_size INTEGER DEFAULT 36;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_color VARCHAR(32) DEFAULT 'white';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_box BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS text_box_color VARCHAR(32) DEFAULT 'black@0.6';
@@ -0,0 +1,6 @@
+-- "Up next" lower third before each transition and a clock/countdown layer
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS upnext_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS upnext_lead INTEGER DEFAULT 10;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS upnext_label VARCHAR(64) DEFAULT 'Up next';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS clock_mode VARCHAR(10) DEFAULT 'off';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS clock_anchor VARCHAR(20) DEFAULT 'bottom-right';
@@ -0,0 +1,2 @@
+-- Integrated loudness every clip is brought to on air (EBU R128: -23 LUFS)
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS loudness_target DOUBLE PRECISION DEFAULT -23;
@@ -0,0 +1,3 @@
+-- Channel default transition between playlist items ("cut", "fade" or "crossfade")
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS transition_type VARCHAR(20) DEFAULT 'cut';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS transition_duration DOUBLE PRECISION DEFAULT 1.0;
@@ -0,0 +1,6 @@
+-- Picture made for audio-only items that do not set their own: background image or video
+-- loop (default image when empty), visualizer ("none", "waves" or "spectrum") and the
+-- now-playing line
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS audio_background_path TEXT;
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS audio_visualizer VARCHAR(20) DEFAULT 'none';
+ALTER TABLE settings ADD COLUMN IF NOT EXISTS audio_now_playing BOOLEAN DEFAULT FALSE;
@@ -0,0 +1,9 @@
+-- One ledger row per skip or pause hold of an occurrence, each applying from its own point
+-- (applies_from: when the skip happened, or the resume that ended the hold). Rows without
+-- one are earlier running totals and apply to the whole occurrence.
+ALTER TABLE schedule_offsets DROP CONSTRAINT IF EXISTS schedule_offsets_pkey;
+ALTER TABLE schedule_offsets ADD COLUMN IF NOT EXISTS id UUID NOT NULL DEFAULT gen_random_uuid();
+ALTER TABLE schedule_offsets ADD PRIMARY KEY (id);
+ALTER TABLE schedule_offsets ADD COLUMN IF NOT EXISTS applies_from TIMESTAMPTZ;
+CREATE INDEX IF NOT EXISTS idx_schedule_offsets_occurrence
+    ON schedule_offsets(schedule_id, occurrence_date);
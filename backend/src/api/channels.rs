@@ -0,0 +1,262 @@
+use crate::models::channel::{is_valid_slug, Channel, CreateChannelRequest, UpdateChannelRequest};
+use crate::services::channels::ChannelRegistry;
+use crate::services::engine::PlayoutEngine;
+use actix_web::dev::Payload;
+use actix_web::{error, web, FromRequest, HttpRequest, HttpResponse, Responder};
+use futures::future::{ready, Ready};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Channel a request is for: `{channel}` (slug or id) under `/api/channels/{channel}/...`,
+/// the default channel on the unscoped routes
+pub struct ChannelScope {
+    pub engine: Arc<PlayoutEngine>,
+}
+
+impl ChannelScope {
+    pub fn id(&self) -> Uuid {
+        self.engine.channel.id
+    }
+
+    pub fn channel(&self) -> &Channel {
+        &self.engine.channel
+    }
+}
+
+/// `{id}` of a channel route; a struct so the `{channel}` segment can sit alongside it
+#[derive(Deserialize)]
+pub struct ItemPath {
+    pub id: Uuid,
+}
+
+impl FromRequest for ChannelScope {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let Some(registry) = req.app_data::<web::Data<Arc<ChannelRegistry>>>() else {
+            return ready(Err(error::ErrorInternalServerError(
+                "Channel registry not configured",
+            )));
+        };
+        let engine = match req.match_info().get("channel") {
+            None => Some(registry.default_engine()),
+            Some(key) => registry.get(key),
+        };
+        ready(match engine {
+            Some(engine) => Ok(ChannelScope { engine }),
+            None => Err(error::InternalError::from_response(
+                "channel not found",
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "error": format!(
+                        "Channel '{}' not found",
+                        req.match_info().get("channel").unwrap_or_default()
+                    )
+                })),
+            )
+            .into()),
+        })
+    }
+}
+
+async fn list_channels(pool: web::Data<PgPool>) -> impl Responder {
+    let result = sqlx::query_as::<_, Channel>(
+        "SELECT * FROM channels ORDER BY is_default DESC, created_at ASC",
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(channels) => HttpResponse::Ok().json(channels),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()}))
+        }
+    }
+}
+
+async fn get_channel(channel: ChannelScope) -> impl Responder {
+    let status = channel.engine.status.lock().await.clone();
+    HttpResponse::Ok().json(serde_json::json!({
+        "channel": channel.channel(),
+        "master_path": channel.channel().master_path(),
+        "status": status
+    }))
+}
+
+async fn create_channel(
+    req: web::Json<CreateChannelRequest>,
+    pool: web::Data<PgPool>,
+    registry: web::Data<Arc<ChannelRegistry>>,
+) -> impl Responder {
+    let slug = req.slug.trim().to_lowercase();
+    if !is_valid_slug(&slug) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Slug must be 1-32 lowercase letters, digits or dashes"
+        }));
+    }
+    if req.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Name is required"}));
+    }
+    let template = match req.copy_from.as_deref() {
+        Some(key) => match registry.get(key) {
+            Some(engine) => engine.channel.id,
+            None => {
+                return HttpResponse::NotFound().json(serde_json::json!({
+                    "error": format!("Channel '{}' not found", key)
+                }))
+            }
+        },
+        None => registry.default_engine().channel.id,
+    };
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({"error": e.to_string()}))
+        }
+    };
+
+    let channel = match sqlx::query_as::<_, Channel>(
+        "INSERT INTO channels (slug, name, mediamtx_path) VALUES ($1, $2, $3) RETURNING *",
+    )
+    .bind(&slug)
+    .bind(req.name.trim())
+    .bind(format!("live/{}", slug))
+    .fetch_one(&mut tx)
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = e.to_string();
+            return if msg.contains("duplicate key") {
+                HttpResponse::Conflict().json(serde_json::json!({
+                    "error": format!("Channel '{}' already exists", slug)
+                }))
+            } else {
+                HttpResponse::InternalServerError().json(serde_json::json!({"error": msg}))
+            };
+        }
+    };
+
+    // Everything comes from the template's row, so settings columns added later are copied
+    // too; outputs start disabled and point at the channel's own MediaMTX paths, and the
+    // running state starts over
+    let host = std::env::var("MEDIAMTX_HOST").unwrap_or_else(|_| "mediamtx".to_string());
+    let stream_path = format!("{}/stream", channel.mediamtx_path);
+    let mut result = sqlx::query(
+        "CREATE TEMP TABLE new_settings (LIKE settings INCLUDING DEFAULTS) ON COMMIT DROP",
+    )
+    .execute(&mut tx)
+    .await;
+    if result.is_ok() {
+        result =
+            sqlx::query("INSERT INTO new_settings SELECT * FROM settings WHERE channel_id = $1")
+                .bind(template)
+                .execute(&mut tx)
+                .await;
+    }
+    if result.is_ok() {
+        result = sqlx::query(
+            "UPDATE new_settings SET
+                channel_id = $1, id = FALSE, channel_name = $2, is_running = FALSE,
+                output_type = 'rtmp', output_url = $3,
+                rtmp_output_url = $3, srt_output_url = $4, rtsp_output_url = $5,
+                webrtc_output_url = $6,
+                rtmp_enabled = DEFAULT, srt_enabled = DEFAULT, rtsp_enabled = DEFAULT,
+                webrtc_enabled = DEFAULT, udp_enabled = DEFAULT, hls_enabled = DEFAULT,
+                llhls_enabled = DEFAULT, dash_enabled = DEFAULT, mss_enabled = DEFAULT,
+                rist_enabled = DEFAULT, udp_output_url = DEFAULT, dash_output_url = DEFAULT,
+                mss_output_url = DEFAULT, rist_output_url = DEFAULT, epg_url = DEFAULT,
+                auto_start_protocols = DEFAULT, clips_played_today = DEFAULT, last_error = DEFAULT,
+                updated_at = DEFAULT",
+        )
+        .bind(channel.id)
+        .bind(&channel.name)
+        .bind(format!("rtmp://{}:1935/{}", host, stream_path))
+        .bind(format!(
+            "srt://{}:8890?mode=caller&streamid=publish:{}",
+            host, stream_path
+        ))
+        .bind(format!("rtsp://localhost:8554/{}", stream_path))
+        .bind(format!("http://localhost:8889/{}", stream_path))
+        .execute(&mut tx)
+        .await;
+    }
+    if result.is_ok() {
+        result = sqlx::query("INSERT INTO settings SELECT * FROM new_settings")
+            .execute(&mut tx)
+            .await;
+    }
+
+    if let Err(e) = result {
+        return HttpResponse::InternalServerError().json(
+            serde_json::json!({"error": format!("Failed to create channel settings: {}", e)}),
+        );
+    }
+    if let Err(e) = tx.commit().await {
+        return HttpResponse::InternalServerError()
+            .json(serde_json::json!({"error": e.to_string()}));
+    }
+
+    registry.spawn(pool.get_ref(), channel.clone());
+    HttpResponse::Created().json(channel)
+}
+
+async fn update_channel(
+    channel: ChannelScope,
+    req: web::Json<UpdateChannelRequest>,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
+    if req.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Name is required"}));
+    }
+    let result =
+        sqlx::query_as::<_, Channel>("UPDATE channels SET name = $1 WHERE id = $2 RETURNING *")
+            .bind(req.name.trim())
+            .bind(channel.id())
+            .fetch_one(pool.get_ref())
+            .await;
+
+    match result {
+        Ok(updated) => HttpResponse::Ok().json(updated),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()}))
+        }
+    }
+}
+
+async fn delete_channel(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    registry: web::Data<Arc<ChannelRegistry>>,
+) -> impl Responder {
+    if channel.channel().is_default {
+        return HttpResponse::BadRequest()
+            .json(serde_json::json!({"error": "The default channel cannot be deleted"}));
+    }
+
+    // Off air first, then settings and schedules go with the channel row
+    registry.remove(channel.id()).await;
+    match sqlx::query("DELETE FROM channels WHERE id = $1")
+        .bind(channel.id())
+        .execute(pool.get_ref())
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({"message": "Channel deleted"})),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({"error": e.to_string()}))
+        }
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("", web::get().to(list_channels))
+        .route("", web::post().to(create_channel))
+        .route("/{channel}", web::get().to(get_channel))
+        .route("/{channel}", web::put().to(update_channel))
+        .route("/{channel}", web::delete().to(delete_channel))
+        .service(web::scope("/{channel}").configure(crate::api::routes::configure_channel));
+}
@@ -0,0 +1,53 @@
+use crate::api::channels::ChannelScope;
+use crate::services::graphics::{self, TEXT_FILE, TICKER_FILE};
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct TextUpdate {
+    pub text: String,
+}
+
+fn read_layer(channel: &ChannelScope, file: &str) -> HttpResponse {
+    let text = std::fs::read_to_string(channel.channel().text_dir().join(file)).unwrap_or_default();
+    HttpResponse::Ok().json(serde_json::json!({ "text": text }))
+}
+
+fn write_layer(channel: &ChannelScope, file: &str, text: &str) -> HttpResponse {
+    match graphics::write_text_file(&channel.channel().text_dir(), file, text) {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({ "text": text })),
+        Err(e) => HttpResponse::InternalServerError()
+            .json(serde_json::json!({"error": format!("Failed to write {}: {}", file, e)})),
+    }
+}
+
+async fn get_ticker(channel: ChannelScope) -> impl Responder {
+    read_layer(&channel, TICKER_FILE)
+}
+
+// Picked up by the running encoder on its next frame
+async fn update_ticker(channel: ChannelScope, req: web::Json<TextUpdate>) -> impl Responder {
+    let response = write_layer(&channel, TICKER_FILE, &graphics::ticker_line(&req.text));
+    if response.status().is_success() {
+        channel
+            .engine
+            .add_log("✓ Ticker text updated".to_string())
+            .await;
+    }
+    response
+}
+
+async fn get_text(channel: ChannelScope) -> impl Responder {
+    read_layer(&channel, TEXT_FILE)
+}
+
+async fn update_text(channel: ChannelScope, req: web::Json<TextUpdate>) -> impl Responder {
+    write_layer(&channel, TEXT_FILE, req.text.trim_end())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/ticker", web::get().to(get_ticker))
+        .route("/ticker", web::put().to(update_ticker))
+        .route("/text", web::get().to(get_text))
+        .route("/text", web::put().to(update_text));
+}
@@ -8,7 +8,8 @@ use std::path::Path;
 use uuid::Uuid;
 
 use crate::models::media::{CreateFolder, Folder, Media};
-use crate::services::ffmpeg::FFmpegService;
+use crate::services::ffmpeg::{is_still_image, FFmpegService};
+use crate::services::loudness::Loudness;
 use crate::services::metadata_fetcher::MetadataFetcherService;
 
 #[derive(serde::Deserialize)]
@@ -160,6 +161,27 @@ async fn update_filler(
     }
 }
 
+// Clear the watchdog's suspect flag once the file has been checked or replaced
+async fn clear_suspect(media_id: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let result = sqlx::query(
+        "UPDATE media SET is_suspect = FALSE, suspect_reason = NULL, suspect_at = NULL
+         WHERE id = $1",
+    )
+    .bind(media_id.into_inner())
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Suspect flag cleared"
+        })),
+        Ok(_) => HttpResponse::NotFound().json(serde_json::json!({"error": "Media not found"})),
+        Err(_) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({"error": "Database error"}))
+        }
+    }
+}
+
 #[derive(serde::Deserialize)]
 pub struct UpdateMediaRequest {
     pub filename: Option<String>,
@@ -188,6 +210,7 @@ async fn update_media(
     if let Some(ref metadata) = req.metadata {
         separated.push("metadata = ");
         separated.push_bind_unseparated(metadata);
+        separated.push_unseparated(KEEP_LOUDNESS);
     }
 
     query_builder.push(" WHERE id = ");
@@ -627,11 +650,14 @@ async fn fetch_metadata(media_id: web::Path<Uuid>, pool: web::Data<PgPool>) -> i
                         serde_json::to_value(&meta).unwrap_or(serde_json::json!({}));
 
                     // Update DB
-                    let update = sqlx::query("UPDATE media SET metadata = $1 WHERE id = $2")
-                        .bind(&metadata_json)
-                        .bind(m.id)
-                        .execute(pool.get_ref())
-                        .await;
+                    let update = sqlx::query(&format!(
+                        "UPDATE media SET metadata = $1{} WHERE id = $2",
+                        KEEP_LOUDNESS
+                    ))
+                    .bind(&metadata_json)
+                    .bind(m.id)
+                    .execute(pool.get_ref())
+                    .await;
 
                     match update {
                         Ok(_) => HttpResponse::Ok().json(serde_json::json!({
@@ -743,11 +769,19 @@ async fn upload_media(
         let thumbnail_filename = format!("{}.jpg", id);
         let thumbnail_path = format!("{}/{}", thumbnails_path, thumbnail_filename);
 
+        let is_image = is_still_image(&file_path);
         if info.has_video {
-            let _ = ffmpeg.generate_thumbnail(&file_path, &thumbnail_path, 1.0);
+            let at = if is_image { 0.0 } else { 1.0 };
+            let _ = ffmpeg.generate_thumbnail(&file_path, &thumbnail_path, at);
         }
 
-        let media_type = if info.has_video { "video" } else { "audio" };
+        let media_type = if is_image {
+            "image"
+        } else if info.has_video {
+            "video"
+        } else {
+            "audio"
+        };
 
         sqlx::query("INSERT INTO media (id, filename, path, media_type, duration, width, height, codec, bitrate, thumbnail_path, folder_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)")
             .bind(id)
@@ -764,11 +798,73 @@ async fn upload_media(
             .execute(pool.get_ref())
             .await
             .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+
+        // The loudnorm pass reads the whole file, so it runs after the upload returns
+        if info.has_audio {
+            let pool = pool.get_ref().clone();
+            tokio::spawn(async move {
+                if let Err(e) = analyze_loudness(&pool, id, file_path.clone()).await {
+                    log::warn!("Loudness analysis of {} failed: {}", file_path, e);
+                }
+            });
+        }
     }
 
     Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Upload successful"})))
 }
 
+// Replacing metadata keeps the loudness measured at ingest
+const KEEP_LOUDNESS: &str =
+    " || jsonb_strip_nulls(jsonb_build_object('loudness', metadata->'loudness'))";
+
+/// Measure a clip (EBU R128) and store the result in `media.metadata.loudness`
+async fn analyze_loudness(pool: &PgPool, id: Uuid, path: String) -> Result<Loudness, String> {
+    let measured =
+        tokio::task::spawn_blocking(move || FFmpegService::new().measure_loudness(&path))
+            .await
+            .map_err(|e| e.to_string())??;
+    let value = serde_json::to_value(measured).map_err(|e| e.to_string())?;
+    sqlx::query(
+        "UPDATE media SET metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object('loudness', $1::jsonb) WHERE id = $2",
+    )
+    .bind(value)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    log::info!(
+        "🔊 Loudness of {}: {:.1} LUFS, {:.1} dBTP, LRA {:.1} LU",
+        id,
+        measured.integrated,
+        measured.true_peak,
+        measured.lra
+    );
+    Ok(measured)
+}
+
+// Re-measure an existing clip (media uploaded before analysis existed, or replaced on disk)
+async fn measure_loudness(media_id: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
+    let path = match sqlx::query("SELECT path FROM media WHERE id = $1")
+        .bind(*media_id)
+        .fetch_optional(pool.get_ref())
+        .await
+    {
+        Ok(Some(row)) => row.get::<String, _>("path"),
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({"error": "Media not found"}))
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({"error": e.to_string()}))
+        }
+    };
+
+    match analyze_loudness(pool.get_ref(), *media_id, path).await {
+        Ok(measured) => HttpResponse::Ok().json(measured),
+        Err(e) => HttpResponse::UnprocessableEntity().json(serde_json::json!({"error": e})),
+    }
+}
+
 async fn delete_media(media_id: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
     // Get path first
     let media = sqlx::query_as::<_, Media>("SELECT * FROM media WHERE id = $1")
@@ -932,9 +1028,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/{id}/stream", web::get().to(stream_media))
         .route("/{id}/thumbnail", web::get().to(get_thumbnail))
         .route("/{id}/filler", web::put().to(update_filler))
+        .route("/{id}/suspect", web::delete().to(clear_suspect))
         .route("/{id}/transparent", web::post().to(make_transparent))
         .route("/upload", web::post().to(upload_media))
         .route("/{id}", web::put().to(update_media))
         .route("/{id}/fetch-metadata", web::post().to(fetch_metadata))
+        .route("/{id}/loudness", web::post().to(measure_loudness))
         .route("/{id}", web::delete().to(delete_media));
 }
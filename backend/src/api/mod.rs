@@ -1,10 +1,14 @@
 pub mod auth;
+pub mod channels;
+pub mod graphics;
 pub mod health;
 pub mod media;
 pub mod playlists;
 pub mod playout;
 pub mod protected;
+pub mod reports;
 pub mod routes;
 pub mod schedule;
 pub mod settings;
 pub mod templates;
+pub mod ws;
@@ -1,8 +1,12 @@
-use crate::models::playlist::{Playlist, PlaylistContent, PlaylistItem};
-use crate::models::schedule::Schedule;
-use crate::models::settings::Settings;
+use crate::api::channels::{ChannelScope, ItemPath};
+use crate::models::playlist::{
+    parse_items, sequence_duration, Playlist, PlaylistContent, PlaylistItem, Transition,
+};
+use crate::services::timeline::{self, EntryKind, TimelineEntry};
+use crate::services::{broadcast_day, channels, clock};
 use actix_web::{web, HttpResponse, Responder};
-use chrono::{Datelike, Utc};
+use chrono::Utc;
+use chrono_tz::Tz;
 use serde::Deserialize;
 use serde_json::json;
 use sqlx::PgPool;
@@ -32,6 +36,7 @@ pub struct UpdatePlaylistRequest {
 }
 
 async fn list_playlists(
+    channel: ChannelScope,
     pool: web::Data<PgPool>,
     query: web::Query<PlaylistQuery>,
 ) -> impl Responder {
@@ -44,34 +49,39 @@ async fn list_playlists(
         .await
     } else if let Some(date_str) = &query.date {
         if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-            let dow = date.weekday().num_days_from_monday() as i32;
-
-            // 1. Fetch all relevant scheduled slots joined with playlist data
-            // We need individual slots to calculate exact start times for recurring events
-            // 1. Fetch all relevant scheduled slots joined with playlist data
-            // We use UNION to combine specific date matches with recurring pattern matches
-            // This ensures we only get playlists that are ACTUAL scheduled for this day
-            let rows = sqlx::query_as::<_, (Uuid, String, serde_json::Value, f64, chrono::NaiveTime, String)>(
-                "SELECT p.id, p.name, p.content, p.total_duration, s.start_time, s.repeat_pattern
-                 FROM playlists p
-                 JOIN schedule s ON p.id = s.playlist_id
-                 WHERE 
-                    -- Single date match
-                    (s.repeat_pattern = 'none' AND s.date = $1)
-                    OR
-                    -- Daily match (started on or before today)
-                    (s.repeat_pattern = 'daily' AND s.date <= $1)
-                    OR
-                    -- Weekly match (same day of week, started on or before today)
-                    (s.repeat_pattern = 'weekly' AND (EXTRACT(DOW FROM s.date) + 6)::int % 7 = $2 AND s.date <= $1)
-                 
-                 -- Filter out potential duplicates or inactive if we had an active flag (we rely on date logic)
-                 ORDER BY s.start_time ASC",
-            )
-            .bind(date)
-            .bind(dow)
-            .fetch_all(pool.get_ref())
-            .await;
+            // 1. Expand the schedule for this date (same expander as the engine and EPG)
+            let tz = clock::load_tz(pool.get_ref(), channel.id()).await;
+            let occurrences =
+                broadcast_day::load_occurrences(pool.get_ref(), channel.id(), date, date, &tz)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!("Failed to expand schedule for {}: {}", date, e);
+                        Vec::new()
+                    });
+            let playlist_ids: Vec<Uuid> =
+                occurrences.iter().map(|o| o.schedule.playlist_id).collect();
+
+            // 2. Join each occurrence with its playlist data
+            let rows = sqlx::query_as::<_, Playlist>("SELECT * FROM playlists WHERE id = ANY($1)")
+                .bind(&playlist_ids)
+                .fetch_all(pool.get_ref())
+                .await
+                .map(|playlists| {
+                    occurrences
+                        .iter()
+                        .filter_map(|o| {
+                            let p = playlists.iter().find(|p| p.id == o.schedule.playlist_id)?;
+                            Some((
+                                p.id,
+                                p.name.clone(),
+                                p.content.clone(),
+                                p.total_duration,
+                                o.start,
+                                o.schedule.repeat_pattern.clone(),
+                            ))
+                        })
+                        .collect::<Vec<_>>()
+                });
 
             match rows {
                 Ok(rows) => {
@@ -82,29 +92,18 @@ async fn list_playlists(
                         (String, f64, Vec<PlaylistItem>),
                     > = std::collections::HashMap::new();
 
-                    for (id, name, content_val, total_dur, start_time, _) in rows {
+                    for (id, name, content_val, total_dur, start, _) in rows {
                         // Parse content
-                        let items = if let Ok(c) =
-                            serde_json::from_value::<PlaylistContent>(content_val.clone())
-                        {
-                            c.program
-                        } else if let Ok(i) =
-                            serde_json::from_value::<Vec<PlaylistItem>>(content_val.clone())
-                        {
-                            i
-                        } else {
-                            vec![]
-                        };
-
-                        // Calculate items with absolute time for this slot
-                        let base_dt = chrono::NaiveDateTime::new(date, start_time);
-                        let mut current_dt = base_dt;
+                        let items = parse_items(&content_val).unwrap_or_default();
+
+                        // Calculate items with absolute time for this slot, shown in channel time
+                        let mut current_dt = start;
 
                         let mut scheduled_items = Vec::new();
                         for item in items {
-                            let duration = item.duration;
-                            let item_end_dt =
-                                current_dt + chrono::Duration::seconds(duration as i64);
+                            let duration = item.effective_duration();
+                            let item_end_dt = current_dt
+                                + chrono::Duration::milliseconds((duration * 1000.0) as i64);
 
                             let mut new_item = item.clone();
                             // Generate a unique ID for React keys by appending a suffix
@@ -119,8 +118,13 @@ async fn list_playlists(
                                 new_item.id = Some(format!("item_{}", suffix.to_lowercase()));
                             }
 
-                            new_item.start_time = Some(current_dt.format("%H:%M:%S").to_string());
-                            new_item.end_time = Some(item_end_dt.format("%H:%M:%S").to_string());
+                            new_item.start_time =
+                                Some(clock::local(&tz, current_dt).format("%H:%M:%S").to_string());
+                            new_item.end_time = Some(
+                                clock::local(&tz, item_end_dt)
+                                    .format("%H:%M:%S")
+                                    .to_string(),
+                            );
 
                             scheduled_items.push(new_item);
                             current_dt = item_end_dt;
@@ -185,10 +189,11 @@ async fn create_playlist(
     req: web::Json<CreatePlaylistRequest>,
 ) -> impl Responder {
     let result = sqlx::query_as::<_, Playlist>(
-        "INSERT INTO playlists (name, content) VALUES ($1, $2) RETURNING *",
+        "INSERT INTO playlists (name, content, total_duration) VALUES ($1, $2, $3) RETURNING *",
     )
     .bind(&req.name)
     .bind(&req.content)
+    .bind(calculate_duration_from_json(&req.content, None))
     .fetch_one(pool.get_ref())
     .await;
 
@@ -200,9 +205,9 @@ async fn create_playlist(
     }
 }
 
-async fn get_playlist(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> impl Responder {
+async fn get_playlist(pool: web::Data<PgPool>, path: web::Path<ItemPath>) -> impl Responder {
     let result = sqlx::query_as::<_, Playlist>("SELECT * FROM playlists WHERE id = $1")
-        .bind(*id)
+        .bind(path.id)
         .fetch_optional(pool.get_ref())
         .await;
 
@@ -217,7 +222,7 @@ async fn get_playlist(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> impl Resp
 
 async fn update_playlist(
     pool: web::Data<PgPool>,
-    id: web::Path<Uuid>,
+    path: web::Path<ItemPath>,
     req: web::Json<UpdatePlaylistRequest>,
 ) -> impl Responder {
     let mut query = String::from("UPDATE playlists SET ");
@@ -232,8 +237,12 @@ async fn update_playlist(
         if params_count > 0 {
             query.push_str(", ");
         }
-        query.push_str(&format!("content = ${}", params_count + 1));
-        params_count += 1;
+        query.push_str(&format!(
+            "content = ${}, total_duration = ${}",
+            params_count + 1,
+            params_count + 2
+        ));
+        params_count += 2;
     }
 
     if params_count == 0 {
@@ -247,9 +256,11 @@ async fn update_playlist(
         db_query = db_query.bind(name);
     }
     if let Some(content) = &req.content {
-        db_query = db_query.bind(content);
+        db_query = db_query
+            .bind(content)
+            .bind(calculate_duration_from_json(content, None));
     }
-    db_query = db_query.bind(*id);
+    db_query = db_query.bind(path.id);
 
     match db_query.fetch_one(pool.get_ref()).await {
         Ok(playlist) => HttpResponse::Ok().json(playlist),
@@ -259,11 +270,11 @@ async fn update_playlist(
     }
 }
 
-async fn delete_playlist(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> impl Responder {
+async fn delete_playlist(pool: web::Data<PgPool>, path: web::Path<ItemPath>) -> impl Responder {
     // Check if playlist is in use in schedule
     let count: i64 =
         match sqlx::query_scalar("SELECT COUNT(*) FROM schedule WHERE playlist_id = $1")
-            .bind(*id)
+            .bind(path.id)
             .fetch_one(pool.get_ref())
             .await
         {
@@ -279,7 +290,7 @@ async fn delete_playlist(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> impl R
     }
 
     let result = sqlx::query("DELETE FROM playlists WHERE id = $1")
-        .bind(*id)
+        .bind(path.id)
         .execute(pool.get_ref())
         .await;
 
@@ -291,8 +302,20 @@ async fn delete_playlist(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> impl R
     }
 }
 
-async fn validate_playlist(req: web::Json<ValidatePlaylistRequest>) -> impl Responder {
-    let total_duration = calculate_duration_from_json(&req.content);
+async fn validate_playlist(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    req: web::Json<ValidatePlaylistRequest>,
+) -> impl Responder {
+    // Checked against what the channel airs, its default transition included
+    let default = match channels::load_settings(pool.get_ref(), channel.id()).await {
+        Ok(settings) => settings.default_transition(),
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .json(json!({"error": "Error fetching settings"}))
+        }
+    };
+    let total_duration = calculate_duration_from_json(&req.content, default.as_ref());
     let target_duration = 24.0 * 3600.0; // 24 hours in seconds
 
     let is_valid = (total_duration - target_duration).abs() < 1.0;
@@ -311,9 +334,9 @@ fn format_duration(seconds: f64) -> String {
     format!("{:02}:{:02}:{:02}", h, m, s)
 }
 
-fn calculate_duration_from_json(content: &serde_json::Value) -> f64 {
-    match serde_json::from_value::<PlaylistContent>(content.clone()) {
-        Ok(p_content) => p_content.program.iter().map(|item| item.duration).sum(),
+fn calculate_duration_from_json(content: &serde_json::Value, default: Option<&Transition>) -> f64 {
+    match parse_items(content) {
+        Ok(items) => sequence_duration(&items, default),
         Err(e) => {
             log::error!(
                 "[EPG] Failed to parse playlist content to calculate duration: {}. value: {:?}",
@@ -325,11 +348,8 @@ fn calculate_duration_from_json(content: &serde_json::Value) -> f64 {
     }
 }
 
-async fn get_epg(pool: web::Data<PgPool>) -> impl Responder {
-    let settings = match sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE id = TRUE")
-        .fetch_one(pool.get_ref())
-        .await
-    {
+async fn get_epg(channel: ChannelScope, pool: web::Data<PgPool>) -> impl Responder {
+    let settings = match channels::load_settings(pool.get_ref(), channel.id()).await {
         Ok(s) => s,
         Err(_) => {
             return HttpResponse::InternalServerError().body("Error fetching settings");
@@ -337,193 +357,77 @@ async fn get_epg(pool: web::Data<PgPool>) -> impl Responder {
     };
 
     let epg_days = settings.epg_days.unwrap_or(7);
-    let now_local = Utc::now().with_timezone(&chrono::Local);
-    let now_naive = now_local.naive_local();
-    let timezone_offset = now_local.offset().to_string().replace(":", "");
+    let tz = clock::channel_tz(&settings);
 
     let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
     xml.push_str("<!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n");
     xml.push_str("<tv generator-info-name=\"ONEPA Playout PRO\">\n");
-    xml.push_str("  <channel id=\"onepa.1\">\n");
+    let epg_id = channel.channel().epg_id();
+    xml.push_str(&format!("  <channel id=\"{}\">\n", epg_id));
     xml.push_str(&format!(
         "    <display-name>{}</display-name>\n",
         escape_xml(settings.channel_name.as_deref().unwrap_or("ONEPA TV"))
     ));
     xml.push_str("  </channel>\n");
 
-    for day_offset in 0..epg_days {
-        let current_date = (now_local + chrono::Duration::days(day_offset as i64)).date_naive();
-        let dow = current_date.weekday().num_days_from_monday() as i32;
-
-        let query = "
-            SELECT s.*, p.name as playlist_name 
-            FROM schedule s
-            LEFT JOIN playlists p ON s.playlist_id = p.id
-            WHERE (s.date = $1 
-               OR (s.repeat_pattern = 'daily' AND s.date <= $1)
-               OR (s.repeat_pattern = 'weekly' AND (EXTRACT(DOW FROM s.date) + 6)::int % 7 = $2 AND s.date <= $1))
-              AND NOT EXISTS (
-                  SELECT 1 FROM schedule_exceptions se 
-                  WHERE se.schedule_id = s.id AND se.exception_date = $1
-              )
-            ORDER BY s.start_time ASC";
+    // EPG days follow broadcast days (day_start to day_start), so late-night
+    // programmes stay with the evening they belong to
+    let day_start = broadcast_day::day_start(&settings);
+    let first_day = broadcast_day::broadcast_date(Utc::now(), day_start, &tz);
+    let last_day = first_day + chrono::Duration::days(epg_days.max(1) as i64 - 1);
+
+    // Programmes come from the resolved timeline, so preemption and skips show up as aired
+    let (window_start, _) = broadcast_day::day_bounds(first_day, day_start, &tz);
+    let (_, window_end) = broadcast_day::day_bounds(last_day, day_start, &tz);
+    let rundown = match broadcast_day::load_timeline(
+        pool.get_ref(),
+        channel.id(),
+        window_start,
+        window_end,
+        &tz,
+    )
+    .await
+    {
+        Ok(input) => timeline::resolve(&input, window_start, window_end),
+        Err(e) => {
+            log::error!("[EPG] Error resolving timeline: {}", e);
+            Vec::new()
+        }
+    };
 
+    for entry in rundown.iter().filter(|e| e.kind == EntryKind::Clip) {
         log::debug!(
-            "[EPG] Fetching schedules for date: {}, dow: {}",
-            current_date,
-            dow
+            "[EPG] XML Entry: Playlist={:?}, Start={}, End={}",
+            entry.playlist_name,
+            entry.start,
+            entry.end
         );
-
-        let schedules_result = sqlx::query_as::<_, Schedule>(query)
-            .bind(current_date)
-            .bind(dow)
-            .fetch_all(pool.get_ref())
-            .await;
-
-        match schedules_result {
-            Ok(schedules) => {
-                log::debug!(
-                    "[EPG] Found {} schedules for {}",
-                    schedules.len(),
-                    current_date
-                );
-                for schedule in schedules {
-                    if let Ok(Some(playlist)) =
-                        sqlx::query_as::<_, Playlist>("SELECT * FROM playlists WHERE id = $1")
-                            .bind(schedule.playlist_id)
-                            .fetch_optional(pool.get_ref())
-                            .await
-                    {
-                        let start_time = schedule
-                            .start_time
-                            .unwrap_or_else(|| chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-                        let schedule_start = chrono::NaiveDateTime::new(current_date, start_time);
-
-                        let duration = calculate_duration_from_json(&playlist.content);
-                        // Defensive: If parsing failed or empty, default to 1 hour to ensure visibility
-                        let effective_duration = if duration < 1.0 { 3600.0 } else { duration };
-
-                        let schedule_end =
-                            schedule_start + chrono::Duration::seconds(effective_duration as i64);
-
-                        let is_active = now_naive >= schedule_start && now_naive < schedule_end;
-
-                        // Only skip if it's a FUTURE day AND the schedule is in the past (sanity check)
-                        // For day_offset 0 (today), we show everything scheduled for today for completeness.
-                        if day_offset > 0 && schedule_end < now_naive {
-                            continue;
-                        }
-
-                        log::debug!(
-                            "[EPG] XML Entry: Playlist={}, Start={}, End={}, Active={}",
-                            playlist.name,
-                            schedule_start,
-                            schedule_end,
-                            is_active
-                        );
-
-                        append_playlist_to_xml(
-                            &mut xml,
-                            &playlist,
-                            &schedule,
-                            is_active,
-                            now_naive,
-                            current_date,
-                            &timezone_offset,
-                        );
-                    }
-                }
-            }
-            Err(e) => {
-                log::error!("[EPG] Error fetching schedules for {}: {}", current_date, e);
-            }
-        }
+        append_entry_to_xml(&mut xml, entry, &tz, &epg_id);
     }
 
     xml.push_str("</tv>\n");
     HttpResponse::Ok().content_type("application/xml").body(xml)
 }
 
-fn append_playlist_to_xml(
-    xml: &mut String,
-    playlist: &Playlist,
-    schedule: &Schedule,
-    _is_active: bool,
-    _now: chrono::NaiveDateTime,
-    current_date: chrono::NaiveDate,
-    timezone_offset: &str,
-) {
-    // Try parsing as PlaylistContent (object with 'program' field) or fallback to raw Vec<PlaylistItem> (raw array)
-    let program = if let Ok(content) =
-        serde_json::from_value::<PlaylistContent>(playlist.content.clone())
-    {
-        content.program
-    } else if let Ok(items) = serde_json::from_value::<Vec<PlaylistItem>>(playlist.content.clone())
-    {
-        items
-    } else {
-        log::error!(
-            "[EPG] Failed to parse playlist content for ID: {}. Content: {}",
-            playlist.id,
-            playlist.content
-        );
-        return;
-    };
+fn append_entry_to_xml(xml: &mut String, entry: &TimelineEntry, tz: &Tz, epg_id: &str) {
+    // Channel time with the offset in force at each instant, so programmes either side of
+    // a DST change carry their own offset
+    let start_fmt = entry.start.with_timezone(tz).format("%Y%m%d%H%M%S %z");
+    let end_fmt = entry.end.with_timezone(tz).format("%Y%m%d%H%M%S %z");
 
-    let date_str = current_date.format("%Y%m%d").to_string();
-    let base_start_time = schedule
-        .start_time
-        .unwrap_or_else(|| chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-    let mut current_start = chrono::NaiveDateTime::new(current_date, base_start_time);
-
-    if program.is_empty() {
-        // Fallback for empty playlist: show a single 1-hour block so it's not invisible
-        let current_end = current_start + chrono::Duration::hours(1);
-        let start_fmt = current_start.format("%H%M%S").to_string();
-        let end_fmt = current_end.format("%H%M%S").to_string();
-
-        xml.push_str(&format!(
-            "  <programme start=\"{}{} {}\" stop=\"{}{} {}\" channel=\"onepa.1\">\n",
-            date_str, start_fmt, timezone_offset, date_str, end_fmt, timezone_offset
-        ));
-        xml.push_str(&format!(
-            "    <title lang=\"pt\">{} (Vazio)</title>\n",
-            escape_xml(&playlist.name)
-        ));
-        xml.push_str("    <desc lang=\"pt\">Playlist sem clips configurados</desc>\n");
-        xml.push_str("  </programme>\n");
-    } else {
-        for item in program {
-            let current_end = current_start + chrono::Duration::seconds(item.duration as i64);
-
-            let start_fmt = current_start.format("%H%M%S").to_string();
-            let end_fmt = current_end.format("%H%M%S").to_string();
-
-            let title = item
-                .metadata
-                .as_ref()
-                .and_then(|m| m.get("title"))
-                .and_then(|t| t.as_str())
-                .or_else(|| item.filename.as_deref())
-                .unwrap_or("Sem título");
-
-            xml.push_str(&format!(
-                "  <programme start=\"{}{} {}\" stop=\"{}{} {}\" channel=\"onepa.1\">\n",
-                date_str, start_fmt, timezone_offset, date_str, end_fmt, timezone_offset
-            ));
-            xml.push_str(&format!(
-                "    <title lang=\"pt\">{}</title>\n",
-                escape_xml(title)
-            ));
-            xml.push_str(&format!(
-                "    <desc lang=\"pt\">Clip da playlist: {}</desc>\n",
-                escape_xml(&playlist.name)
-            ));
-            xml.push_str("  </programme>\n");
-
-            current_start = current_end;
-        }
-    }
+    xml.push_str(&format!(
+        "  <programme start=\"{}\" stop=\"{}\" channel=\"{}\">\n",
+        start_fmt, end_fmt, epg_id
+    ));
+    xml.push_str(&format!(
+        "    <title lang=\"pt\">{}</title>\n",
+        escape_xml(entry.title.as_deref().unwrap_or("Sem título"))
+    ));
+    xml.push_str(&format!(
+        "    <desc lang=\"pt\">Clip da playlist: {}</desc>\n",
+        escape_xml(entry.playlist_name.as_deref().unwrap_or_default())
+    ));
+    xml.push_str("  </programme>\n");
 }
 
 fn escape_xml(s: &str) -> String {
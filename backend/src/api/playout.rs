@@ -1,8 +1,14 @@
-use crate::services::engine::PlayoutEngine;
-use actix_web::{web, HttpResponse, Responder};
-use chrono::Datelike;
+use crate::api::channels::ChannelScope;
+use crate::models::media::Media;
+use crate::services::engine::{OverrideSource, PauseHold, ResumeMode};
+use crate::services::ffmpeg;
+use crate::services::timeline::{self, EntryKind, TimelineEntry};
+use crate::services::{broadcast_day, channels, clock};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::Serialize;
-use sqlx::{PgPool, Row};
+use sqlx::PgPool;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -16,17 +22,42 @@ pub struct DebugReport {
     pub missing_media_files: Vec<String>,
     pub overlay_configured: bool,
     pub warnings: Vec<String>,
+    pub broadcast_day: Option<chrono::NaiveDate>,
+    pub occurrence_start: Option<DateTime<Utc>>,
+    pub on_air: Option<TimelineEntry>,
 }
 
-async fn get_status(engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
-    let state = engine.status.lock().await;
+/// HLS playlists (`/hls/...m3u8`), with the channel's SCTE-35 cues as CUE tags
+pub async fn hls_playlist(
+    req: HttpRequest,
+    registry: web::Data<Arc<channels::ChannelRegistry>>,
+) -> impl Responder {
+    let Some(relative) = req.path().strip_prefix("/hls/") else {
+        return HttpResponse::NotFound().finish();
+    };
+    if relative.split('/').any(|part| part == "..") {
+        return HttpResponse::NotFound().finish();
+    }
+
+    let root = registry.default_engine().channel.hls_dir();
+    match tokio::fs::read_to_string(root.join(relative)).await {
+        Ok(m3u8) => HttpResponse::Ok()
+            .content_type("application/vnd.apple.mpegurl")
+            .insert_header(("Cache-Control", "no-cache"))
+            .body(registry.for_hls_path(req.path()).decorate_hls(&m3u8)),
+        Err(_) => HttpResponse::NotFound().finish(),
+    }
+}
+
+async fn get_status(channel: ChannelScope) -> impl Responder {
+    let state = channel.engine.status.lock().await;
     HttpResponse::Ok().json(state.clone())
 }
 
-async fn start_playout(engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
+async fn start_playout(channel: ChannelScope) -> impl Responder {
     log::info!("API: Starting playout engine...");
-    engine.set_running(true).await;
-    let state = engine.status.lock().await;
+    channel.engine.set_running(true).await;
+    let state = channel.engine.status.lock().await;
 
     HttpResponse::Ok().json(serde_json::json!({
         "message": "Playout started successfully",
@@ -34,45 +65,150 @@ async fn start_playout(engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder
     }))
 }
 
-async fn stop_playout(engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
+async fn stop_playout(channel: ChannelScope) -> impl Responder {
     log::info!("API: Stopping playout engine...");
-    engine.set_running(false).await;
+    channel.engine.set_running(false).await;
 
     HttpResponse::Ok().json(serde_json::json!({
         "message": "Playout stopped successfully"
     }))
 }
 
-async fn skip_clip(engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
-    engine.skip_current_clip().await;
+async fn skip_clip(channel: ChannelScope) -> impl Responder {
+    channel.engine.skip_current_clip().await;
     HttpResponse::Ok().json(serde_json::json!({
         "message": "Skip requested"
     }))
 }
 
-async fn pause_playout(_engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
-    // TODO: Implement pause in PlayoutEngine
-    HttpResponse::Ok().json(serde_json::json!({
-        "message": "Pause requested (not implemented yet)"
-    }))
+#[derive(Debug, serde::Deserialize)]
+pub struct PauseQuery {
+    /// "freeze" (default) or "slate"
+    pub hold: Option<PauseHold>,
 }
 
-async fn resume_playout(_engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
-    // TODO: Implement resume in PlayoutEngine
-    HttpResponse::Ok().json(serde_json::json!({
-        "message": "Resume requested (not implemented yet)"
-    }))
+async fn pause_playout(channel: ChannelScope, query: web::Query<PauseQuery>) -> impl Responder {
+    let hold = query.hold.unwrap_or(PauseHold::Freeze);
+    match channel.engine.pause(hold).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Playout paused",
+            "hold": hold
+        })),
+        Err(e) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ResumeQuery {
+    /// "resume" (default, continue the clip) or "rejoin" (back to wall-clock schedule)
+    pub mode: Option<ResumeMode>,
+}
+
+async fn resume_playout(channel: ChannelScope, query: web::Query<ResumeQuery>) -> impl Responder {
+    let mode = query.mode.unwrap_or(ResumeMode::Resume);
+    match channel.engine.resume(mode).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Playout resumed",
+            "mode": mode
+        })),
+        Err(e) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct OverrideRequest {
+    /// Library item to air
+    pub media_id: Option<Uuid>,
+    /// Live stream to air instead (rtmp, rtsp, srt, udp or http)
+    pub url: Option<String>,
+    /// Seconds on air; without it the item plays once (a live stream until it ends)
+    pub duration: Option<f64>,
+}
+
+async fn start_override(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    req: web::Json<OverrideRequest>,
+) -> impl Responder {
+    if req.duration.is_some_and(|d| d <= 0.0 || !d.is_finite()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "duration must be a positive number of seconds"
+        }));
+    }
+
+    let url = req.url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    let source = match (req.media_id, url) {
+        (Some(media_id), None) => {
+            let media = sqlx::query_as::<_, Media>("SELECT * FROM media WHERE id = $1")
+                .bind(media_id)
+                .fetch_optional(pool.get_ref())
+                .await;
+            match media {
+                Ok(Some(m)) => OverrideSource {
+                    title: m
+                        .metadata
+                        .as_ref()
+                        .and_then(|meta| meta.get("title"))
+                        .and_then(|t| t.as_str())
+                        .map(String::from),
+                    input: m.path,
+                    media_id: Some(m.id),
+                },
+                Ok(None) => {
+                    return HttpResponse::NotFound().json(serde_json::json!({
+                        "error": "Media not found"
+                    }))
+                }
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .json(serde_json::json!({ "error": e.to_string() }))
+                }
+            }
+        }
+        (None, Some(url)) if ffmpeg::is_live_input(url) => OverrideSource {
+            input: url.to_string(),
+            media_id: None,
+            title: None,
+        },
+        (None, Some(_)) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "url must be an rtmp, rtsp, srt, udp or http stream"
+            }))
+        }
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Give either media_id or url"
+            }))
+        }
+    };
+
+    match channel.engine.start_override(source, req.duration).await {
+        Ok(info) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Override on air",
+            "override": info
+        })),
+        Err(e) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    }
+}
+
+async fn end_override(channel: ChannelScope) -> impl Responder {
+    match channel.engine.end_override().await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Override ended"
+        })),
+        Err(e) => HttpResponse::Conflict().json(serde_json::json!({ "error": e })),
+    }
 }
 
-async fn get_logs(engine: web::Data<Arc<PlayoutEngine>>) -> impl Responder {
-    let logs = engine.logs.lock().await;
+async fn get_logs(channel: ChannelScope) -> impl Responder {
+    let logs = channel.engine.logs.lock().await;
     let logs_vec: Vec<String> = logs.iter().cloned().collect();
     HttpResponse::Ok().json(serde_json::json!({
         "logs": logs_vec
     }))
 }
 
-async fn diagnose_playout(pool: web::Data<PgPool>) -> impl Responder {
+async fn diagnose_playout(channel: ChannelScope, pool: web::Data<PgPool>) -> impl Responder {
     let mut report = DebugReport {
         has_active_schedule: false,
         active_schedule_id: None,
@@ -82,120 +218,76 @@ async fn diagnose_playout(pool: web::Data<PgPool>) -> impl Responder {
         missing_media_files: Vec::new(),
         overlay_configured: false,
         warnings: Vec::new(),
+        broadcast_day: None,
+        occurrence_start: None,
+        on_air: None,
     };
 
-    // Check schedule using actual schema (date, start_time, repeat_pattern)
-    let now_dt = chrono::Local::now();
-    let today = now_dt.date_naive();
-
-    // First, check for a direct schedule for today
-    let direct_schedule =
-        sqlx::query("SELECT id, playlist_id FROM schedule WHERE date = $1 LIMIT 1")
-            .bind(today)
-            .fetch_optional(pool.get_ref())
-            .await;
-
-    let mut found_playlist_id: Option<Uuid> = None;
-
-    if let Ok(Some(row)) = direct_schedule {
-        report.has_active_schedule = true;
-        report.active_schedule_id = Some(row.get("id"));
-        found_playlist_id = Some(row.get("playlist_id"));
-        report.playlist_id = found_playlist_id;
-    }
-
-    // If no direct schedule, check for repeating schedules
-    if !report.has_active_schedule {
-        // Check daily repeats
-        let daily_schedule = sqlx::query("SELECT id, playlist_id FROM schedule WHERE repeat_pattern = 'daily' AND date <= $1 ORDER BY date DESC LIMIT 1")
-            .bind(today)
-            .fetch_optional(pool.get_ref())
-            .await;
-
-        if let Ok(Some(row)) = daily_schedule {
-            report.has_active_schedule = true;
-            report.active_schedule_id = Some(row.get("id"));
-            found_playlist_id = Some(row.get("playlist_id"));
-            report.playlist_id = found_playlist_id;
-        }
-
-        // Check weekly repeats if still not found
-        if !report.has_active_schedule {
-            let day_of_week = today.weekday().num_days_from_monday();
-            let weekly_schedule = sqlx::query("SELECT id, playlist_id FROM schedule WHERE repeat_pattern = 'weekly' AND EXTRACT(DOW FROM date) = $1 AND date <= $2 ORDER BY date DESC LIMIT 1")
-                .bind(day_of_week as i32)
-                .bind(today)
-                .fetch_optional(pool.get_ref())
-                .await;
+    // Resolve the on-air schedule exactly like the engine (broadcast day anchored on day_start)
+    let now = Utc::now();
+    let settings = channels::load_settings(pool.get_ref(), channel.id())
+        .await
+        .ok();
+    let day_start = settings
+        .as_ref()
+        .map(broadcast_day::day_start)
+        .unwrap_or_else(|| chrono::NaiveTime::from_hms_opt(6, 0, 0).unwrap());
+    let tz = settings
+        .as_ref()
+        .map(clock::channel_tz)
+        .unwrap_or_else(clock::host_tz);
+    report.broadcast_day = Some(broadcast_day::broadcast_date(now, day_start, &tz));
 
-            if let Ok(Some(row)) = weekly_schedule {
-                report.has_active_schedule = true;
-                report.active_schedule_id = Some(row.get("id"));
-                found_playlist_id = Some(row.get("playlist_id"));
-                report.playlist_id = found_playlist_id;
-            }
-        }
-    }
+    // Same rundown the engine plays from
+    let window_start = now - chrono::Duration::days(1);
+    let window_end = now + chrono::Duration::days(1);
+    match broadcast_day::load_timeline(pool.get_ref(), channel.id(), window_start, window_end, &tz)
+        .await
+    {
+        Ok(input) => {
+            let rundown = timeline::resolve(&input, window_start, window_end);
+            match timeline::at(&rundown, now).filter(|e| e.kind == EntryKind::Clip) {
+                Some(entry) => {
+                    report.has_active_schedule = true;
+                    report.active_schedule_id = entry.schedule_id;
+                    report.occurrence_start = entry.occurrence_start;
+                    report.playlist_id = entry.playlist_id;
 
-    // Check playlist if we found a schedule
-    if let Some(playlist_id) = found_playlist_id {
-        let playlist = sqlx::query("SELECT content FROM playlists WHERE id = $1")
-            .bind(playlist_id)
-            .fetch_optional(pool.get_ref())
-            .await;
-
-        match playlist {
-            Ok(Some(row)) => {
-                report.has_playlist = true;
-                let content: serde_json::Value = row.get("content");
-
-                // Check media files
-                if let Some(clips) = content.as_array() {
-                    report.media_files_count = clips.len() as i32;
-                    for clip in clips {
-                        if let Some(path) = clip
-                            .get("path")
-                            .or(clip.get("source"))
-                            .and_then(|v| v.as_str())
-                        {
-                            if !std::path::Path::new(path).exists() {
-                                report.missing_media_files.push(path.to_string());
-                            }
-                        }
-                    }
-                } else if let Some(program) = content.get("program").and_then(|p| p.as_array()) {
-                    report.media_files_count = program.len() as i32;
-                    for clip in program {
-                        if let Some(path) = clip
-                            .get("path")
-                            .or(clip.get("source"))
-                            .and_then(|v| v.as_str())
-                        {
-                            if !std::path::Path::new(path).exists() {
-                                report.missing_media_files.push(path.to_string());
+                    match entry.playlist_id.and_then(|id| input.playlists.get(&id)) {
+                        Some(items) => {
+                            report.has_playlist = true;
+                            report.media_files_count = items.len() as i32;
+                            for item in items {
+                                let path = item.get_path();
+                                if !path.is_empty()
+                                    && item.slideshow.is_none()
+                                    && !std::path::Path::new(&path).exists()
+                                {
+                                    report.missing_media_files.push(path);
+                                }
                             }
                         }
+                        None => report
+                            .warnings
+                            .push("Playlist associada não encontrada".to_string()),
                     }
+                    report.on_air = Some(entry.clone());
                 }
+                None => report
+                    .warnings
+                    .push("Nenhum horário ativo encontrado para este momento".to_string()),
             }
-            _ => report
-                .warnings
-                .push("Playlist associada não encontrada".to_string()),
         }
-    } else {
-        report
+        Err(e) => report
             .warnings
-            .push("Nenhum horário ativo encontrado para este momento".to_string());
+            .push(format!("Erro ao consultar horários: {}", e)),
     }
 
     // Check overlay
-    let settings =
-        sqlx::query("SELECT logo_path FROM settings WHERE id = TRUE AND overlay_enabled = TRUE")
-            .fetch_optional(pool.get_ref())
-            .await;
-
-    if let Ok(Some(row)) = settings {
-        let logo_path: String = row.get("logo_path");
+    if let Some(logo_path) = settings
+        .filter(|s| s.overlay_enabled)
+        .map(|s| s.logo_path.unwrap_or_default())
+    {
         if !logo_path.is_empty() && std::path::Path::new(&logo_path).exists() {
             report.overlay_configured = true;
         } else {
@@ -208,6 +300,112 @@ async fn diagnose_playout(pool: web::Data<PgPool>) -> impl Responder {
     HttpResponse::Ok().json(report)
 }
 
+/// Longest window the timeline endpoint resolves in one call
+const MAX_TIMELINE_DAYS: i64 = 31;
+
+// Query time: RFC 3339, or a naive wall-clock time read in the channel zone
+fn parse_time(
+    tz: &Tz,
+    name: &str,
+    value: &Option<String>,
+) -> Result<Option<DateTime<Utc>>, HttpResponse> {
+    match value.as_deref() {
+        None => Ok(None),
+        Some(raw) => clock::parse_instant(tz, raw).map(Some).ok_or_else(|| {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Invalid '{}': use RFC 3339 or YYYY-MM-DDTHH:MM:SS", name)
+            }))
+        }),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TimelineQuery {
+    /// Defaults to now
+    pub from: Option<String>,
+    /// Defaults to 24 hours after `from`
+    pub to: Option<String>,
+}
+
+async fn get_timeline(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    query: web::Query<TimelineQuery>,
+) -> impl Responder {
+    let tz = clock::load_tz(pool.get_ref(), channel.id()).await;
+    let (from, to) = match (
+        parse_time(&tz, "from", &query.from),
+        parse_time(&tz, "to", &query.to),
+    ) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    let from = from.unwrap_or_else(Utc::now);
+    let to = to.unwrap_or(from + chrono::Duration::days(1));
+    if to <= from {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "'to' must be after 'from'"
+        }));
+    }
+    if to - from > chrono::Duration::days(MAX_TIMELINE_DAYS) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Timeline window is limited to {} days", MAX_TIMELINE_DAYS)
+        }));
+    }
+
+    match broadcast_day::load_timeline(pool.get_ref(), channel.id(), from, to, &tz).await {
+        Ok(input) => HttpResponse::Ok().json(serde_json::json!({
+            "from": from,
+            "to": to,
+            "timezone": tz.name(),
+            "entries": timeline::resolve(&input, from, to)
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AtQuery {
+    /// Defaults to now
+    pub t: Option<String>,
+}
+
+// Entries following the one on air in the `at` response
+const AT_UPCOMING: usize = 5;
+
+async fn get_at(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    query: web::Query<AtQuery>,
+) -> impl Responder {
+    let tz = clock::load_tz(pool.get_ref(), channel.id()).await;
+    let t = match parse_time(&tz, "t", &query.t) {
+        Ok(t) => t.unwrap_or_else(Utc::now),
+        Err(e) => return e,
+    };
+    let from = t - chrono::Duration::days(1);
+    let to = t + chrono::Duration::days(1);
+
+    match broadcast_day::load_timeline(pool.get_ref(), channel.id(), from, to, &tz).await {
+        Ok(input) => {
+            let rundown = timeline::resolve(&input, from, to);
+            let entry = timeline::at(&rundown, t);
+            let upcoming: Vec<&TimelineEntry> = rundown
+                .iter()
+                .filter(|e| e.start > t)
+                .take(AT_UPCOMING)
+                .collect();
+            HttpResponse::Ok().json(serde_json::json!({
+                "t": t,
+                "entry": entry,
+                "position": entry.map(|e| e.position_at(t)),
+                "upcoming": upcoming
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct ProtocolToggleRequest {
     pub protocol: String,
@@ -215,10 +413,10 @@ pub struct ProtocolToggleRequest {
 }
 
 async fn toggle_protocol(
-    engine: web::Data<Arc<PlayoutEngine>>,
+    channel: ChannelScope,
     req: web::Json<ProtocolToggleRequest>,
 ) -> impl Responder {
-    match engine.toggle_protocol(&req.protocol, req.enabled).await {
+    match channel.engine.toggle_protocol(&req.protocol, req.enabled).await {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({
             "message": format!("Protocol {} {}", req.protocol, if req.enabled { "enabled" } else { "disabled" })
         })),
@@ -233,7 +431,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .route("/skip", web::post().to(skip_clip))
         .route("/pause", web::post().to(pause_playout))
         .route("/resume", web::post().to(resume_playout))
+        .route("/override", web::post().to(start_override))
+        .route("/override", web::delete().to(end_override))
         .route("/diagnose", web::get().to(diagnose_playout))
+        .route("/timeline", web::get().to(get_timeline))
+        .route("/at", web::get().to(get_at))
         .route("/protocol/toggle", web::post().to(toggle_protocol))
         .route("/logs", web::get().to(get_logs));
 }
@@ -0,0 +1,151 @@
+use crate::api::channels::ChannelScope;
+use crate::models::as_run::AsRunEntry;
+use crate::services::clock;
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct AsRunQuery {
+    /// First day (YYYY-MM-DD, channel time), defaults to today
+    pub from: Option<NaiveDate>,
+    /// Last day, inclusive, defaults to `from`
+    pub to: Option<NaiveDate>,
+    pub schedule_id: Option<Uuid>,
+    pub playlist_id: Option<Uuid>,
+    /// "schedule", "filler" or "override"
+    pub source: Option<String>,
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+    pub limit: Option<i64>,
+}
+
+const DEFAULT_LIMIT: i64 = 10_000;
+
+async fn get_as_run(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    query: web::Query<AsRunQuery>,
+) -> impl Responder {
+    let tz = clock::load_tz(pool.get_ref(), channel.id()).await;
+    let from = query.from.unwrap_or_else(|| clock::today(&tz));
+    let to = query.to.unwrap_or(from);
+    if to < from {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "'to' must not be before 'from'"
+        }));
+    }
+
+    let result = sqlx::query_as::<_, AsRunEntry>(
+        "SELECT * FROM as_run
+         WHERE actual_start >= $1 AND actual_start < $2 AND channel_id = $7
+         AND ($3::uuid IS NULL OR schedule_id = $3)
+         AND ($4::uuid IS NULL OR playlist_id = $4)
+         AND ($5::text IS NULL OR source = $5)
+         ORDER BY actual_start ASC
+         LIMIT $6",
+    )
+    .bind(clock::to_utc(&tz, from.and_hms_opt(0, 0, 0).unwrap()))
+    .bind(clock::to_utc(
+        &tz,
+        (to + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap(),
+    ))
+    .bind(query.schedule_id)
+    .bind(query.playlist_id)
+    .bind(&query.source)
+    .bind(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, DEFAULT_LIMIT))
+    .bind(channel.id())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    let entries = match result {
+        Ok(entries) => entries,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({"error": e.to_string()}))
+        }
+    };
+
+    if query.format.as_deref() == Some("csv") {
+        return HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"as-run_{}_{}.csv\"", from, to),
+            ))
+            .body(to_csv(&entries, &tz));
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "from": from,
+        "to": to,
+        "timezone": tz.name(),
+        "count": entries.len(),
+        "entries": entries
+    }))
+}
+
+// Times in channel wall-clock with their UTC offset, unambiguous across DST changes
+fn to_csv(entries: &[AsRunEntry], tz: &Tz) -> String {
+    let mut csv = String::from(
+        "actual_start,actual_end,planned_start,planned_end,duration,source,title,filename,\
+         clip_path,media_id,playlist_id,playlist_name,schedule_id,occurrence_date,end_reason\n",
+    );
+    let time = |t: Option<DateTime<Utc>>| {
+        t.map(|t| {
+            t.with_timezone(tz)
+                .format("%Y-%m-%d %H:%M:%S%.3f%:z")
+                .to_string()
+        })
+        .unwrap_or_default()
+    };
+    let text = |v: Option<String>| v.unwrap_or_default();
+
+    for e in entries {
+        let duration = e
+            .actual_end
+            .map(|end| {
+                format!(
+                    "{:.3}",
+                    (end - e.actual_start).num_milliseconds() as f64 / 1000.0
+                )
+            })
+            .unwrap_or_default();
+        let fields = [
+            time(Some(e.actual_start)),
+            time(e.actual_end),
+            time(e.planned_start),
+            time(e.planned_end),
+            duration,
+            e.source.clone(),
+            text(e.title.clone()),
+            text(e.filename.clone()),
+            e.clip_path.clone(),
+            text(e.media_id.map(|id| id.to_string())),
+            text(e.playlist_id.map(|id| id.to_string())),
+            text(e.playlist_name.clone()),
+            text(e.schedule_id.map(|id| id.to_string())),
+            text(e.occurrence_date.map(|d| d.to_string())),
+            text(e.end_reason.clone()),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| escape_csv(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/as-run", web::get().to(get_as_run));
+}
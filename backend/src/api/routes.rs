@@ -6,11 +6,22 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(web::scope("/auth").configure(crate::api::auth::configure))
             .service(web::scope("/health").configure(crate::api::health::configure))
             .service(web::scope("/media").configure(crate::api::media::configure))
-            .service(web::scope("/playlists").configure(crate::api::playlists::configure))
-            .service(web::scope("/playout").configure(crate::api::playout::configure))
-            .service(web::scope("/schedule").configure(crate::api::schedule::configure))
-            .service(web::scope("/settings").configure(crate::api::settings::configure))
             .service(web::scope("/templates").configure(crate::api::templates::configure))
-            .service(web::scope("/protected").configure(crate::api::protected::configure)),
+            .service(web::scope("/protected").configure(crate::api::protected::configure))
+            .service(web::scope("/channels").configure(crate::api::channels::configure))
+            // Unscoped routes act on the default channel
+            .configure(configure_channel),
     );
 }
+
+/// Routes that belong to one channel, mounted at `/api` (default channel) and
+/// `/api/channels/{channel}`
+pub fn configure_channel(cfg: &mut web::ServiceConfig) {
+    cfg.route("/ws", web::get().to(crate::api::ws::ws_handler))
+        .service(web::scope("/graphics").configure(crate::api::graphics::configure))
+        .service(web::scope("/playlists").configure(crate::api::playlists::configure))
+        .service(web::scope("/playout").configure(crate::api::playout::configure))
+        .service(web::scope("/schedule").configure(crate::api::schedule::configure))
+        .service(web::scope("/settings").configure(crate::api::settings::configure))
+        .service(web::scope("/reports").configure(crate::api::reports::configure));
+}
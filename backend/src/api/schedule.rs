@@ -1,10 +1,11 @@
 use actix_web::{web, HttpResponse, Responder};
-use chrono::Datelike;
 use serde::Deserialize;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::schedule::{CreateSchedule, Schedule};
+use crate::api::channels::{ChannelScope, ItemPath};
+use crate::models::schedule::{normalize_repeat_pattern, CreateSchedule, RecurrenceRule, Schedule};
+use crate::services::{broadcast_day, clock};
 
 #[derive(Deserialize)]
 pub struct ScheduleQuery {
@@ -13,6 +14,7 @@ pub struct ScheduleQuery {
 }
 
 async fn list_schedule(
+    channel: ChannelScope,
     query: web::Query<ScheduleQuery>,
     pool: web::Data<PgPool>,
 ) -> impl Responder {
@@ -20,8 +22,9 @@ async fn list_schedule(
         "SELECT s.*, p.name as playlist_name 
          FROM schedule s 
          JOIN playlists p ON s.playlist_id = p.id 
-         WHERE 1=1",
+         WHERE s.channel_id = ",
     );
+    query_builder.push_bind(channel.id());
 
     if let Some(ref start_date) = query.start_date {
         if let Ok(date) = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d") {
@@ -62,21 +65,99 @@ pub struct CreateScheduleRequest {
     pub date: String,
     pub start_time: Option<String>,
     pub repeat_pattern: Option<String>,
+    pub recurrence: Option<String>,
+    pub priority: Option<i32>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+// Conflicts reported in a 409 response (the total is returned alongside)
+const MAX_REPORTED_CONFLICTS: usize = 50;
+
+// Airtime of a playlist on the channel; None when the playlist does not exist
+async fn playlist_duration(
+    pool: &PgPool,
+    channel_id: Uuid,
+    playlist_id: Uuid,
+) -> Result<Option<f64>, String> {
+    broadcast_day::playlist_airtime(pool, channel_id, &[playlist_id])
+        .await
+        .map(|airtime| airtime.get(&playlist_id).copied())
+}
+
+// Returns a 409 response when the candidate overlaps other entries and saving is not forced
+async fn check_conflicts(
+    pool: &PgPool,
+    candidate: &Schedule,
+    force: bool,
+) -> Result<(), HttpResponse> {
+    let tz = clock::load_tz(pool, candidate.channel_id).await;
+    let conflicts = match broadcast_day::find_conflicts(pool, candidate, &tz).await {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to check schedule conflicts: {}", e);
+            return Err(HttpResponse::InternalServerError()
+                .json(serde_json::json!({"error": "Failed to check schedule conflicts"})));
+        }
+    };
+
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+
+    if force {
+        log::warn!(
+            "Schedule saved with {} overlapping occurrence(s) (priority {})",
+            conflicts.len(),
+            candidate.priority
+        );
+        return Ok(());
+    }
+
+    Err(HttpResponse::Conflict().json(serde_json::json!({
+        "error": "Schedule overlaps existing entries. Resubmit with force=true (priority decides which entry airs).",
+        "conflict_count": conflicts.len(),
+        "conflicts": conflicts.iter().take(MAX_REPORTED_CONFLICTS).collect::<Vec<_>>()
+    })))
+}
+
+// Validate an RRULE and derive the stored (repeat_pattern, recurrence) pair.
+// Older clients only send repeat_pattern; 'none' is stored as NULL.
+fn resolve_recurrence(
+    repeat_pattern: Option<&str>,
+    recurrence: Option<&str>,
+) -> Result<(Option<String>, Option<String>), String> {
+    match recurrence.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(rrule) => {
+            let rule = RecurrenceRule::parse(rrule)?;
+            Ok((
+                Some(rule.repeat_pattern().to_string()),
+                Some(rule.to_string()),
+            ))
+        }
+        None => {
+            let pattern = normalize_repeat_pattern(repeat_pattern);
+            if let Some(ref p) = pattern {
+                RecurrenceRule::parse(p)?;
+            }
+            Ok((pattern, None))
+        }
+    }
 }
 
 async fn create_schedule(
+    channel: ChannelScope,
     req: web::Json<CreateScheduleRequest>,
     pool: web::Data<PgPool>,
 ) -> impl Responder {
     // Verify playlist exists
-    let playlist_check = sqlx::query("SELECT id FROM playlists WHERE id = $1")
-        .bind(&req.playlist_id)
-        .fetch_optional(pool.get_ref())
-        .await;
-
-    if playlist_check.is_err() || playlist_check.unwrap().is_none() {
-        return HttpResponse::BadRequest().json(serde_json::json!({"error": "Playlist not found"}));
-    }
+    let duration = match playlist_duration(pool.get_ref(), channel.id(), req.playlist_id).await {
+        Ok(Some(d)) => d,
+        _ => {
+            return HttpResponse::BadRequest()
+                .json(serde_json::json!({"error": "Playlist not found"}))
+        }
+    };
 
     let date = match chrono::NaiveDate::parse_from_str(&req.date, "%Y-%m-%d") {
         Ok(d) => d,
@@ -92,15 +173,45 @@ async fn create_schedule(
             .or_else(|| chrono::NaiveTime::parse_from_str(t, "%H:%M").ok())
     });
 
+    let (repeat_pattern, recurrence) =
+        match resolve_recurrence(req.repeat_pattern.as_deref(), req.recurrence.as_deref()) {
+            Ok(r) => r,
+            Err(e) => {
+                return HttpResponse::BadRequest()
+                    .json(serde_json::json!({"error": format!("Invalid recurrence: {}", e)}))
+            }
+        };
+
+    let priority = req.priority.unwrap_or(0);
+    let candidate = Schedule {
+        id: Uuid::nil(),
+        channel_id: channel.id(),
+        playlist_id: req.playlist_id,
+        date,
+        start_time,
+        repeat_pattern: repeat_pattern.clone(),
+        recurrence: recurrence.clone(),
+        priority,
+        playlist_name: None,
+        playlist_duration: Some(duration),
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(response) = check_conflicts(pool.get_ref(), &candidate, req.force).await {
+        return response;
+    }
+
     let result = sqlx::query_as::<_, Schedule>(
-        "INSERT INTO schedule (playlist_id, date, start_time, repeat_pattern) 
-         VALUES ($1, $2, $3, $4) 
+        "INSERT INTO schedule (channel_id, playlist_id, date, start_time, repeat_pattern, recurrence, priority) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7) 
          RETURNING *",
     )
+    .bind(channel.id())
     .bind(&req.playlist_id)
     .bind(date)
     .bind(start_time)
-    .bind(&req.repeat_pattern)
+    .bind(&repeat_pattern)
+    .bind(&recurrence)
+    .bind(priority)
     .fetch_one(pool.get_ref())
     .await;
 
@@ -114,11 +225,16 @@ async fn create_schedule(
     }
 }
 
-async fn delete_schedule(schedule_id: web::Path<Uuid>, pool: web::Data<PgPool>) -> impl Responder {
-    let id = schedule_id.into_inner();
+async fn delete_schedule(
+    channel: ChannelScope,
+    path: web::Path<ItemPath>,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
+    let id = path.id;
     log::info!("Deleting schedule item: {}", id);
-    let result = sqlx::query("DELETE FROM schedule WHERE id = $1")
+    let result = sqlx::query("DELETE FROM schedule WHERE id = $1 AND channel_id = $2")
         .bind(id)
+        .bind(channel.id())
         .execute(pool.get_ref())
         .await;
 
@@ -145,7 +261,11 @@ pub struct BulkDeleteRequest {
     pub end_date: String,
 }
 
-async fn delete_bulk(req: web::Json<BulkDeleteRequest>, pool: web::Data<PgPool>) -> impl Responder {
+async fn delete_bulk(
+    channel: ChannelScope,
+    req: web::Json<BulkDeleteRequest>,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
     let start = match chrono::NaiveDate::parse_from_str(&req.start_date, "%Y-%m-%d") {
         Ok(d) => d,
         Err(_) => {
@@ -161,11 +281,13 @@ async fn delete_bulk(req: web::Json<BulkDeleteRequest>, pool: web::Data<PgPool>)
         }
     };
 
-    let result = sqlx::query("DELETE FROM schedule WHERE date >= $1 AND date <= $2")
-        .bind(start)
-        .bind(end)
-        .execute(pool.get_ref())
-        .await;
+    let result =
+        sqlx::query("DELETE FROM schedule WHERE date >= $1 AND date <= $2 AND channel_id = $3")
+            .bind(start)
+            .bind(end)
+            .bind(channel.id())
+            .execute(pool.get_ref())
+            .await;
 
     match result {
         Ok(res) => HttpResponse::Ok().json(serde_json::json!({
@@ -178,20 +300,81 @@ async fn delete_bulk(req: web::Json<BulkDeleteRequest>, pool: web::Data<PgPool>)
 }
 
 async fn update_schedule(
-    schedule_id: web::Path<Uuid>,
+    channel: ChannelScope,
+    path: web::Path<ItemPath>,
     schedule_data: web::Json<CreateSchedule>,
     pool: web::Data<PgPool>,
 ) -> impl Responder {
+    let (repeat_pattern, recurrence) = match resolve_recurrence(
+        schedule_data.repeat_pattern.as_deref(),
+        schedule_data.recurrence.as_deref(),
+    ) {
+        Ok(r) => r,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .json(serde_json::json!({"error": format!("Invalid recurrence: {}", e)}))
+        }
+    };
+
+    let id = path.id;
+    let existing_priority: Option<i32> =
+        match sqlx::query_scalar("SELECT priority FROM schedule WHERE id = $1 AND channel_id = $2")
+            .bind(id)
+            .bind(channel.id())
+            .fetch_optional(pool.get_ref())
+            .await
+        {
+            Ok(p) => p,
+            Err(e) => {
+                log::error!("Failed to update schedule: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(serde_json::json!({"error": "Failed to update schedule"}));
+            }
+        };
+    let Some(existing_priority) = existing_priority else {
+        return HttpResponse::NotFound().json(serde_json::json!({"error": "Schedule not found"}));
+    };
+
+    let duration =
+        match playlist_duration(pool.get_ref(), channel.id(), schedule_data.playlist_id).await {
+            Ok(Some(d)) => d,
+            _ => {
+                return HttpResponse::BadRequest()
+                    .json(serde_json::json!({"error": "Playlist not found"}))
+            }
+        };
+
+    let priority = schedule_data.priority.unwrap_or(existing_priority);
+    let candidate = Schedule {
+        id,
+        channel_id: channel.id(),
+        playlist_id: schedule_data.playlist_id,
+        date: schedule_data.date,
+        start_time: schedule_data.start_time,
+        repeat_pattern: repeat_pattern.clone(),
+        recurrence: recurrence.clone(),
+        priority,
+        playlist_name: None,
+        playlist_duration: Some(duration),
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(response) = check_conflicts(pool.get_ref(), &candidate, schedule_data.force).await {
+        return response;
+    }
+
     let result = sqlx::query(
         "UPDATE schedule 
-         SET playlist_id = $1, date = $2, start_time = $3, repeat_pattern = $4 
-         WHERE id = $5",
+         SET playlist_id = $1, date = $2, start_time = $3, repeat_pattern = $4, recurrence = $5, priority = $6 
+         WHERE id = $7 AND channel_id = $8",
     )
     .bind(schedule_data.playlist_id)
     .bind(schedule_data.date)
     .bind(schedule_data.start_time)
-    .bind(&schedule_data.repeat_pattern)
-    .bind(schedule_id.into_inner())
+    .bind(&repeat_pattern)
+    .bind(&recurrence)
+    .bind(priority)
+    .bind(id)
+    .bind(channel.id())
     .execute(pool.get_ref())
     .await;
 
@@ -214,6 +397,7 @@ pub struct GetPlaylistForDateRequest {
 }
 
 async fn get_playlist_for_date(
+    channel: ChannelScope,
     req: web::Query<GetPlaylistForDateRequest>,
     pool: web::Data<PgPool>,
 ) -> impl Responder {
@@ -225,65 +409,35 @@ async fn get_playlist_for_date(
         }
     };
 
-    // First, try to find a direct schedule for this date
-    let direct_schedule = sqlx::query(
-        "SELECT p.* FROM playlists p 
-         JOIN schedule s ON p.id = s.playlist_id 
-         WHERE s.date = $1 AND s.repeat_pattern IS NULL 
-         LIMIT 1",
+    // Resolve through the shared recurrence expander
+    let tz = clock::load_tz(pool.get_ref(), channel.id()).await;
+    let occurrences = match broadcast_day::load_occurrences(
+        pool.get_ref(),
+        channel.id(),
+        date,
+        date,
+        &tz,
     )
-    .bind(date)
-    .fetch_optional(pool.get_ref())
-    .await;
-
-    if let Ok(Some(_)) = direct_schedule {
-        return HttpResponse::Ok().json(serde_json::json!({
-            "found": true,
-            "source": "direct_schedule"
-        }));
-    }
-
-    // Check for repeating schedules
-    let day_of_week = date.weekday().num_days_from_monday();
-
-    // Check daily repeats
-    let daily_schedule = sqlx::query(
-        "SELECT p.* FROM playlists p 
-         JOIN schedule s ON p.id = s.playlist_id 
-         WHERE s.repeat_pattern = 'daily' AND s.date <= $1 
-         ORDER BY s.date DESC 
-         LIMIT 1",
-    )
-    .bind(date)
-    .fetch_optional(pool.get_ref())
-    .await;
-
-    if let Ok(Some(_)) = daily_schedule {
-        return HttpResponse::Ok().json(serde_json::json!({
-            "found": true,
-            "source": "daily_repeat"
-        }));
-    }
-
-    // Check weekly repeats (same day of week)
-    let weekly_schedule = sqlx::query(
-        "SELECT p.* FROM playlists p 
-         JOIN schedule s ON p.id = s.playlist_id 
-         WHERE s.repeat_pattern = 'weekly' 
-         AND EXTRACT(DOW FROM s.date) = $1 
-         AND s.date <= $2 
-         ORDER BY s.date DESC 
-         LIMIT 1",
-    )
-    .bind(day_of_week as i32)
-    .bind(date)
-    .fetch_optional(pool.get_ref())
-    .await;
+    .await
+    {
+        Ok(o) => o,
+        Err(e) => {
+            log::error!("Failed to expand schedule for {}: {}", date, e);
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({"error": "Failed to fetch schedule"}));
+        }
+    };
 
-    if let Ok(Some(_)) = weekly_schedule {
+    if let Some(occ) = occurrences.first() {
+        let source = match occ.schedule.rule() {
+            None => "direct_schedule".to_string(),
+            Some(rule) => format!("{}_repeat", rule.repeat_pattern()),
+        };
         return HttpResponse::Ok().json(serde_json::json!({
             "found": true,
-            "source": "weekly_repeat"
+            "source": source,
+            "schedule_id": occ.schedule.id,
+            "playlist_id": occ.schedule.playlist_id
         }));
     }
 
@@ -300,6 +454,7 @@ pub struct CreateExceptionRequest {
 }
 
 async fn add_exception(
+    channel: ChannelScope,
     req: web::Json<CreateExceptionRequest>,
     pool: web::Data<PgPool>,
 ) -> impl Responder {
@@ -311,13 +466,15 @@ async fn add_exception(
         }
     };
 
+    // Only schedules of this channel
     let result = sqlx::query(
         "INSERT INTO schedule_exceptions (schedule_id, exception_date) 
-         VALUES ($1, $2) 
+         SELECT id, $2 FROM schedule WHERE id = $1 AND channel_id = $3
          ON CONFLICT (schedule_id, exception_date) DO NOTHING",
     )
     .bind(&req.schedule_id)
     .bind(date)
+    .bind(channel.id())
     .execute(pool.get_ref())
     .await;
 
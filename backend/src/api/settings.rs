@@ -1,22 +1,25 @@
+use crate::api::channels::ChannelScope;
 use crate::models::settings::{Settings, UpdateSettingsRequest};
+use crate::services::{clock, loudness};
 use actix_files::NamedFile;
 use actix_multipart::Multipart;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use base64::{engine::general_purpose, Engine as _};
 use futures::TryStreamExt;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use sqlx::{PgPool, Row};
 use std::io::Write;
 use std::path::Path;
 
-async fn get_settings(pool: web::Data<PgPool>) -> impl Responder {
+async fn get_settings(channel: ChannelScope, pool: web::Data<PgPool>) -> impl Responder {
     let assets_path = std::env::var("ASSETS_PATH")
         .unwrap_or_else(|_| "/var/lib/onepa-playout/assets".to_string());
     let protected_path = format!("{}/protected", assets_path);
     let docs_path = std::env::var("DOCS_PATH").unwrap_or_else(|_| "/app/docs".to_string());
 
-    let result = sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE id = TRUE")
+    let result = sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE channel_id = $1")
+        .bind(channel.id())
         .fetch_optional(pool.get_ref())
         .await;
 
@@ -47,14 +50,17 @@ async fn get_settings(pool: web::Data<PgPool>) -> impl Responder {
         Ok(None) => {
             // Insert default settings
             let _ = sqlx::query(
-                "INSERT INTO settings (id, output_url) VALUES (TRUE, 'rtmp://localhost:1935/stream')"
+                "INSERT INTO settings (channel_id, id, output_url) VALUES ($1, $2, 'rtmp://localhost:1935/stream')"
             )
+            .bind(channel.id())
+            .bind(channel.channel().is_default)
             .execute(pool.get_ref())
             .await;
 
             // Return default structure (or re-fetch)
             HttpResponse::Ok().json(Settings {
-                id: true,
+                id: channel.channel().is_default,
+                channel_id: channel.id(),
                 output_type: "rtmp".to_string(),
                 output_url: "rtmp://localhost:1935/stream".to_string(),
                 resolution: "1920x1080".to_string(),
@@ -124,6 +130,29 @@ async fn get_settings(pool: web::Data<PgPool>) -> impl Responder {
                 tmdb_api_key: None,
                 omdb_api_key: None,
                 tvmaze_api_key: None,
+                timezone: None,
+                scte35_enabled: false,
+                ticker_enabled: false,
+                ticker_speed: Some(120),
+                ticker_position: Some("bottom".to_string()),
+                text_enabled: false,
+                text_anchor: Some("top-left".to_string()),
+                text_font_path: None,
+                text_font_size: Some(36),
+                text_color: Some("white".to_string()),
+                text_box: true,
+                text_box_color: Some("black@0.6".to_string()),
+                upnext_enabled: false,
+                upnext_lead: Some(10),
+                upnext_label: Some("Up next".to_string()),
+                clock_mode: Some("off".to_string()),
+                clock_anchor: Some("bottom-right".to_string()),
+                loudness_target: Some(loudness::DEFAULT_TARGET_LUFS),
+                transition_type: Some("cut".to_string()),
+                transition_duration: Some(1.0),
+                audio_background_path: None,
+                audio_visualizer: Some("none".to_string()),
+                audio_now_playing: false,
             })
         }
         Err(_) => HttpResponse::InternalServerError()
@@ -132,10 +161,46 @@ async fn get_settings(pool: web::Data<PgPool>) -> impl Responder {
 }
 
 async fn update_settings(
+    channel: ChannelScope,
     req: web::Json<UpdateSettingsRequest>,
     pool: web::Data<PgPool>,
 ) -> impl Responder {
     log::info!("Received update_settings request: {:?}", req);
+    if let Some(tz) = req.timezone.as_deref().filter(|tz| !tz.is_empty()) {
+        if clock::parse_tz(tz).is_none() {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Unknown timezone '{}' (expected an IANA name like Europe/Lisbon)", tz)
+            }));
+        }
+    }
+    if let Some(target) = req.loudness_target {
+        if !(-40.0..=-5.0).contains(&target) {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Loudness target must be between -40 and -5 LUFS"
+            }));
+        }
+    }
+    if let Some(kind) = req.transition_type.as_deref() {
+        if !["cut", "fade", "crossfade"].contains(&kind) {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Transition must be cut, fade or crossfade"
+            }));
+        }
+    }
+    if let Some(duration) = req.transition_duration {
+        if !(0.0..=10.0).contains(&duration) {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Transition duration must be between 0 and 10 seconds"
+            }));
+        }
+    }
+    if let Some(visualizer) = req.audio_visualizer.as_deref() {
+        if !["none", "waves", "spectrum"].contains(&visualizer) {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Audio visualizer must be none, waves or spectrum"
+            }));
+        }
+    }
     let mut sql = String::from("UPDATE settings SET updated_at = CURRENT_TIMESTAMP");
     let mut counter = 1;
 
@@ -203,8 +268,31 @@ async fn update_settings(
     add_field!(req.tmdb_api_key, "tmdb_api_key");
     add_field!(req.omdb_api_key, "omdb_api_key");
     add_field!(req.tvmaze_api_key, "tvmaze_api_key");
-
-    sql.push_str(" WHERE id = TRUE");
+    add_field!(req.timezone, "timezone");
+    add_field!(req.scte35_enabled, "scte35_enabled");
+    add_field!(req.ticker_enabled, "ticker_enabled");
+    add_field!(req.ticker_speed, "ticker_speed");
+    add_field!(req.ticker_position, "ticker_position");
+    add_field!(req.text_enabled, "text_enabled");
+    add_field!(req.text_anchor, "text_anchor");
+    add_field!(req.text_font_path, "text_font_path");
+    add_field!(req.text_font_size, "text_font_size");
+    add_field!(req.text_color, "text_color");
+    add_field!(req.text_box, "text_box");
+    add_field!(req.text_box_color, "text_box_color");
+    add_field!(req.upnext_enabled, "upnext_enabled");
+    add_field!(req.upnext_lead, "upnext_lead");
+    add_field!(req.upnext_label, "upnext_label");
+    add_field!(req.clock_mode, "clock_mode");
+    add_field!(req.clock_anchor, "clock_anchor");
+    add_field!(req.loudness_target, "loudness_target");
+    add_field!(req.transition_type, "transition_type");
+    add_field!(req.transition_duration, "transition_duration");
+    add_field!(req.audio_background_path, "audio_background_path");
+    add_field!(req.audio_visualizer, "audio_visualizer");
+    add_field!(req.audio_now_playing, "audio_now_playing");
+
+    sql.push_str(&format!(" WHERE channel_id = ${}", counter));
     log::info!("Updating settings SQL: {}", sql);
 
     let mut query = sqlx::query(&sql);
@@ -282,6 +370,30 @@ async fn update_settings(
     bind_field!(req.tmdb_api_key);
     bind_field!(req.omdb_api_key);
     bind_field!(req.tvmaze_api_key);
+    bind_field!(req.timezone);
+    bind_field!(bool, req.scte35_enabled);
+    bind_field!(bool, req.ticker_enabled);
+    bind_field!(num, req.ticker_speed);
+    bind_field!(req.ticker_position);
+    bind_field!(bool, req.text_enabled);
+    bind_field!(req.text_anchor);
+    bind_field!(req.text_font_path);
+    bind_field!(num, req.text_font_size);
+    bind_field!(req.text_color);
+    bind_field!(bool, req.text_box);
+    bind_field!(req.text_box_color);
+    bind_field!(bool, req.upnext_enabled);
+    bind_field!(num, req.upnext_lead);
+    bind_field!(req.upnext_label);
+    bind_field!(req.clock_mode);
+    bind_field!(req.clock_anchor);
+    bind_field!(num, req.loudness_target);
+    bind_field!(req.transition_type);
+    bind_field!(num, req.transition_duration);
+    bind_field!(req.audio_background_path);
+    bind_field!(req.audio_visualizer);
+    bind_field!(bool, req.audio_now_playing);
+    query = query.bind(channel.id());
 
     let result = query.execute(pool.get_ref()).await;
 
@@ -295,7 +407,11 @@ async fn update_settings(
     }
 }
 
-async fn upload_logo(mut payload: Multipart, pool: web::Data<PgPool>) -> impl Responder {
+async fn upload_logo(
+    channel: ChannelScope,
+    mut payload: Multipart,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
     let mut filepath = String::new();
 
     while let Ok(Some(mut field)) = payload.try_next().await {
@@ -331,8 +447,9 @@ async fn upload_logo(mut payload: Multipart, pool: web::Data<PgPool>) -> impl Re
     }
 
     // Update logo_path in settings
-    let result = sqlx::query("UPDATE settings SET logo_path = $1 WHERE id = TRUE")
+    let result = sqlx::query("UPDATE settings SET logo_path = $1 WHERE channel_id = $2")
         .bind(&filepath)
+        .bind(channel.id())
         .execute(pool.get_ref())
         .await;
 
@@ -400,7 +517,11 @@ async fn upload_app_logo(mut payload: Multipart, pool: web::Data<PgPool>) -> imp
     }
 }
 
-async fn upload_overlay_pair(mut payload: Multipart, pool: web::Data<PgPool>) -> impl Responder {
+async fn upload_overlay_pair(
+    channel: ChannelScope,
+    mut payload: Multipart,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
     let mut original_path = String::new();
     let mut converted_path = String::new();
     let upload_dir = "/var/lib/onepa-playout/media";
@@ -442,8 +563,9 @@ async fn upload_overlay_pair(mut payload: Multipart, pool: web::Data<PgPool>) ->
     }
 
     // Update settings with the converted path
-    let result = sqlx::query("UPDATE settings SET logo_path = $1 WHERE id = TRUE")
+    let result = sqlx::query("UPDATE settings SET logo_path = $1 WHERE channel_id = $2")
         .bind(&converted_path)
+        .bind(channel.id())
         .execute(pool.get_ref())
         .await;
 
@@ -487,8 +609,13 @@ async fn get_app_logo(pool: web::Data<PgPool>, req: HttpRequest) -> impl Respond
     }
 }
 
-async fn get_logo(pool: web::Data<PgPool>, req: HttpRequest) -> impl Responder {
-    let result = sqlx::query("SELECT logo_path FROM settings WHERE id = TRUE")
+async fn get_logo(
+    channel: ChannelScope,
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+) -> impl Responder {
+    let result = sqlx::query("SELECT logo_path FROM settings WHERE channel_id = $1")
+        .bind(channel.id())
         .fetch_one(pool.get_ref())
         .await;
 
@@ -0,0 +1,151 @@
+use crate::api::channels::ChannelScope;
+use crate::services::engine::PlayoutEngine;
+use crate::services::events::{PlayoutEvent, Topic};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use serde::Deserialize;
+use std::collections::HashSet;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Debug, Deserialize)]
+pub struct WsQuery {
+    /// Comma separated topics (status, clips, logs, relays); all when omitted
+    pub topics: Option<String>,
+}
+
+/// Messages a client may send to change its subscription
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum ClientMessage {
+    Subscribe { topics: Vec<Topic> },
+    Unsubscribe { topics: Vec<Topic> },
+}
+
+/// `/api/ws` (or `/api/channels/{channel}/ws`): pushes the channel's engine events for the subscribed topics
+pub async fn ws_handler(
+    req: HttpRequest,
+    body: web::Payload,
+    channel: ChannelScope,
+    query: web::Query<WsQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (response, mut session, mut stream) = actix_ws::handle(&req, body)?;
+
+    let mut topics: HashSet<Topic> = match query.topics.as_deref() {
+        Some(list) => list.split(',').filter_map(Topic::parse).collect(),
+        None => Topic::ALL.into_iter().collect(),
+    };
+    let engine = channel.engine;
+    let mut events = engine.subscribe();
+
+    actix_web::rt::spawn(async move {
+        log::info!("[WS] Client subscribed to {:?}", topics);
+        if topics.contains(&Topic::Status) && send_snapshot(&mut session, &engine).await.is_err() {
+            return;
+        }
+
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        if topics.contains(&event.topic())
+                            && send(&mut session, &event).await.is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        // Client fell behind: tell it and resync the status
+                        log::warn!("[WS] Client lagged, {} events dropped", missed);
+                        let notice = serde_json::json!({ "type": "lagged", "missed": missed });
+                        if session.text(notice.to_string()).await.is_err() {
+                            break;
+                        }
+                        if topics.contains(&Topic::Status)
+                            && send_snapshot(&mut session, &engine).await.is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                msg = stream.recv() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        let (reply, resync) = apply_client_message(&text, &mut topics);
+                        if session.text(reply.to_string()).await.is_err() {
+                            break;
+                        }
+                        if resync && send_snapshot(&mut session, &engine).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        let _ = session.close(reason).await;
+                        log::info!("[WS] Client disconnected");
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => break,
+                },
+            }
+        }
+
+        let _ = session.close(None).await;
+        log::info!("[WS] Client disconnected");
+    });
+
+    Ok(response)
+}
+
+// Update the subscription; true when the client just subscribed to status and needs a snapshot
+fn apply_client_message(text: &str, topics: &mut HashSet<Topic>) -> (serde_json::Value, bool) {
+    match serde_json::from_str::<ClientMessage>(text) {
+        Ok(ClientMessage::Subscribe { topics: added }) => {
+            let resync = added.contains(&Topic::Status) && !topics.contains(&Topic::Status);
+            topics.extend(added);
+            (
+                serde_json::json!({ "type": "subscribed", "topics": topics }),
+                resync,
+            )
+        }
+        Ok(ClientMessage::Unsubscribe { topics: removed }) => {
+            topics.retain(|t| !removed.contains(t));
+            (
+                serde_json::json!({ "type": "subscribed", "topics": topics }),
+                false,
+            )
+        }
+        Err(e) => (
+            serde_json::json!({ "type": "error", "error": e.to_string() }),
+            false,
+        ),
+    }
+}
+
+async fn send(
+    session: &mut actix_ws::Session,
+    event: &PlayoutEvent,
+) -> Result<(), actix_ws::Closed> {
+    match serde_json::to_string(event) {
+        Ok(text) => session.text(text).await,
+        Err(e) => {
+            log::error!("[WS] Failed to serialize event: {}", e);
+            Ok(())
+        }
+    }
+}
+
+async fn send_snapshot(
+    session: &mut actix_ws::Session,
+    engine: &PlayoutEngine,
+) -> Result<(), actix_ws::Closed> {
+    let status = engine.status.lock().await.clone();
+    let event = PlayoutEvent::Snapshot {
+        status: serde_json::to_value(status).unwrap_or_default(),
+    };
+    send(session, &event).await
+}
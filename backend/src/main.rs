@@ -62,12 +62,10 @@ async fn main() -> std::io::Result<()> {
     services::auth::configure();
     services::database::configure();
 
-    // Start Playout Engine
-    let engine = std::sync::Arc::new(services::engine::PlayoutEngine::new(pool.clone()));
-    let engine_clone = engine.clone();
-    tokio::spawn(async move {
-        engine_clone.start().await;
-    });
+    // Start one Playout Engine per channel
+    let registry = services::channels::ChannelRegistry::load(&pool)
+        .await
+        .expect("Failed to load channels");
 
     // Start HTTP server
     HttpServer::new(move || {
@@ -84,14 +82,14 @@ async fn main() -> std::io::Result<()> {
 
         App::new()
             .app_data(web::Data::new(pool.clone()))
-            .app_data(web::Data::new(engine.clone()))
+            .app_data(web::Data::new(registry.clone()))
             .wrap(cors)
             .wrap(middleware::Logger::default())
             .wrap_fn(|req, srv| {
-                let engine = req
-                    .app_data::<web::Data<std::sync::Arc<services::engine::PlayoutEngine>>>()
-                    .cloned();
                 let path = req.path().to_string();
+                let engine = req
+                    .app_data::<web::Data<std::sync::Arc<services::channels::ChannelRegistry>>>()
+                    .map(|registry| registry.for_hls_path(&path));
                 let query = req.query_string().to_string();
 
                 // Robust Client IP Detection (X-Forwarded-For > X-Real-IP > PeerAddr)
@@ -191,6 +189,10 @@ async fn main() -> std::io::Result<()> {
                 .boxed_local()
             })
             .configure(api::routes::configure)
+            .route(
+                "/hls/{playlist:.*\\.m3u8}",
+                web::get().to(api::playout::hls_playlist),
+            )
             .service(actix_files::Files::new("/hls", &hls_serve_path).show_files_listing())
             .service(actix_files::Files::new("/assets", &assets_serve_path).show_files_listing())
     })
@@ -0,0 +1,69 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct AsRunEntry {
+    pub id: Uuid,
+    #[sqlx(default)]
+    pub channel_id: Option<Uuid>,
+    pub source: String, // schedule, filler, override
+    pub media_id: Option<Uuid>,
+    pub clip_path: String,
+    pub filename: Option<String>,
+    pub title: Option<String>,
+    pub playlist_id: Option<Uuid>,
+    pub playlist_name: Option<String>,
+    pub schedule_id: Option<Uuid>,
+    pub occurrence_date: Option<NaiveDate>,
+    pub planned_start: Option<DateTime<Utc>>,
+    pub planned_end: Option<DateTime<Utc>>,
+    pub actual_start: DateTime<Utc>,
+    pub actual_end: Option<DateTime<Utc>>,
+    pub end_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A clip start about to be written to the as-run log
+#[derive(Debug, Clone, Default)]
+pub struct NewAsRun {
+    pub channel_id: Option<Uuid>,
+    pub source: String,
+    pub media_id: Option<Uuid>,
+    pub clip_path: String,
+    pub filename: Option<String>,
+    pub title: Option<String>,
+    pub playlist_id: Option<Uuid>,
+    pub playlist_name: Option<String>,
+    pub schedule_id: Option<Uuid>,
+    pub occurrence_date: Option<NaiveDate>,
+    pub planned_start: Option<DateTime<Utc>>,
+    pub planned_end: Option<DateTime<Utc>>,
+}
+
+/// Why a clip left the air
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndReason {
+    Completed,
+    Skipped,
+    Interrupted,
+    Paused,
+    Stopped,
+    /// The encoder kept failing on the clip (media marked suspect)
+    Failed,
+}
+
+impl EndReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndReason::Completed => "completed",
+            EndReason::Skipped => "skipped",
+            EndReason::Interrupted => "interrupted",
+            EndReason::Paused => "paused",
+            EndReason::Stopped => "stopped",
+            EndReason::Failed => "failed",
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Channel {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    /// MediaMTX path prefix; the engine publishes on `{mediamtx_path}/master`
+    pub mediamtx_path: String,
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Channel {
+    /// MediaMTX path of the master feed (`live/master` for the default channel)
+    pub fn master_path(&self) -> String {
+        format!("{}/master", self.mediamtx_path)
+    }
+
+    pub fn master_url(&self, host: &str) -> String {
+        format!("rtmp://{}:1935/{}", host, self.master_path())
+    }
+
+    /// XMLTV channel id (`onepa.1` for the default channel, as published before channels existed)
+    pub fn epg_id(&self) -> String {
+        if self.is_default {
+            "onepa.1".to_string()
+        } else {
+            format!("onepa.{}", self.slug)
+        }
+    }
+
+    /// HLS preview directory: HLS_PATH for the default channel, a subdirectory per slug otherwise
+    pub fn hls_dir(&self) -> PathBuf {
+        let root = PathBuf::from(
+            std::env::var("HLS_PATH").unwrap_or_else(|_| "/var/lib/onepa-playout/hls".to_string()),
+        );
+        if self.is_default {
+            root
+        } else {
+            root.join(&self.slug)
+        }
+    }
+
+    /// Files read by the on-air text layers (ticker, static text)
+    pub fn text_dir(&self) -> PathBuf {
+        PathBuf::from(
+            std::env::var("ASSETS_PATH")
+                .unwrap_or_else(|_| "/var/lib/onepa-playout/assets".to_string()),
+        )
+        .join("text")
+        .join(&self.slug)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateChannelRequest {
+    pub slug: String,
+    pub name: String,
+    /// Copy settings (paths, encoding, day start, timezone...) from this channel, default channel otherwise
+    pub copy_from: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateChannelRequest {
+    pub name: String,
+}
+
+/// Lowercase letters, digits and dashes: the slug ends up in URLs, paths and MediaMTX names
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.len() <= 32
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_slug() {
+        assert!(is_valid_slug("news"));
+        assert!(is_valid_slug("sport-2"));
+        assert!(!is_valid_slug(""));
+        assert!(!is_valid_slug("-news"));
+        assert!(!is_valid_slug("News"));
+        assert!(!is_valid_slug("news/live"));
+        assert!(!is_valid_slug(&"a".repeat(33)));
+    }
+}
@@ -19,6 +19,12 @@ pub struct Media {
     pub folder_id: Option<Uuid>,
     pub metadata: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
+    #[sqlx(default)]
+    pub is_suspect: Option<bool>, // encoder kept failing on it
+    #[sqlx(default)]
+    pub suspect_reason: Option<String>,
+    #[sqlx(default)]
+    pub suspect_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
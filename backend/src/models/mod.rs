@@ -1,3 +1,5 @@
+pub mod as_run;
+pub mod channel;
 pub mod media;
 pub mod playlist;
 pub mod schedule;
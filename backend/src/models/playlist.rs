@@ -3,12 +3,13 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct PlaylistItem {
     pub id: Option<String>, // Can be string or uuid from frontend
     pub filename: Option<String>,
     pub path: Option<String>,
     pub source: Option<String>,
+    #[serde(default)]
     pub duration: f64,
     #[serde(default)]
     pub r#in: f64,
@@ -20,15 +21,255 @@ pub struct PlaylistItem {
     pub metadata: Option<serde_json::Value>, // EPG metadata
     pub is_filler: Option<bool>,
     pub media_id: Option<String>,
+    /// Ad-break cue points inside the item
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cues: Vec<CuePoint>,
+    /// Graphics and logo changes timed against the item
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<SecondaryEvent>,
+    /// How the previous item hands over to this one (channel default when missing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<Transition>,
+    /// Slow zoom across still images (image and slideshow items)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ken_burns: bool,
+    /// Images of a media folder shown in turn for the item's duration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slideshow: Option<Slideshow>,
+    /// Picture made for an audio item (channel defaults when missing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visual: Option<AudioVisual>,
+}
+
+/// Slideshow item: the images of a media folder, in filename order and repeated as needed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Slideshow {
+    pub folder_id: Uuid,
+    /// Seconds each image stays on screen
+    #[serde(default = "default_image_duration")]
+    pub image_duration: f64,
+    /// Audio bed (file path), looped for the length of the item
+    pub audio: Option<String>,
+}
+
+fn default_image_duration() -> f64 {
+    5.0
+}
+
+/// Audio drawn over the background of an audio item
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Visualizer {
+    #[default]
+    None,
+    /// Waveform (`showwaves`)
+    Waves,
+    /// Scrolling spectrum (`showspectrum`)
+    Spectrum,
+}
+
+/// Picture of an audio item: a background with an optional visualizer and now-playing line
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct AudioVisual {
+    /// Image, or video played in a loop; the channel's audio background when missing
+    pub background: Option<String>,
+    #[serde(default)]
+    pub visualizer: Visualizer,
+    /// Title (and artist) of the track from its metadata
+    #[serde(default)]
+    pub now_playing: bool,
+}
+
+/// Direction of a splice: leave the network feed for an ad break, or return to it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CueType {
+    Out,
+    In,
+}
+
+/// SCTE-35 splice insert point within a playlist item
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CuePoint {
+    #[serde(rename = "type")]
+    pub kind: CueType,
+    /// Seconds into the item's airtime
+    #[serde(default)]
+    pub offset: f64,
+    /// Break length (cue-out only); the return cue-in is implied when set
+    pub duration: Option<f64>,
+    /// splice_event_id, derived from the airtime when missing
+    pub event_id: Option<u32>,
+}
+
+/// What a secondary event does while it is active
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecondaryKind {
+    /// Overlay `path` (image or looping video) on top of the programme
+    Graphic,
+    /// Take the channel logo off screen
+    HideLogo,
+}
+
+/// Secondary event within a playlist item
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SecondaryEvent {
+    #[serde(rename = "type")]
+    pub kind: SecondaryKind,
+    /// Seconds into the item's airtime
+    #[serde(default)]
+    pub offset: f64,
+    /// Seconds on screen, until the end of the item when missing
+    pub duration: Option<f64>,
+    pub path: Option<String>,
+    /// Corner and margins as for the logo (`top-right`, 50, 50 when missing)
+    pub anchor: Option<String>,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub opacity: Option<f32>,
+    pub scale: Option<f32>,
+}
+
+/// Effect between two consecutive items
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TransitionKind {
+    #[default]
+    Cut,
+    /// Outgoing item fades to black, incoming one fades up from it
+    Fade,
+    /// Pictures dissolve into each other (`xfade`), sound crossfades (`acrossfade`)
+    Crossfade,
+}
+
+/// Transition into an item; the two items overlap for `duration` seconds
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Transition {
+    #[serde(rename = "type")]
+    pub kind: TransitionKind,
+    #[serde(default = "default_transition_duration")]
+    pub duration: f64,
+}
+
+fn default_transition_duration() -> f64 {
+    1.0
 }
 
 impl PlaylistItem {
+    /// File the item plays; a slideshow without one is keyed by its folder
     pub fn get_path(&self) -> String {
         self.path
             .clone()
-            .or_else(|| self.source.clone())
+            .filter(|p| !p.is_empty())
+            .or_else(|| self.source.clone().filter(|s| !s.is_empty()))
+            .or_else(|| {
+                self.slideshow
+                    .as_ref()
+                    .map(|s| format!("slideshow:{}", s.folder_id))
+            })
             .unwrap_or_default()
     }
+
+    /// Airtime of the item once in/out points are applied (out = 0 means "until the end")
+    pub fn effective_duration(&self) -> f64 {
+        let start = self.r#in.max(0.0);
+        let mut end = if self.out > start {
+            self.out
+        } else {
+            self.duration
+        };
+        if self.duration > 0.0 {
+            end = end.min(self.duration);
+        }
+        (end - start).max(0.0)
+    }
+
+    /// Ad break item: its whole airtime is a break (slate or house ads downstream replaces)
+    pub fn is_break(&self) -> bool {
+        self.media_type.as_deref() == Some("break")
+    }
+
+    /// Cue points of the item, a break item implying cue-out at its start for its full length
+    pub fn cue_points(&self) -> Vec<CuePoint> {
+        let mut cues = self.cues.clone();
+        if self.is_break() && !cues.iter().any(|c| c.kind == CueType::Out) {
+            cues.push(CuePoint {
+                kind: CueType::Out,
+                offset: 0.0,
+                duration: Some(self.effective_duration()),
+                event_id: None,
+            });
+        }
+        cues.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        cues
+    }
+
+    /// Concat `inpoint`, only when the clip is trimmed at the head
+    pub fn inpoint(&self) -> Option<f64> {
+        (self.r#in > 0.0).then_some(self.r#in)
+    }
+
+    /// Concat `outpoint`, only when the clip is trimmed at the tail
+    pub fn outpoint(&self) -> Option<f64> {
+        let trimmed = self.out > self.r#in && (self.duration <= 0.0 || self.out < self.duration);
+        trimmed.then_some(self.out)
+    }
+}
+
+/// Parse playlist content stored either as `{"program": [...]}` or as a raw array
+pub fn parse_items(content: &serde_json::Value) -> Result<Vec<PlaylistItem>, String> {
+    if content.is_array() {
+        serde_json::from_value::<Vec<PlaylistItem>>(content.clone()).map_err(|e| e.to_string())
+    } else if content.get("program").is_some() {
+        serde_json::from_value::<PlaylistContent>(content.clone())
+            .map(|c| c.program)
+            .map_err(|e| e.to_string())
+    } else {
+        Err("Invalid playlist content".to_string())
+    }
+}
+
+/// Airtime of the items played in order with their own transitions only: what is stored in
+/// `playlists.total_duration`, shared by every channel. Scheduling goes by
+/// `sequence_duration` with the channel's default transition.
+pub fn total_effective_duration(items: &[PlaylistItem]) -> f64 {
+    sequence_duration(items, None)
+}
+
+/// Airtime of the items played in order: effective durations less transition overlaps
+pub fn sequence_duration(items: &[PlaylistItem], default: Option<&Transition>) -> f64 {
+    let total: f64 = items.iter().map(|i| i.effective_duration()).sum();
+    let overlaps: f64 = transitions(items, default)
+        .iter()
+        .flatten()
+        .map(|t| t.duration)
+        .sum();
+    total - overlaps
+}
+
+/// Transition into each item (None for the first one and for cuts). An overlap never takes
+/// more than half of either item, so every item keeps some airtime of its own.
+pub fn transitions<'a, I>(items: I, default: Option<&Transition>) -> Vec<Option<Transition>>
+where
+    I: IntoIterator<Item = &'a PlaylistItem>,
+{
+    let mut result = Vec::new();
+    let mut previous: Option<f64> = None;
+    for item in items {
+        let duration = item.effective_duration();
+        let transition = previous.and_then(|prev| {
+            let t = item.transition.as_ref().or(default)?;
+            let overlap = t.duration.min(prev / 2.0).min(duration / 2.0);
+            (t.kind != TransitionKind::Cut && overlap > 0.0).then_some(Transition {
+                kind: t.kind,
+                duration: overlap,
+            })
+        });
+        result.push(transition);
+        previous = Some(duration);
+    }
+    result
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -47,6 +288,12 @@ pub struct Playlist {
     pub updated_at: DateTime<Utc>,
 }
 
+impl Playlist {
+    pub fn items(&self) -> Result<Vec<PlaylistItem>, String> {
+        parse_items(&self.content)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct CreatePlaylist {
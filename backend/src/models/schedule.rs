@@ -1,23 +1,433 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Schedule {
     pub id: Uuid,
+    #[sqlx(default)]
+    pub channel_id: Uuid,
     pub playlist_id: Uuid,
     pub date: NaiveDate,
     pub start_time: Option<chrono::NaiveTime>,
-    pub repeat_pattern: Option<String>, // daily, weekly, monthly
+    pub repeat_pattern: Option<String>, // daily, weekly, monthly, NULL for one-time
+    #[sqlx(default)]
+    pub recurrence: Option<String>, // RRULE, takes precedence over repeat_pattern
+    #[sqlx(default)]
+    pub priority: i32, // higher wins when blocks overlap
+    #[sqlx(default)]
     pub playlist_name: Option<String>,
+    #[sqlx(default)]
+    pub playlist_duration: Option<f64>,
     pub created_at: DateTime<Utc>,
 }
 
+impl Schedule {
+    /// Recurrence of this entry (None for one-time entries)
+    pub fn rule(&self) -> Option<RecurrenceRule> {
+        if let Some(rrule) = self.recurrence.as_deref().filter(|r| !r.trim().is_empty()) {
+            match RecurrenceRule::parse(rrule) {
+                Ok(rule) => return Some(rule),
+                Err(e) => log::warn!(
+                    "Schedule {}: invalid recurrence '{}': {}",
+                    self.id,
+                    rrule,
+                    e
+                ),
+            }
+        }
+        normalize_repeat_pattern(self.repeat_pattern.as_deref())
+            .and_then(|p| RecurrenceRule::parse(&p).ok())
+    }
+
+    /// Calendar dates in `from..=to` on which this entry fires
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        match self.rule() {
+            Some(rule) => rule.occurrences(self.date, from, to),
+            None if self.date >= from && self.date <= to => vec![self.date],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Map legacy/empty repeat patterns to a canonical value (NULL and 'none' both mean one-time)
+pub fn normalize_repeat_pattern(pattern: Option<&str>) -> Option<String> {
+    match pattern.map(|p| p.trim().to_lowercase()) {
+        None => None,
+        Some(p) if p.is_empty() || p == "none" => None,
+        Some(p) => Some(p),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Subset of RFC 5545 RRULE: FREQ (DAILY/WEEKLY/MONTHLY), INTERVAL, BYDAY (with
+/// optional ordinal for monthly rules, e.g. 2TU or -1FR), BYMONTHDAY, UNTIL and COUNT.
+/// The schedule date acts as DTSTART.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub until: Option<NaiveDate>,
+    pub count: Option<u32>,
+}
+
+impl RecurrenceRule {
+    /// Parse an RRULE string; the legacy keywords daily/weekly/monthly are accepted too
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let body = input
+            .strip_prefix("RRULE:")
+            .or_else(|| input.strip_prefix("rrule:"))
+            .unwrap_or(input);
+
+        let mut rule = RecurrenceRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            until: None,
+            count: None,
+        };
+
+        match body.to_lowercase().as_str() {
+            "daily" => return Ok(rule),
+            "weekly" => {
+                rule.freq = Frequency::Weekly;
+                return Ok(rule);
+            }
+            "monthly" => {
+                rule.freq = Frequency::Monthly;
+                return Ok(rule);
+            }
+            _ => {}
+        }
+
+        let mut freq = None;
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid RRULE part '{}'", part))?;
+            let value = value.trim();
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(format!("Unsupported FREQ '{}'", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|i| *i > 0)
+                        .ok_or_else(|| format!("Invalid INTERVAL '{}'", value))?
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse::<u32>()
+                            .map_err(|_| format!("Invalid COUNT '{}'", value))?,
+                    )
+                }
+                "UNTIL" => {
+                    // Date part of YYYYMMDD or YYYYMMDDTHHMMSSZ (also accepts YYYY-MM-DD)
+                    let date_part: String = value.chars().take_while(|c| *c != 'T').collect();
+                    rule.until = Some(
+                        NaiveDate::parse_from_str(&date_part, "%Y%m%d")
+                            .or_else(|_| NaiveDate::parse_from_str(&date_part, "%Y-%m-%d"))
+                            .map_err(|_| format!("Invalid UNTIL '{}'", value))?,
+                    )
+                }
+                "BYDAY" => {
+                    for token in value.split(',').filter(|t| !t.is_empty()) {
+                        rule.by_day.push(parse_by_day(token)?);
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for token in value.split(',').filter(|t| !t.is_empty()) {
+                        let day = token
+                            .parse::<i32>()
+                            .ok()
+                            .filter(|d| *d != 0 && (-31..=31).contains(d))
+                            .ok_or_else(|| format!("Invalid BYMONTHDAY '{}'", token))?;
+                        rule.by_month_day.push(day);
+                    }
+                }
+                "WKST" => {}
+                other => return Err(format!("Unsupported RRULE part '{}'", other)),
+            }
+        }
+
+        rule.freq = freq.ok_or("RRULE requires FREQ")?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL cannot be combined".to_string());
+        }
+        if rule.freq != Frequency::Monthly && rule.by_day.iter().any(|(n, _)| n.is_some()) {
+            return Err("Ordinal BYDAY (e.g. 2TU) is only valid with FREQ=MONTHLY".to_string());
+        }
+        Ok(rule)
+    }
+
+    /// Whether `date` matches the rule pattern for a series starting on `dtstart`
+    /// (ignores COUNT, which needs the whole series)
+    fn matches(&self, dtstart: NaiveDate, date: NaiveDate) -> bool {
+        if date < dtstart || self.until.map(|u| date > u).unwrap_or(false) {
+            return false;
+        }
+        let interval = self.interval.max(1) as i64;
+
+        match self.freq {
+            Frequency::Daily => {
+                (date - dtstart).num_days() % interval == 0
+                    && (self.by_day.is_empty()
+                        || self.by_day.iter().any(|(_, wd)| *wd == date.weekday()))
+            }
+            Frequency::Weekly => {
+                let week_of =
+                    |d: NaiveDate| d - Duration::days(d.weekday().num_days_from_monday() as i64);
+                let weeks = (week_of(date) - week_of(dtstart)).num_days() / 7;
+                let day_ok = if self.by_day.is_empty() {
+                    date.weekday() == dtstart.weekday()
+                } else {
+                    self.by_day.iter().any(|(_, wd)| *wd == date.weekday())
+                };
+                weeks % interval == 0 && day_ok
+            }
+            Frequency::Monthly => {
+                let months = (date.year() - dtstart.year()) as i64 * 12 + date.month() as i64
+                    - dtstart.month() as i64;
+                if months % interval != 0 {
+                    return false;
+                }
+                let last_day = days_in_month(date.year(), date.month()) as i32;
+                let day = date.day() as i32;
+                if !self.by_day.is_empty() {
+                    self.by_day.iter().any(|(n, wd)| {
+                        *wd == date.weekday()
+                            && match n {
+                                None => true,
+                                Some(n) if *n > 0 => (day - 1) / 7 + 1 == *n,
+                                Some(n) => (last_day - day) / 7 + 1 == -n,
+                            }
+                    }) && (self.by_month_day.is_empty()
+                        || month_day_matches(&self.by_month_day, day, last_day))
+                } else if !self.by_month_day.is_empty() {
+                    month_day_matches(&self.by_month_day, day, last_day)
+                } else {
+                    day == dtstart.day() as i32
+                }
+            }
+        }
+    }
+
+    /// Dates in `from..=to` produced by a series starting on `dtstart`
+    pub fn occurrences(
+        &self,
+        dtstart: NaiveDate,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let end = match self.until {
+            Some(until) => to.min(until),
+            None => to,
+        };
+        // COUNT needs every instance since DTSTART; otherwise start at the window
+        let mut date = if self.count.is_some() {
+            dtstart
+        } else {
+            from.max(dtstart)
+        };
+        let mut seen = 0u32;
+
+        while date <= end {
+            if self.matches(dtstart, date) {
+                seen += 1;
+                if self.count.map(|c| seen > c).unwrap_or(false) {
+                    break;
+                }
+                if date >= from {
+                    dates.push(date);
+                }
+            }
+            date += Duration::days(1);
+        }
+        dates
+    }
+
+    /// Legacy repeat_pattern keyword closest to this rule (for older clients)
+    pub fn repeat_pattern(&self) -> &'static str {
+        match self.freq {
+            Frequency::Daily => "daily",
+            Frequency::Weekly => "weekly",
+            Frequency::Monthly => "monthly",
+        }
+    }
+}
+
+impl std::fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|(n, wd)| {
+                    let code = &wd.to_string()[..2];
+                    match n {
+                        Some(n) => format!("{}{}", n, code.to_uppercase()),
+                        None => code.to_uppercase(),
+                    }
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_by_day(token: &str) -> Result<(Option<i32>, Weekday), String> {
+    let token = token.trim().to_uppercase();
+    if token.len() < 2 {
+        return Err(format!("Invalid BYDAY '{}'", token));
+    }
+    let (ordinal, code) = token.split_at(token.len() - 2);
+    let weekday = match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(format!("Invalid BYDAY '{}'", token)),
+    };
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(
+            ordinal
+                .trim_start_matches('+')
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && (-5..=5).contains(n))
+                .ok_or_else(|| format!("Invalid BYDAY '{}'", token))?,
+        )
+    };
+    Ok((ordinal, weekday))
+}
+
+fn month_day_matches(by_month_day: &[i32], day: i32, last_day: i32) -> bool {
+    by_month_day.iter().any(|d| {
+        if *d > 0 {
+            *d == day
+        } else {
+            last_day + d + 1 == day
+        }
+    })
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (ny, nm) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    (NaiveDate::from_ymd_opt(ny, nm, 1).unwrap() - Duration::days(1)).day()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateSchedule {
     pub playlist_id: Uuid,
     pub date: NaiveDate,
     pub start_time: Option<chrono::NaiveTime>,
     pub repeat_pattern: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub priority: Option<i32>,
+    /// Save even if the block overlaps other entries (priority decides who airs)
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_weekdays_only() {
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR").unwrap();
+        // 2026-10-05 is a Monday
+        let dates = rule.occurrences(d(2026, 10, 5), d(2026, 10, 5), d(2026, 10, 11));
+        assert_eq!(dates.len(), 5);
+        assert!(!dates.contains(&d(2026, 10, 10)));
+    }
+
+    #[test]
+    fn test_nth_weekday_of_month() {
+        // Second Tuesday and last Friday of every month
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;BYDAY=2TU,-1FR").unwrap();
+        let dates = rule.occurrences(d(2026, 1, 1), d(2026, 10, 1), d(2026, 10, 31));
+        assert_eq!(dates, vec![d(2026, 10, 13), d(2026, 10, 30)]);
+    }
+
+    #[test]
+    fn test_interval_until_and_count() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=2;UNTIL=20261010").unwrap();
+        let dates = rule.occurrences(d(2026, 10, 1), d(2026, 10, 1), d(2026, 10, 31));
+        assert_eq!(dates.len(), 5);
+        assert_eq!(dates.last(), Some(&d(2026, 10, 9)));
+
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;COUNT=3").unwrap();
+        let dates = rule.occurrences(d(2026, 10, 1), d(2026, 10, 10), d(2026, 12, 31));
+        assert_eq!(dates, vec![d(2026, 10, 15)]);
+    }
+
+    #[test]
+    fn test_legacy_patterns_and_roundtrip() {
+        assert_eq!(normalize_repeat_pattern(Some("none")), None);
+        assert_eq!(
+            normalize_repeat_pattern(Some("Weekly")),
+            Some("weekly".to_string())
+        );
+        let rule = RecurrenceRule::parse("monthly").unwrap();
+        assert_eq!(rule.freq, Frequency::Monthly);
+        let text = "FREQ=MONTHLY;INTERVAL=2;BYDAY=1MO;COUNT=4";
+        assert_eq!(RecurrenceRule::parse(text).unwrap().to_string(), text);
+        assert!(RecurrenceRule::parse("FREQ=YEARLY").is_err());
+    }
 }
@@ -1,10 +1,15 @@
+use crate::models::playlist::{AudioVisual, Transition, TransitionKind, Visualizer};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Settings {
+    /// TRUE on the default channel's row
     pub id: bool,
+    #[sqlx(default)]
+    pub channel_id: Uuid,
     pub output_type: String,
     pub output_url: String,
     pub resolution: String,
@@ -69,9 +74,107 @@ pub struct Settings {
     pub omdb_api_key: Option<String>,
     #[sqlx(default)]
     pub tvmaze_api_key: Option<String>,
+    /// IANA zone name (e.g. "Europe/Lisbon"); host zone when unset
+    #[sqlx(default)]
+    pub timezone: Option<String>,
+    /// Ad-break cues as SCTE-35 on SRT/UDP and CUE tags in HLS
+    #[sqlx(default)]
+    pub scte35_enabled: bool,
+    /// Scrolling ticker read from the channel's `ticker.txt`
+    #[sqlx(default)]
+    pub ticker_enabled: bool,
+    /// Crawl speed in pixels per second
+    #[sqlx(default)]
+    pub ticker_speed: Option<i32>,
+    /// "top" or "bottom" band
+    #[sqlx(default)]
+    pub ticker_position: Option<String>,
+    /// Static text read from the channel's `text.txt`
+    #[sqlx(default)]
+    pub text_enabled: bool,
+    #[sqlx(default)]
+    pub text_anchor: Option<String>,
+    /// Font file for the text layers; fontconfig "Sans" when unset
+    #[sqlx(default)]
+    pub text_font_path: Option<String>,
+    #[sqlx(default)]
+    pub text_font_size: Option<i32>,
+    #[sqlx(default)]
+    pub text_color: Option<String>,
+    #[sqlx(default)]
+    pub text_box: bool,
+    #[sqlx(default)]
+    pub text_box_color: Option<String>,
+    /// "Up next: <title>" lower third before each transition
+    #[sqlx(default)]
+    pub upnext_enabled: bool,
+    /// Seconds before the transition the lower third comes on
+    #[sqlx(default)]
+    pub upnext_lead: Option<i32>,
+    #[sqlx(default)]
+    pub upnext_label: Option<String>,
+    /// "off", "clock" (channel local time) or "countdown" (time left in the clip)
+    #[sqlx(default)]
+    pub clock_mode: Option<String>,
+    #[sqlx(default)]
+    pub clock_anchor: Option<String>,
+    /// Integrated loudness (LUFS) clips are normalized to on air
+    #[sqlx(default)]
+    pub loudness_target: Option<f64>,
+    /// Transition between items that do not set their own: "cut", "fade" or "crossfade"
+    #[sqlx(default)]
+    pub transition_type: Option<String>,
+    /// Seconds of overlap of the default transition
+    #[sqlx(default)]
+    pub transition_duration: Option<f64>,
+    /// Image or video loop behind audio-only items, the default image when empty
+    #[sqlx(default)]
+    pub audio_background_path: Option<String>,
+    /// Visualizer over audio-only items: "none", "waves" or "spectrum"
+    #[sqlx(default)]
+    pub audio_visualizer: Option<String>,
+    /// Now-playing line over audio-only items
+    #[sqlx(default)]
+    pub audio_now_playing: bool,
 }
 
 impl Settings {
+    /// Channel default transition, None for a cut
+    pub fn default_transition(&self) -> Option<Transition> {
+        let kind = match self.transition_type.as_deref() {
+            Some("fade") => TransitionKind::Fade,
+            Some("crossfade") => TransitionKind::Crossfade,
+            _ => return None,
+        };
+        Some(Transition {
+            kind,
+            duration: self.transition_duration.unwrap_or(1.0),
+        })
+    }
+
+    /// Picture of an audio item: its own settings, else the channel's; the background falls
+    /// back to the channel's audio background, then its default image
+    pub fn audio_visual(&self, item: Option<&AudioVisual>) -> AudioVisual {
+        let mut visual = item.cloned().unwrap_or(AudioVisual {
+            background: None,
+            visualizer: match self.audio_visualizer.as_deref() {
+                Some("waves") => Visualizer::Waves,
+                Some("spectrum") => Visualizer::Spectrum,
+                _ => Visualizer::None,
+            },
+            now_playing: self.audio_now_playing,
+        });
+        visual.background = [
+            visual.background.take(),
+            self.audio_background_path.clone(),
+            self.default_image_path.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|p| !p.trim().is_empty());
+        visual
+    }
+
     pub fn get_display_urls(&self, host: &str) -> std::collections::HashMap<String, String> {
         let mut urls = std::collections::HashMap::new();
 
@@ -183,4 +286,27 @@ pub struct UpdateSettingsRequest {
     pub tmdb_api_key: Option<String>,
     pub omdb_api_key: Option<String>,
     pub tvmaze_api_key: Option<String>,
+    pub timezone: Option<String>,
+    pub scte35_enabled: Option<bool>,
+    pub ticker_enabled: Option<bool>,
+    pub ticker_speed: Option<i32>,
+    pub ticker_position: Option<String>,
+    pub text_enabled: Option<bool>,
+    pub text_anchor: Option<String>,
+    pub text_font_path: Option<String>,
+    pub text_font_size: Option<i32>,
+    pub text_color: Option<String>,
+    pub text_box: Option<bool>,
+    pub text_box_color: Option<String>,
+    pub upnext_enabled: Option<bool>,
+    pub upnext_lead: Option<i32>,
+    pub upnext_label: Option<String>,
+    pub clock_mode: Option<String>,
+    pub clock_anchor: Option<String>,
+    pub loudness_target: Option<f64>,
+    pub transition_type: Option<String>,
+    pub transition_duration: Option<f64>,
+    pub audio_background_path: Option<String>,
+    pub audio_visualizer: Option<String>,
+    pub audio_now_playing: Option<bool>,
 }
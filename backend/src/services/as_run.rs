@@ -0,0 +1,67 @@
+use crate::models::as_run::{EndReason, NewAsRun};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+// Clips ending this close to their planned end count as completed
+const COMPLETION_TOLERANCE_SECS: i64 = 2;
+
+/// Write a clip start to the as-run log, returns the row id
+pub async fn open(pool: &PgPool, entry: &NewAsRun) -> Result<Uuid, String> {
+    let row = sqlx::query(
+        "INSERT INTO as_run (channel_id, source, media_id, clip_path, filename, title, playlist_id,
+            playlist_name, schedule_id, occurrence_date, planned_start, planned_end, actual_start)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+         RETURNING id",
+    )
+    .bind(entry.channel_id)
+    .bind(&entry.source)
+    .bind(entry.media_id)
+    .bind(&entry.clip_path)
+    .bind(&entry.filename)
+    .bind(&entry.title)
+    .bind(entry.playlist_id)
+    .bind(&entry.playlist_name)
+    .bind(entry.schedule_id)
+    .bind(entry.occurrence_date)
+    .bind(entry.planned_start)
+    .bind(entry.planned_end)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(row.get("id"))
+}
+
+/// Stamp the actual end of an open row
+pub async fn close(pool: &PgPool, id: Uuid, reason: EndReason) -> Result<(), String> {
+    sqlx::query("UPDATE as_run SET actual_end = $1, end_reason = $2 WHERE id = $3")
+        .bind(Utc::now())
+        .bind(reason.as_str())
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reason for a clip that simply gave way to the next one
+pub fn reason_at(planned_end: Option<DateTime<Utc>>, at: DateTime<Utc>) -> EndReason {
+    match planned_end {
+        Some(end) if at + chrono::Duration::seconds(COMPLETION_TOLERANCE_SECS) < end => {
+            EndReason::Interrupted
+        }
+        _ => EndReason::Completed,
+    }
+}
+
+/// Rows left open by a crash or restart are marked interrupted (actual end stays unknown)
+pub async fn close_dangling(pool: &PgPool) -> Result<u64, String> {
+    sqlx::query("UPDATE as_run SET end_reason = $1 WHERE actual_end IS NULL AND end_reason IS NULL")
+        .bind(EndReason::Interrupted.as_str())
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected())
+        .map_err(|e| e.to_string())
+}
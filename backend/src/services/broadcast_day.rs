@@ -0,0 +1,420 @@
+use crate::models::playlist::{self, Playlist};
+use crate::models::schedule::Schedule;
+use crate::models::settings::Settings;
+use crate::services::clock;
+use crate::services::timeline::{Shift, TimelineInput};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// One airing of a schedule entry at a concrete instant
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub schedule: Schedule,
+    /// Channel-local date the entry fires on (key for exceptions and the offset ledger)
+    pub date: NaiveDate,
+    pub start: DateTime<Utc>,
+    /// Unknown when the playlist duration is not known
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl Occurrence {
+    pub fn key(&self) -> (Uuid, NaiveDate) {
+        (self.schedule.id, self.date)
+    }
+
+    /// Start times are channel wall-clock; see `clock::to_utc` for the DST rules
+    pub fn new(schedule: &Schedule, date: NaiveDate, tz: &Tz) -> Option<Self> {
+        let start = clock::to_utc(tz, date.and_time(schedule.start_time?));
+        let end = schedule
+            .playlist_duration
+            .filter(|d| *d > 0.0)
+            .map(|d| start + Duration::milliseconds((d * 1000.0) as i64));
+        Some(Occurrence {
+            schedule: schedule.clone(),
+            date,
+            start,
+            end,
+        })
+    }
+
+    /// `starts` holds every start on the channel (sorted); blocks of unknown length run until
+    /// the next of them, which starts right after rather than over them
+    fn overlaps(&self, other: &Occurrence, starts: &[DateTime<Utc>]) -> bool {
+        self.covers(other.start, starts) || other.covers(self.start, starts)
+    }
+
+    fn covers(&self, at: DateTime<Utc>, starts: &[DateTime<Utc>]) -> bool {
+        if at < self.start {
+            return false;
+        }
+        match self.end {
+            Some(end) => at < end,
+            None => starts
+                .iter()
+                .find(|s| **s > self.start)
+                .is_none_or(|next| at < *next),
+        }
+    }
+}
+
+/// An existing occurrence that overlaps a candidate schedule entry
+#[derive(Debug, Clone, Serialize)]
+pub struct Conflict {
+    pub schedule_id: Uuid,
+    pub playlist_id: Uuid,
+    pub playlist_name: Option<String>,
+    pub priority: i32,
+    pub date: NaiveDate,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub candidate_start: DateTime<Utc>,
+    /// Which entry airs during the overlap: "new" or "existing"
+    pub winner: String,
+}
+
+/// Broadcast day boundary from settings.day_start ("HH:MM" or "HH:MM:SS"), 06:00 by default
+pub fn day_start(settings: &Settings) -> NaiveTime {
+    settings
+        .day_start
+        .as_deref()
+        .and_then(|d| {
+            NaiveTime::parse_from_str(d, "%H:%M:%S")
+                .or_else(|_| NaiveTime::parse_from_str(d, "%H:%M"))
+                .ok()
+        })
+        .unwrap_or_else(|| NaiveTime::from_hms_opt(6, 0, 0).unwrap())
+}
+
+/// Broadcast day an instant belongs to in the channel zone (times before day_start belong
+/// to the previous day)
+pub fn broadcast_date(at: DateTime<Utc>, day_start: NaiveTime, tz: &Tz) -> NaiveDate {
+    let local = clock::local(tz, at);
+    if local.time() < day_start {
+        local.date() - Duration::days(1)
+    } else {
+        local.date()
+    }
+}
+
+/// Start (inclusive) and end (exclusive) of a broadcast day; 23 or 25 hours long when
+/// the clocks change during it
+pub fn day_bounds(
+    date: NaiveDate,
+    day_start: NaiveTime,
+    tz: &Tz,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        clock::to_utc(tz, date.and_time(day_start)),
+        clock::to_utc(tz, (date + Duration::days(1)).and_time(day_start)),
+    )
+}
+
+/// Airtime of each existing playlist as the channel plays it, its default transition applied
+/// the way the resolver does (0 when the content is invalid). The stored `total_duration` is
+/// shared by every channel and leaves the default out.
+pub async fn playlist_airtime(
+    pool: &PgPool,
+    channel_id: Uuid,
+    playlist_ids: &[Uuid],
+) -> Result<HashMap<Uuid, f64>, String> {
+    let default = sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE channel_id = $1")
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .and_then(|s| s.default_transition());
+
+    Ok(
+        sqlx::query_as::<_, Playlist>("SELECT * FROM playlists WHERE id = ANY($1)")
+            .bind(playlist_ids)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|p| {
+                let airtime = p
+                    .items()
+                    .map(|items| playlist::sequence_duration(&items, default.as_ref()))
+                    .unwrap_or(0.0);
+                (p.id, airtime)
+            })
+            .collect(),
+    )
+}
+
+/// Expand every schedule entry (one-time, legacy pattern or RRULE) into its occurrences
+/// on the channel-local dates `from..=to`
+pub async fn load_occurrences(
+    pool: &PgPool,
+    channel_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Result<Vec<Occurrence>, String> {
+    let schedules = sqlx::query_as::<_, Schedule>(
+        "SELECT s.*, p.name as playlist_name, p.total_duration as playlist_duration
+         FROM schedule s
+         JOIN playlists p ON s.playlist_id = p.id
+         WHERE s.channel_id = $1 AND s.date <= $2 AND s.start_time IS NOT NULL",
+    )
+    .bind(channel_id)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let ids: Vec<Uuid> = schedules.iter().map(|s| s.playlist_id).collect();
+    let airtime = playlist_airtime(pool, channel_id, &ids).await?;
+    let schedules: Vec<Schedule> = schedules
+        .into_iter()
+        .map(|s| Schedule {
+            playlist_duration: airtime.get(&s.playlist_id).copied(),
+            ..s
+        })
+        .collect();
+
+    let exceptions: HashSet<(Uuid, NaiveDate)> = sqlx::query(
+        "SELECT schedule_id, exception_date FROM schedule_exceptions
+         WHERE exception_date BETWEEN $1 AND $2",
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .iter()
+    .map(|row| (row.get("schedule_id"), row.get("exception_date")))
+    .collect();
+
+    // Single recurrence expander shared by the engine, EPG and playlist views
+    let mut occurrences = Vec::new();
+    for s in &schedules {
+        for date in s.occurrences(from, to) {
+            if !exceptions.contains(&(s.id, date)) {
+                occurrences.extend(Occurrence::new(s, date, tz));
+            }
+        }
+    }
+
+    occurrences.sort_by_key(|o| o.start);
+    Ok(occurrences)
+}
+
+/// Load what the timeline resolver needs for `from..to`: occurrences (one day back for
+/// blocks spilling into the window), their playlists and the offset ledger
+pub async fn load_timeline(
+    pool: &PgPool,
+    channel_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    tz: &Tz,
+) -> Result<TimelineInput, String> {
+    let settings = sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE channel_id = $1")
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    load_timeline_with(pool, channel_id, from, to, tz, settings.as_ref()).await
+}
+
+/// `load_timeline` for callers that already hold the channel settings
+pub async fn load_timeline_with(
+    pool: &PgPool,
+    channel_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    tz: &Tz,
+    settings: Option<&Settings>,
+) -> Result<TimelineInput, String> {
+    let from_date = clock::local(tz, from).date() - Duration::days(1);
+    let to_date = clock::local(tz, to).date();
+    let occurrences = load_occurrences(pool, channel_id, from_date, to_date, tz).await?;
+
+    let ids: Vec<Uuid> = occurrences.iter().map(|o| o.schedule.playlist_id).collect();
+    let mut playlists = HashMap::new();
+    for playlist in sqlx::query_as::<_, Playlist>("SELECT * FROM playlists WHERE id = ANY($1)")
+        .bind(&ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        match playlist.items() {
+            Ok(items) => {
+                playlists.insert(playlist.id, items);
+            }
+            Err(e) => log::error!("Playlist {} has invalid content: {}", playlist.id, e),
+        }
+    }
+
+    let schedule_ids: Vec<Uuid> = occurrences.iter().map(|o| o.schedule.id).collect();
+    let mut shifts: HashMap<_, Vec<Shift>> = HashMap::new();
+    for row in sqlx::query(
+        "SELECT schedule_id, occurrence_date, offset_seconds, applies_from FROM schedule_offsets
+         WHERE schedule_id = ANY($1) AND occurrence_date BETWEEN $2 AND $3",
+    )
+    .bind(&schedule_ids)
+    .bind(from_date)
+    .bind(to_date)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    {
+        shifts
+            .entry((row.get("schedule_id"), row.get("occurrence_date")))
+            .or_default()
+            .push(Shift {
+                from: row.get("applies_from"),
+                seconds: row.get("offset_seconds"),
+            });
+    }
+
+    Ok(TimelineInput {
+        occurrences,
+        playlists,
+        shifts,
+        default_transition: settings.and_then(|s| s.default_transition()),
+    })
+}
+
+/// Days ahead that recurring entries are checked for conflicts
+const CONFLICT_HORIZON_DAYS: i64 = 366;
+
+/// Existing occurrences that overlap `candidate` (recurrences expanded, playlist durations applied)
+pub async fn find_conflicts(
+    pool: &PgPool,
+    candidate: &Schedule,
+    tz: &Tz,
+) -> Result<Vec<Conflict>, String> {
+    // Past airings cannot conflict any more
+    let from = candidate.date.max(clock::today(tz));
+    let to = from + Duration::days(CONFLICT_HORIZON_DAYS);
+    let candidate_occurrences: Vec<Occurrence> = candidate
+        .occurrences(from, to)
+        .into_iter()
+        .filter_map(|date| Occurrence::new(candidate, date, tz))
+        .collect();
+    if candidate_occurrences.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // One extra day back for blocks spilling over midnight into the window
+    let existing: Vec<Occurrence> =
+        load_occurrences(pool, candidate.channel_id, from - Duration::days(1), to, tz)
+            .await?
+            .into_iter()
+            .filter(|o| o.schedule.id != candidate.id)
+            .collect();
+
+    let mut starts: Vec<DateTime<Utc>> = candidate_occurrences
+        .iter()
+        .chain(&existing)
+        .map(|o| o.start)
+        .collect();
+    starts.sort();
+
+    let mut conflicts = Vec::new();
+    for c in &candidate_occurrences {
+        for o in existing.iter().filter(|o| c.overlaps(o, &starts)) {
+            let new_wins = (candidate.priority, c.start) > (o.schedule.priority, o.start);
+            conflicts.push(Conflict {
+                schedule_id: o.schedule.id,
+                playlist_id: o.schedule.playlist_id,
+                playlist_name: o.schedule.playlist_name.clone(),
+                priority: o.schedule.priority,
+                date: o.date,
+                start: o.start,
+                end: o.end,
+                candidate_start: c.start,
+                winner: if new_wins { "new" } else { "existing" }.to_string(),
+            });
+        }
+    }
+    Ok(conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_bounds_follow_dst() {
+        let tz = clock::parse_tz("Europe/Lisbon").unwrap();
+        let six = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
+        let hours = |y, m, d| {
+            let (start, end) = day_bounds(NaiveDate::from_ymd_opt(y, m, d).unwrap(), six, &tz);
+            (end - start).num_hours()
+        };
+        assert_eq!(hours(2026, 3, 28), 23);
+        assert_eq!(hours(2026, 10, 24), 25);
+        assert_eq!(hours(2026, 7, 1), 24);
+    }
+
+    fn occurrence(hms: (u32, u32, u32), length_secs: Option<i64>) -> Occurrence {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let start_time = NaiveTime::from_hms_opt(hms.0, hms.1, hms.2).unwrap();
+        let start = date.and_time(start_time).and_utc();
+        Occurrence {
+            schedule: Schedule {
+                id: Uuid::new_v4(),
+                channel_id: Uuid::nil(),
+                playlist_id: Uuid::new_v4(),
+                date,
+                start_time: Some(start_time),
+                repeat_pattern: None,
+                created_at: Utc::now(),
+                recurrence: None,
+                priority: 0,
+                playlist_name: None,
+                playlist_duration: None,
+            },
+            date,
+            start,
+            end: length_secs.map(|l| start + Duration::seconds(l)),
+        }
+    }
+
+    #[test]
+    fn test_unknown_length_runs_until_next_start() {
+        let open = occurrence((10, 0, 0), None);
+        let next = occurrence((11, 0, 0), Some(600));
+        let later = occurrence((12, 0, 0), Some(600));
+        let earlier = occurrence((9, 0, 0), Some(7200));
+        let before = occurrence((9, 0, 0), Some(600));
+        let mut starts: Vec<_> = [&open, &next, &later, &earlier]
+            .iter()
+            .map(|o| o.start)
+            .collect();
+        starts.sort();
+
+        // The next block ends the open one; anything after that does not touch it either
+        assert!(!open.overlaps(&next, &starts));
+        assert!(!next.overlaps(&open, &starts));
+        assert!(!open.overlaps(&later, &starts));
+        // Another block at the same start is a clash
+        let same = occurrence((10, 0, 0), Some(600));
+        assert!(open.overlaps(&same, &starts));
+        // A block still on air when the open one starts
+        assert!(open.overlaps(&earlier, &starts));
+        assert!(!open.overlaps(&before, &starts));
+    }
+
+    #[test]
+    fn test_adjacent_blocks_do_not_overlap() {
+        let first = occurrence((10, 0, 0), Some(3600));
+        let second = occurrence((11, 0, 0), None);
+        let third = occurrence((12, 0, 0), Some(600));
+        let starts = vec![first.start, second.start, third.start];
+
+        assert!(!first.overlaps(&second, &starts));
+        assert!(!second.overlaps(&first, &starts));
+        assert!(!second.overlaps(&third, &starts));
+        assert!(!third.overlaps(&second, &starts));
+        // One second into the next block is still a clash
+        let late = occurrence((10, 0, 1), Some(3600));
+        assert!(late.overlaps(&second, &starts));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::models::channel::Channel;
+use crate::models::settings::Settings;
+use crate::services::engine::PlayoutEngine;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+/// Settings row of one channel
+pub async fn load_settings(pool: &PgPool, channel_id: Uuid) -> Result<Settings, String> {
+    sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE channel_id = $1")
+        .bind(channel_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// One playout engine per channel, looked up by id or slug
+pub struct ChannelRegistry {
+    engines: RwLock<HashMap<Uuid, Arc<PlayoutEngine>>>,
+}
+
+impl ChannelRegistry {
+    /// Start an engine for every channel in the database
+    pub async fn load(pool: &PgPool) -> Result<Arc<Self>, String> {
+        let channels = sqlx::query_as::<_, Channel>(
+            "SELECT * FROM channels ORDER BY is_default DESC, created_at ASC",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        if !channels.iter().any(|c| c.is_default) {
+            return Err("No default channel configured".to_string());
+        }
+
+        let registry = Arc::new(ChannelRegistry {
+            engines: RwLock::new(HashMap::new()),
+        });
+        for channel in channels {
+            registry.spawn(pool, channel);
+        }
+        Ok(registry)
+    }
+
+    /// Create and start the engine of a (new) channel
+    pub fn spawn(&self, pool: &PgPool, channel: Channel) -> Arc<PlayoutEngine> {
+        log::info!(
+            "📺 Channel '{}' ({}) on {}",
+            channel.name,
+            channel.slug,
+            channel.master_path()
+        );
+        std::fs::create_dir_all(channel.hls_dir()).ok();
+        let id = channel.id;
+        let engine = Arc::new(PlayoutEngine::new(pool.clone(), channel));
+        tokio::spawn(engine.clone().start());
+        self.engines.write().unwrap().insert(id, engine.clone());
+        engine
+    }
+
+    /// Stop a channel's engine for good and forget it
+    pub async fn remove(&self, id: Uuid) {
+        let engine = self.engines.write().unwrap().remove(&id);
+        if let Some(engine) = engine {
+            engine.retire().await;
+        }
+    }
+
+    /// Engine by channel id or slug
+    pub fn get(&self, key: &str) -> Option<Arc<PlayoutEngine>> {
+        let engines = self.engines.read().unwrap();
+        match Uuid::parse_str(key) {
+            Ok(id) => engines.get(&id).cloned(),
+            Err(_) => engines.values().find(|e| e.channel.slug == key).cloned(),
+        }
+    }
+
+    pub fn default_engine(&self) -> Arc<PlayoutEngine> {
+        self.engines
+            .read()
+            .unwrap()
+            .values()
+            .find(|e| e.channel.is_default)
+            .cloned()
+            .expect("default channel engine")
+    }
+
+    /// Engine serving an HLS preview request (`/hls/{slug}/...`, default channel otherwise)
+    pub fn for_hls_path(&self, path: &str) -> Arc<PlayoutEngine> {
+        path.trim_start_matches("/hls/")
+            .split_once('/')
+            .and_then(|(slug, _)| self.get(slug))
+            .unwrap_or_else(|| self.default_engine())
+    }
+}
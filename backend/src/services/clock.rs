@@ -0,0 +1,142 @@
+use crate::models::settings::Settings;
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Parse an IANA zone name ("Europe/Lisbon", "America/Sao_Paulo", "UTC")
+pub fn parse_tz(name: &str) -> Option<Tz> {
+    name.trim().parse::<Tz>().ok()
+}
+
+/// Zone of the host: TZ env var, then the system setting, then UTC
+pub fn host_tz() -> Tz {
+    std::env::var("TZ")
+        .ok()
+        .and_then(|tz| parse_tz(tz.trim_start_matches(':')))
+        .or_else(|| {
+            iana_time_zone::get_timezone()
+                .ok()
+                .and_then(|tz| parse_tz(&tz))
+        })
+        .unwrap_or(Tz::UTC)
+}
+
+/// Channel timezone from settings.timezone, host zone when unset or invalid
+pub fn channel_tz(settings: &Settings) -> Tz {
+    match settings.timezone.as_deref().filter(|tz| !tz.is_empty()) {
+        Some(name) => parse_tz(name).unwrap_or_else(|| {
+            log::warn!("Invalid channel timezone '{}', using host zone", name);
+            host_tz()
+        }),
+        None => host_tz(),
+    }
+}
+
+/// Channel timezone straight from the channel's settings row
+pub async fn load_tz(pool: &PgPool, channel_id: Uuid) -> Tz {
+    sqlx::query("SELECT timezone FROM settings WHERE channel_id = $1")
+        .bind(channel_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|row| row.try_get::<Option<String>, _>("timezone").ok().flatten())
+        .and_then(|name| parse_tz(&name))
+        .unwrap_or_else(host_tz)
+}
+
+/// Instant of a wall-clock time in `tz`, with DST made explicit:
+/// - a time skipped by spring-forward (e.g. 01:30 when clocks jump 01:00 -> 02:00) airs as
+///   if the clocks had not moved yet, i.e. shifted forward by the gap (02:30 new time);
+/// - a time repeated by fall-back airs once, at its first occurrence (summer time).
+pub fn to_utc(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(t) => t.with_timezone(&Utc),
+        LocalResult::Ambiguous(first, _) => first.with_timezone(&Utc),
+        LocalResult::None => {
+            // Offset in force before the gap (transitions are never a day apart)
+            let before = tz
+                .offset_from_utc_datetime(&(local - Duration::days(1)))
+                .fix();
+            Utc.from_utc_datetime(&(local - before))
+        }
+    }
+}
+
+/// Wall-clock time of an instant in `tz`
+pub fn local(tz: &Tz, at: DateTime<Utc>) -> NaiveDateTime {
+    at.with_timezone(tz).naive_local()
+}
+
+/// Calendar date in `tz` right now
+pub fn today(tz: &Tz) -> NaiveDate {
+    local(tz, Utc::now()).date()
+}
+
+/// Instant parsed from RFC 3339 ("2026-03-29T01:30:00Z", "...+01:00") or a naive
+/// wall-clock time ("2026-03-29T01:30:00"), which is read in the channel zone
+pub fn parse_instant(tz: &Tz, s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            [
+                "%Y-%m-%dT%H:%M:%S%.f",
+                "%Y-%m-%d %H:%M:%S%.f",
+                "%Y-%m-%dT%H:%M",
+            ]
+            .iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+            .map(|naive| to_utc(tz, naive))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lisbon(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        let tz = parse_tz("Europe/Lisbon").unwrap();
+        to_utc(
+            &tz,
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, min, 0)
+                .unwrap(),
+        )
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_spring_forward_gap_shifts_forward() {
+        // Lisbon skips 01:00-02:00 on 2026-03-29
+        assert_eq!(lisbon(2026, 3, 29, 0, 30), utc(2026, 3, 29, 0, 30));
+        assert_eq!(lisbon(2026, 3, 29, 1, 30), utc(2026, 3, 29, 1, 30));
+        assert_eq!(lisbon(2026, 3, 29, 2, 30), utc(2026, 3, 29, 1, 30));
+    }
+
+    #[test]
+    fn test_fall_back_takes_first_occurrence() {
+        // Lisbon repeats 01:00-02:00 on 2026-10-25
+        assert_eq!(lisbon(2026, 10, 25, 1, 30), utc(2026, 10, 25, 0, 30));
+        assert_eq!(lisbon(2026, 10, 25, 2, 30), utc(2026, 10, 25, 2, 30));
+    }
+
+    #[test]
+    fn test_parse_instant() {
+        let tz = parse_tz("Europe/Lisbon").unwrap();
+        assert_eq!(
+            parse_instant(&tz, "2026-07-01T12:00:00"),
+            Some(utc(2026, 7, 1, 11, 0))
+        );
+        assert_eq!(
+            parse_instant(&tz, "2026-07-01T12:00:00Z"),
+            Some(utc(2026, 7, 1, 12, 0))
+        );
+        assert_eq!(parse_instant(&tz, "noon"), None);
+    }
+}
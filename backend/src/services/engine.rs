@@ -1,8 +1,21 @@
-use crate::models::playlist::Playlist;
-use crate::models::schedule::Schedule;
+use crate::models::as_run::{EndReason, NewAsRun};
+use crate::models::channel::Channel;
+use crate::models::playlist::{PlaylistItem, Slideshow, Transition};
 use crate::models::settings::Settings;
-use crate::services::ffmpeg::FFmpegService;
-use chrono::{Datelike, Local, NaiveTime};
+use crate::services::as_run;
+use crate::services::broadcast_day;
+use crate::services::channels;
+use crate::services::clock;
+use crate::services::events::{self, PlayoutEvent};
+use crate::services::ffmpeg::{self, AudioPicture, FFmpegService, MediaInfo, MASTER_PIPE};
+use crate::services::filler::{self, FillerCandidate, FillerSegment};
+use crate::services::graphics::{self, EventLayers};
+use crate::services::loudness::{self, Loudness};
+use crate::services::program_feed::ProgramFeed;
+use crate::services::scte35::{self, CueSchedule};
+use crate::services::timeline::{self, EntryKind, TimelineEntry};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use std::collections::{HashMap, VecDeque};
@@ -10,7 +23,7 @@ use std::net::IpAddr;
 use std::process::Child;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 use tokio::time::{sleep, Duration};
 use uuid::Uuid;
 
@@ -27,6 +40,9 @@ pub struct PlayoutStatus {
     pub current_playlist_id: Option<Uuid>,
     pub current_playlist_name: Option<String>,
     pub display_urls: std::collections::HashMap<String, String>,
+    /// Emergency override holding the air
+    #[serde(rename = "override", default, skip_serializing_if = "Option::is_none")]
+    pub overriding: Option<OverrideInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,13 +56,313 @@ pub struct ActiveStream {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipInfo {
     pub filename: String,
+    /// Title from the item or media metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     pub duration: f64,
     pub position: f64,
 }
 
+/// How playback continues after a pause
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResumeMode {
+    /// Continue the clip from where it was paused (the schedule slides back)
+    Resume,
+    /// Jump back to whatever the schedule says is on air right now
+    Rejoin,
+}
+
+/// What is held on air while paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PauseHold {
+    /// Last frame of the interrupted clip
+    Freeze,
+    /// Configured default_image_path slate
+    Slate,
+}
+
+/// What an emergency override airs
+#[derive(Debug, Clone)]
+pub struct OverrideSource {
+    /// Media file or live stream URL
+    pub input: String,
+    pub media_id: Option<Uuid>,
+    pub title: Option<String>,
+}
+
+/// Emergency override on air
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideInfo {
+    pub input: String,
+    pub live: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// When the schedule takes over again; none for a live stream aired until it ends
+    pub until: Option<DateTime<Utc>>,
+}
+
+// Override on air and the concat list built for it
+struct OverrideState {
+    info: OverrideInfo,
+    list: Option<std::path::PathBuf>,
+}
+
+// Airtime of an override of a file `length` seconds long and how many times it is listed
+// to cover it: the requested duration (cut when it is up), else one pass
+fn override_fill(duration: Option<f64>, length: Option<f64>) -> Result<(f64, usize), String> {
+    let length = length.filter(|d| *d > 0.0).ok_or("Media has no duration")?;
+    let seconds = duration.unwrap_or(length);
+    let repeats = ((seconds / length).ceil() as usize).clamp(1, MAX_SLATE_REPEATS);
+    Ok((seconds, repeats))
+}
+
+// What the tick does with the override on air
+#[derive(Debug, PartialEq)]
+enum OverrideTurn {
+    Hold,
+    End(Option<EndReason>),
+}
+
+// `exit` is how its encoder ended (success or not), None while it runs. The override holds
+// until its time is up or its encoder exits; a clean exit is a single pass played out.
+fn override_turn(info: &OverrideInfo, exit: Option<bool>, now: DateTime<Utc>) -> OverrideTurn {
+    match exit {
+        None if info.until.is_none_or(|until| now < until) => OverrideTurn::Hold,
+        Some(false) => OverrideTurn::End(Some(EndReason::Failed)),
+        _ => OverrideTurn::End(None),
+    }
+}
+
+// Filler sequence currently covering a gap in the schedule, planned FILLER_WINDOW_SECS at
+// a time
+struct FillerPlan {
+    gap_end: DateTime<Utc>,
+    started_at: DateTime<Utc>,
+    segments: Vec<FillerSegment>,
+}
+
+impl FillerPlan {
+    // Index of the segment on air at `now` and how far into the plan it starts
+    fn position(&self, now: DateTime<Utc>) -> (usize, f64) {
+        let elapsed = (now - self.started_at).num_milliseconds() as f64 / 1000.0;
+        let mut acc = 0.0;
+        for (i, seg) in self.segments.iter().enumerate() {
+            if elapsed < acc + seg.duration {
+                return (i, acc);
+            }
+            acc += seg.duration;
+        }
+        let last = self.segments.len().saturating_sub(1);
+        (last, acc - self.segments.last().map_or(0.0, |s| s.duration))
+    }
+
+    fn window_end(&self) -> DateTime<Utc> {
+        self.started_at + seconds(filler::plan_duration(&self.segments))
+    }
+
+    // What is left of the plan from the segment on air: the segments, how far into the
+    // first one we are, and when it started
+    fn remainder(&self, now: DateTime<Utc>) -> (Vec<FillerSegment>, f64, DateTime<Utc>) {
+        let (index, acc) = self.position(now);
+        let start = self.started_at + seconds(acc);
+        let offset = ((now - start).num_milliseconds() as f64 / 1000.0).max(0.0);
+        (self.segments[index..].to_vec(), offset, start)
+    }
+}
+
+// Filler the preparer has checked (and conformed where a stream is missing), so gaps and
+// substitutions never probe or render on the playout path
+#[derive(Clone, Default)]
+struct FillerPool {
+    candidates: Vec<FillerCandidate>,
+    fallback: Option<FillerCandidate>,
+    // Concat source per filler path
+    sources: HashMap<String, String>,
+    params: u64,
+}
+
+impl FillerPool {
+    // Filler taking the airtime of an item that failed pre-flight, so the rest of its
+    // sequence keeps its timing
+    fn substitute(&self, item: &PlaylistItem) -> Vec<FillerSegment> {
+        filler::plan_fill(
+            item.effective_duration(),
+            &self.candidates,
+            self.fallback.as_ref(),
+        )
+    }
+
+    fn write_entries(
+        &self,
+        file: &mut std::fs::File,
+        segments: &[FillerSegment],
+    ) -> Result<(), String> {
+        for seg in segments {
+            let source = self.sources.get(&seg.path).unwrap_or(&seg.path);
+            write_concat_entry(file, source, None, seg.outpoint)?;
+        }
+        Ok(())
+    }
+}
+
+// Encoder started make-before-break: it takes over from the one on air once its first
+// frames reach the program feed
+struct Handover {
+    child: Child,
+    source: u64,
+    deadline: Instant,
+}
+
+// Open as-run row and the airing it belongs to
+struct AsRunOpen {
+    id: Uuid,
+    key: String,
+    filename: String,
+    planned_end: Option<DateTime<Utc>>,
+}
+
+// Modification time the probe ran against, and its outcome
+type PreflightResult = (Option<std::time::SystemTime>, Result<MediaInfo, String>);
+
+// Concat source of an item (the file itself or a render of it) and whether the item's
+// in/out points apply to it
+type Source = (String, bool);
+
+// Upcoming items for the preparer, nearest first, and the settings they air with
+#[derive(Default)]
+struct PrepareQueue {
+    items: Vec<PlaylistItem>,
+    settings: Option<Settings>,
+    params: u64,
+}
+
+// What the preparer made of an item
+struct Prepared {
+    at: Instant,
+    result: Result<Source, String>,
+}
+
+impl Prepared {
+    // Trusted for a while, and only as long as its render is still there
+    fn is_fresh(&self) -> bool {
+        self.at.elapsed() < Duration::from_secs(PREPARED_TTL_SECS)
+            && self
+                .result
+                .as_ref()
+                .map_or(true, |(source, _)| std::path::Path::new(source).exists())
+    }
+}
+
+// Rendered clips (conformed copies, stills, slideshows), pruned after a week
+const RENDER_DIR: &str = "onepa-conformed";
+const RENDER_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 3600);
+
+// Slot of a clip that kept crashing the encoder, covered by the failover slate
+struct Failover {
+    clip_path: String,
+    until: DateTime<Utc>,
+}
+
+impl Failover {
+    // Whether the slate still owns the slot: its time is not up and the schedule still has
+    // the failing clip on air
+    fn holds(&self, on_air_path: Option<&str>, now: DateTime<Utc>) -> bool {
+        now < self.until && on_air_path == Some(self.clip_path.as_str())
+    }
+}
+
+// Encoder failures per clip path and when the last one was
+#[derive(Default)]
+struct ClipFailures(HashMap<String, (u32, Instant)>);
+
+impl ClipFailures {
+    // Failures of `path` at `now` including this one; a clip that ran clean for
+    // FAILURE_WINDOW_SECS starts over
+    fn record(&mut self, path: &str, now: Instant) -> u32 {
+        let window = Duration::from_secs(FAILURE_WINDOW_SECS);
+        self.0
+            .retain(|_, (_, last)| now.saturating_duration_since(*last) < window);
+        let entry = self.0.entry(path.to_string()).or_insert((0, now));
+        entry.0 += 1;
+        entry.1 = now;
+        entry.0
+    }
+
+    fn clear(&mut self, path: &str) {
+        self.0.remove(path);
+    }
+}
+
+// What a master encoder dying unexpectedly leads to, besides bridging with the slate
+#[derive(Debug, PartialEq)]
+enum EncoderDeath {
+    // Not the clip's fault (or no clip to blame): bridge and carry on
+    Bridge,
+    // The clip's nth failure, below MAX_CLIP_FAILURES: restart it
+    Retry(u32),
+    // Failed MAX_CLIP_FAILURES times: mark it suspect and hold the slate over its slot
+    Failover(u32),
+}
+
+// Judge a master encoder death on `clip_path`. Nothing is counted while the failover slate
+// is on air already or when the publisher went away and took the encoder with it.
+fn judge_encoder_death(
+    clip_path: Option<&str>,
+    in_failover: bool,
+    feed_down: bool,
+    failures: &mut ClipFailures,
+    now: Instant,
+) -> EncoderDeath {
+    let Some(path) = clip_path.filter(|_| !in_failover && !feed_down) else {
+        return EncoderDeath::Bridge;
+    };
+    match failures.record(path, now) {
+        n if n >= MAX_CLIP_FAILURES => EncoderDeath::Failover(n),
+        n => EncoderDeath::Retry(n),
+    }
+}
+
+// Watchdog poll interval: bound on how long the master feed can stay empty
+const WATCHDOG_INTERVAL_MS: u64 = 250;
+// Encoder failures on the same clip before it is marked suspect
+const MAX_CLIP_FAILURES: u32 = 3;
+// Failures older than this are forgotten
+const FAILURE_WINDOW_SECS: u64 = 300;
+// Length of the pre-built slate loop
+const SLATE_LOOP_SECS: f64 = 6.0 * 3600.0;
+const MAX_SLATE_REPEATS: usize = 10_000;
+// How long a reconfigured encoder gets to go on air before the old one is stopped anyway
+const CUTOVER_TIMEOUT_SECS: u64 = 15;
+// Items after the one on air that a sequence takes, and that are prepared ahead
+const SEQUENCE_AHEAD: usize = 50;
+// The preparer goes over its queue again after this long even if nothing changed (files
+// get replaced, media gets flagged suspect)
+const PREPARE_RECHECK_SECS: u64 = 60;
+// Prepared items are checked again after this long, and forgotten once off the queue
+const PREPARED_TTL_SECS: u64 = 600;
+// Filler is planned this far ahead, and extended when less than FILLER_EXTEND_SECS is left
+const FILLER_WINDOW_SECS: f64 = 3600.0;
+const FILLER_EXTEND_SECS: i64 = 60;
+
+// Events a slow WebSocket client may fall behind by before it starts missing some
+const EVENT_BUFFER: usize = 256;
+
+struct PauseState {
+    paused_at: DateTime<Utc>,
+    schedule_key: Option<(Uuid, NaiveDate)>,
+    still_path: String,
+    clip: Option<ClipInfo>,
+}
+
 pub struct PlayoutEngine {
     pool: PgPool,
+    pub channel: Channel,
     current_process: Arc<Mutex<Option<Child>>>,
+    // Replacement encoder waiting to go on air; the watchdog swaps it in
+    handover: Arc<Mutex<Option<Handover>>>,
     current_clip_id: Arc<Mutex<Option<String>>>,
     pub is_running: Arc<Mutex<bool>>,
     pub last_error: Arc<Mutex<Option<String>>>,
@@ -55,6 +371,9 @@ pub struct PlayoutEngine {
     skip_requested: Arc<Mutex<bool>>,
     last_overlay_opacity: Arc<Mutex<f32>>,
     last_overlay_scale: Arc<Mutex<f32>>,
+    last_text_layers: Arc<Mutex<String>>,
+    last_transition: Arc<Mutex<Option<Transition>>>,
+    last_up_next: Arc<Mutex<String>>,
     last_output_url: Arc<Mutex<String>>,
     last_resolution: Arc<Mutex<String>>,
     last_video_bitrate: Arc<Mutex<String>>,
@@ -67,13 +386,54 @@ pub struct PlayoutEngine {
     relay_cooldowns: Arc<Mutex<HashMap<String, std::time::Instant>>>,
     pub hls_sessions: Arc<Mutex<HashMap<String, Instant>>>,
     pub preview_ips: Arc<Mutex<HashMap<IpAddr, Instant>>>,
+    // Pause/Resume: slate held on the master feed
+    pause_state: Arc<Mutex<Option<PauseState>>>,
+    current_clip_path: Arc<Mutex<Option<String>>>,
+    active_schedule: Arc<Mutex<Option<(Uuid, NaiveDate)>>>,
+    // Broadcast day the offset ledger was last rolled for
+    ledger_day: Arc<Mutex<Option<NaiveDate>>>,
+    filler_plan: Arc<Mutex<Option<FillerPlan>>>,
+    // As-run log: row of the clip currently on air
+    as_run_open: Arc<Mutex<Option<AsRunOpen>>>,
+    // Watchdog: recent encoder failures per clip, failover slate holding a slot
+    clip_failures: Arc<Mutex<ClipFailures>>,
+    failover: Arc<Mutex<Option<Failover>>>,
+    slate_loop: Arc<Mutex<Option<(String, std::path::PathBuf)>>>,
+    // Pre-flight: probe results per path (keyed by mtime), clips of the running sequence
+    // that were replaced by filler
+    preflight_cache: Arc<Mutex<HashMap<String, PreflightResult>>>,
+    substituted_clips: Arc<Mutex<std::collections::HashSet<String>>>,
+    // Background preparation: items coming up in the rundown (nearest first) and the
+    // signal that they changed
+    prepare_queue: Arc<Mutex<PrepareQueue>>,
+    prepare_wake: Arc<tokio::sync::Notify>,
+    // Prepared items by `prepare_key`, and the filler ready to air
+    prepared: Arc<Mutex<HashMap<u64, Prepared>>>,
+    filler_pool: Arc<Mutex<FillerPool>>,
+    // Live push to /api/ws subscribers
+    events: broadcast::Sender<PlayoutEvent>,
+    last_published_status: Arc<Mutex<Option<serde_json::Value>>>,
+    // Channel timezone from settings, refreshed every tick (wall-clock times in logs)
+    channel_tz: Arc<Mutex<Tz>>,
+    // Consecutive checks the master feed was down, before relays are stopped
+    master_inactive_count: std::sync::atomic::AtomicU32,
+    // Set when the channel is deleted: the tick loop and watchdog exit
+    retired: Arc<Mutex<bool>>,
+    // Upcoming ad-break splice points, read by the MPEG-TS outputs' SCTE-35 injectors
+    cue_schedule: CueSchedule,
+    // Publisher of the master path; encoders are spliced into it
+    program_feed: std::sync::Mutex<Option<ProgramFeed>>,
+    // Emergency break-in, ahead of the schedule
+    override_state: Arc<Mutex<Option<OverrideState>>>,
 }
 
 impl PlayoutEngine {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: PgPool, channel: Channel) -> Self {
         PlayoutEngine {
             pool,
+            channel,
             current_process: Arc::new(Mutex::new(None)),
+            handover: Arc::new(Mutex::new(None)),
             current_clip_id: Arc::new(Mutex::new(None)),
             is_running: Arc::new(Mutex::new(false)),
             last_error: Arc::new(Mutex::new(None)),
@@ -89,11 +449,15 @@ impl PlayoutEngine {
                 current_playlist_id: None,
                 current_playlist_name: None,
                 display_urls: std::collections::HashMap::new(),
+                overriding: None,
             })),
             engine_start_time: Arc::new(Mutex::new(None)),
             skip_requested: Arc::new(Mutex::new(false)),
             last_overlay_opacity: Arc::new(Mutex::new(1.0)),
             last_overlay_scale: Arc::new(Mutex::new(1.0)),
+            last_text_layers: Arc::new(Mutex::new(String::new())),
+            last_transition: Arc::new(Mutex::new(None)),
+            last_up_next: Arc::new(Mutex::new(String::new())),
             last_output_url: Arc::new(Mutex::new("".to_string())),
             last_resolution: Arc::new(Mutex::new("1920x1080".to_string())),
             last_video_bitrate: Arc::new(Mutex::new("5000k".to_string())),
@@ -105,6 +469,29 @@ impl PlayoutEngine {
             relay_cooldowns: Arc::new(Mutex::new(HashMap::new())),
             hls_sessions: Arc::new(Mutex::new(HashMap::new())),
             preview_ips: Arc::new(Mutex::new(HashMap::new())),
+            pause_state: Arc::new(Mutex::new(None)),
+            current_clip_path: Arc::new(Mutex::new(None)),
+            active_schedule: Arc::new(Mutex::new(None)),
+            ledger_day: Arc::new(Mutex::new(None)),
+            filler_plan: Arc::new(Mutex::new(None)),
+            as_run_open: Arc::new(Mutex::new(None)),
+            clip_failures: Arc::new(Mutex::new(ClipFailures::default())),
+            failover: Arc::new(Mutex::new(None)),
+            slate_loop: Arc::new(Mutex::new(None)),
+            preflight_cache: Arc::new(Mutex::new(HashMap::new())),
+            substituted_clips: Arc::new(Mutex::new(std::collections::HashSet::new())),
+            prepare_queue: Arc::new(Mutex::new(PrepareQueue::default())),
+            prepare_wake: Arc::new(tokio::sync::Notify::new()),
+            prepared: Arc::new(Mutex::new(HashMap::new())),
+            filler_pool: Arc::new(Mutex::new(FillerPool::default())),
+            events: broadcast::channel(EVENT_BUFFER).0,
+            last_published_status: Arc::new(Mutex::new(None)),
+            channel_tz: Arc::new(Mutex::new(clock::host_tz())),
+            master_inactive_count: std::sync::atomic::AtomicU32::new(0),
+            retired: Arc::new(Mutex::new(false)),
+            cue_schedule: Arc::new(std::sync::Mutex::new(Vec::new())),
+            program_feed: std::sync::Mutex::new(None),
+            override_state: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -117,8 +504,62 @@ impl PlayoutEngine {
         if logs.len() >= 20 {
             logs.pop_front();
         }
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        logs.push_back(format!("[{}] {}", timestamp, msg));
+        let timestamp = self.wall_clock(Utc::now(), "%Y-%m-%d %H:%M:%S").await;
+        let line = format!("[{}] {}", timestamp, msg);
+        logs.push_back(line.clone());
+        self.emit(PlayoutEvent::Log { message: line });
+    }
+
+    // Instant formatted as channel wall-clock time
+    async fn wall_clock(&self, at: DateTime<Utc>, fmt: &str) -> String {
+        clock::local(&*self.channel_tz.lock().await, at)
+            .format(fmt)
+            .to_string()
+    }
+
+    /// Receive engine events (status diffs, clip transitions, logs, relay changes)
+    pub fn subscribe(&self) -> broadcast::Receiver<PlayoutEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: PlayoutEvent) {
+        // Err only means nobody is listening
+        let _ = self.events.send(event);
+    }
+
+    // Push what changed in PlayoutStatus since the last call, plus relay up/down
+    async fn publish_status(&self) {
+        let status = self.status.lock().await.clone();
+        let Ok(current) = serde_json::to_value(&status) else {
+            return;
+        };
+        let mut last = self.last_published_status.lock().await;
+
+        let previous_streams: HashMap<String, String> = last
+            .as_ref()
+            .and_then(|v| v.get("active_streams"))
+            .and_then(|v| serde_json::from_value::<Vec<ActiveStream>>(v.clone()).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| (s.protocol, s.status))
+            .collect();
+        for stream in &status.active_streams {
+            let previous = previous_streams.get(&stream.protocol);
+            if previous != Some(&stream.status) {
+                self.emit(PlayoutEvent::Relay {
+                    protocol: stream.protocol.clone(),
+                    status: stream.status.clone(),
+                    previous: previous.cloned(),
+                    up: stream.status == "active",
+                });
+            }
+        }
+
+        let changes = events::status_diff(last.as_ref(), &current);
+        if !changes.is_empty() {
+            self.emit(PlayoutEvent::StatusDiff { changes });
+        }
+        *last = Some(current);
     }
 
     pub async fn skip_current_clip(&self) {
@@ -126,21 +567,34 @@ impl PlayoutEngine {
         *skip = true;
     }
 
+    // Settings row of this engine's channel
+    async fn settings(&self) -> Result<Settings, String> {
+        channels::load_settings(&self.pool, self.channel.id).await
+    }
+
+    /// Take the channel off air for good (channel deleted): encoder and relays are stopped
+    /// and the tick loop exits
+    pub async fn retire(&self) {
+        *self.retired.lock().await = true;
+        *self.is_running.lock().await = false;
+        self.as_run_end(Some(EndReason::Stopped)).await;
+        self.stop_process().await;
+        for (_, mut child) in self.distribution_processes.lock().await.drain() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+        log::info!("Channel {} retired", self.channel.slug);
+    }
+
     pub async fn set_running(&self, running: bool) {
         let mut r = self.is_running.lock().await;
         *r = running;
 
-        if !running {
-            let _ = sqlx::query("UPDATE settings SET is_running = $1 WHERE id = TRUE")
-                .bind(running)
-                .execute(&self.pool)
-                .await;
-        } else {
-            let _ = sqlx::query("UPDATE settings SET is_running = $1 WHERE id = TRUE")
-                .bind(running)
-                .execute(&self.pool)
-                .await;
-        }
+        let _ = sqlx::query("UPDATE settings SET is_running = $1 WHERE channel_id = $2")
+            .bind(running)
+            .bind(self.channel.id)
+            .execute(&self.pool)
+            .await;
 
         let mut status = self.status.lock().await;
         let mut start_time = self.engine_start_time.lock().await;
@@ -152,6 +606,12 @@ impl PlayoutEngine {
             status.uptime = 0;
             status.active_streams.clear();
             *start_time = None;
+            *self.pause_state.lock().await = None;
+            status.overriding = None;
+            if let Some(list) = self.override_state.lock().await.take().and_then(|o| o.list) {
+                std::fs::remove_file(list).ok();
+            }
+            self.as_run_end(Some(EndReason::Stopped)).await;
             self.stop_process().await;
         } else {
             status.status = "playing".to_string();
@@ -161,7 +621,8 @@ impl PlayoutEngine {
             // Clear last error when manually starting
             let mut err = self.last_error.lock().await;
             *err = None;
-            let _ = sqlx::query("UPDATE settings SET last_error = NULL WHERE id = TRUE")
+            let _ = sqlx::query("UPDATE settings SET last_error = NULL WHERE channel_id = $1")
+                .bind(self.channel.id)
                 .execute(&self.pool)
                 .await;
 
@@ -172,12 +633,9 @@ impl PlayoutEngine {
 
             // Log which protocols will be active
             let pool = self.pool.clone();
+            let channel_id = self.channel.id;
             tokio::spawn(async move {
-                if let Ok(settings) =
-                    sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE id = TRUE")
-                        .fetch_one(&pool)
-                        .await
-                {
+                if let Ok(settings) = channels::load_settings(&pool, channel_id).await {
                     log::info!(
                         "Engine started with: RTMP={}, SRT={}, UDP={}",
                         settings.rtmp_enabled,
@@ -200,657 +658,2603 @@ impl PlayoutEngine {
         *self.is_running.lock().await
     }
 
-    pub async fn start(self: Arc<Self>) {
-        log::info!("Playout Engine started");
+    pub async fn is_paused(&self) -> bool {
+        self.pause_state.lock().await.is_some()
+    }
 
-        // Load initial state from DB
-        if let Ok(settings) =
-            sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE id = TRUE")
-                .fetch_one(&self.pool)
-                .await
-        {
-            let mut r = self.is_running.lock().await;
-            *r = settings.is_running;
-            let mut err = self.last_error.lock().await;
-            *err = settings.last_error;
+    /// Hold the master feed on a freeze frame or slate until resumed
+    pub async fn pause(&self, hold: PauseHold) -> Result<(), String> {
+        if !*self.is_running.lock().await {
+            return Err("Playout engine is not running".to_string());
+        }
+        if self.is_paused().await {
+            return Err("Playout is already paused".to_string());
+        }
+        if self.override_state.lock().await.is_some() {
+            return Err("An override is on air".to_string());
+        }
 
-            // If running, initialize start time
-            if settings.is_running {
-                let mut start_time = self.engine_start_time.lock().await;
-                *start_time = Some(Local::now());
+        let settings = self.settings().await?;
+
+        let clip = self.status.lock().await.current_clip.clone();
+        let clip_path = self.current_clip_path.lock().await.clone();
+
+        let slate_path = settings
+            .default_image_path
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(resolve_asset_path);
+
+        // Freeze frame: grab the current picture of the interrupted clip
+        let still_path = match (hold, clip_path.as_ref(), clip.as_ref()) {
+            (PauseHold::Freeze, Some(path), Some(c)) => {
+                let frame_path = std::env::temp_dir().join(format!(
+                    "pause_{}_{}.jpg",
+                    self.channel.slug,
+                    Local::now().timestamp_millis()
+                ));
+                let frame_str = frame_path.to_string_lossy().to_string();
+                let (input, output, position) = (path.clone(), frame_str.clone(), c.position);
+                let extracted = tokio::task::spawn_blocking(move || {
+                    FFmpegService::new().extract_frame(&input, &output, position)
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r);
+                match extracted {
+                    Ok(_) => Some(frame_str),
+                    Err(e) => {
+                        log::warn!("Freeze frame extraction failed, using slate: {}", e);
+                        slate_path.clone()
+                    }
+                }
             }
+            _ => slate_path.clone(),
+        }
+        .ok_or("No freeze frame available and no default image configured")?;
 
-            // Sync clips counter from DB
-            let mut status = self.status.lock().await;
-            status.clips_played_today = settings.clips_played_today.unwrap_or(0);
+        // Stop the running sequence without clearing HLS sessions (feed stays up)
+        self.cancel_handover().await;
+        if let Some(mut child) = self.current_process.lock().await.take() {
+            child.kill().ok();
+            child.wait().ok();
         }
+        self.current_sequence.lock().await.clear();
+        *self.current_clip_id.lock().await = None;
+        self.as_run_end(Some(EndReason::Paused)).await;
+
+        let child = self.start_still(&still_path, &settings)?;
+        *self.current_process.lock().await = Some(child);
+
+        *self.pause_state.lock().await = Some(PauseState {
+            paused_at: Utc::now(),
+            schedule_key: *self.active_schedule.lock().await,
+            still_path,
+            clip: clip.clone(),
+        });
 
-        loop {
-            let is_running = *self.is_running.lock().await;
-            log::debug!("Engine tick loop active (is_running: {})", is_running);
-            if let Err(e) = self.tick().await {
-                log::error!("Engine tick error: {}", e);
-                let mut err = self.last_error.lock().await;
-                *err = Some(e.clone());
+        self.status.lock().await.status = "paused".to_string();
 
-                // Persist error to DB
-                let _ = sqlx::query("UPDATE settings SET last_error = $1 WHERE id = TRUE")
-                    .bind(&e)
-                    .execute(&self.pool)
-                    .await;
-            }
+        let label = clip.map(|c| c.filename).unwrap_or_default();
+        log::info!("⏸ Playout paused ({:?}) on {}", hold, label);
+        self.add_log(format!("⏸ Playout paused on {}", label)).await;
+        Ok(())
+    }
 
-            // --- Handle Skip Request ---
-            let mut skip = self.skip_requested.lock().await;
-            if *skip {
-                log::info!("Processing skip request...");
-                if let Err(e) = self.process_skip().await {
-                    log::error!("Failed to process skip: {}", e);
-                }
-                *skip = false;
-            }
-            // ---------------------------
+    /// Leave the pause slate and go back to the scheduled programme
+    pub async fn resume(&self, mode: ResumeMode) -> Result<(), String> {
+        let state = self
+            .pause_state
+            .lock()
+            .await
+            .take()
+            .ok_or("Playout is not paused")?;
 
-            sleep(Duration::from_secs(1)).await;
+        self.cancel_handover().await;
+        if let Some(mut child) = self.current_process.lock().await.take() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+        if state
+            .still_path
+            .starts_with(&*std::env::temp_dir().to_string_lossy())
+        {
+            std::fs::remove_file(&state.still_path).ok();
         }
-    }
-
-    async fn process_skip(&self) -> Result<(), String> {
-        let status = self.status.lock().await;
-        if let Some(ref current) = status.current_clip {
-            let remaining = current.duration - current.position;
-            if remaining > 0.0 {
-                log::info!(
-                    "Skipping clip. Advancing schedule by {} seconds.",
-                    remaining
-                );
-                // Update schedule start_time by shifting it back
-                // We target the current ACTIVE schedule item
-                let _ = sqlx::query("UPDATE schedule SET start_time = start_time - ($1 * interval '1 second') WHERE id = (
-                    SELECT id FROM schedule 
-                    WHERE (date = current_date OR repeat_pattern != 'none')
-                    AND start_time <= current_time 
-                    ORDER BY CASE WHEN date = current_date THEN 0 ELSE 1 END, start_time DESC 
-                    LIMIT 1
-                )")
-                    .bind(remaining)
-                    .execute(&self.pool)
-                    .await
-                    .map_err(|e| e.to_string())?;
 
-                log::info!(
-                    "Skip: Schedule shifted by {}s. Stopping process for reload.",
-                    remaining
-                );
+        let paused_for = (Utc::now() - state.paused_at).num_milliseconds() as f64 / 1000.0;
 
-                // Force immediate process stop to pick up new clip on next tick
-                self.stop_process().await;
+        // Resume: slide this schedule occurrence back by the time spent paused
+        if mode == ResumeMode::Resume {
+            if let Some(key) = state.schedule_key {
+                self.adjust_schedule_offset(key, -paused_for).await?;
             }
         }
+
+        {
+            let mut status = self.status.lock().await;
+            status.status = "playing".to_string();
+            status.current_clip = state.clip;
+        }
+
+        log::info!(
+            "▶ Playout resumed ({:?}) after {:.1}s paused",
+            mode,
+            paused_for
+        );
+        self.add_log(format!("▶ Playout resumed after {:.0}s", paused_for))
+            .await;
         Ok(())
     }
-    async fn tick(&self) -> Result<(), String> {
-        // Update Stream Stats and Distribution (even if engine stopped so protocols show)
-        let settings = sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE id = TRUE")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        self.manage_distribution(&settings).await;
-        self.update_stream_stats(&settings).await;
 
-        // Check if engine is enabled
+    /// Break into the programme with a media item or live stream: once through, or for
+    /// `duration` seconds (a live stream without one stays until it ends). The schedule
+    /// takes over again at its wall-clock position.
+    pub async fn start_override(
+        &self,
+        source: OverrideSource,
+        duration: Option<f64>,
+    ) -> Result<OverrideInfo, String> {
         if !*self.is_running.lock().await {
-            return Ok(());
+            return Err("Playout engine is not running".to_string());
+        }
+        if self.is_paused().await {
+            return Err("Playout is paused".to_string());
         }
 
-        // Update uptime and sync logs
-        {
-            let mut status = self.status.lock().await;
-            if status.status == "playing" {
-                if let Some(start) = *self.engine_start_time.lock().await {
-                    status.uptime = (Local::now() - start).num_seconds();
+        let settings = self.settings().await?;
+        let live = ffmpeg::is_live_input(&source.input);
+        // Probed and conformed (both on the blocking pool) before anything on air is touched
+        let (input, airtime, list) = self.prepare_override(&source, duration, &settings).await?;
+
+        // Make-before-break: the schedule stays on air until the override's frames are, and
+        // keeps it if the override encoder cannot start
+        let (child, feed_source) = match self.spawn_master_input(&input, &settings, "[Override]") {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                if let Some(list) = list {
+                    std::fs::remove_file(list).ok();
                 }
+                return Err(e);
             }
-            let logs_buffer = self.logs.lock().await;
-            status.logs = logs_buffer.iter().cloned().collect();
-        }
+        };
 
-        let now = Local::now();
-        let current_date = now.date_naive();
-        let current_time = now.time();
+        let started_at = Utc::now();
+        let info = OverrideInfo {
+            input: source.input.clone(),
+            live,
+            title: source.title.clone(),
+            started_at,
+            until: airtime.map(|s| started_at + seconds(s)),
+        };
 
-        // 1. Fetch settings
-        let settings = sqlx::query_as::<_, Settings>("SELECT * FROM settings WHERE id = TRUE")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+        // Held first, so the tick leaves the air alone from here on
+        let previous = self.override_state.lock().await.replace(OverrideState {
+            info: info.clone(),
+            list,
+        });
+        *self.filler_plan.lock().await = None;
+        self.cue_schedule.lock().unwrap().clear();
+        self.begin_handover(child, feed_source).await;
+        if let Some(list) = previous.and_then(|p| p.list) {
+            std::fs::remove_file(list).ok();
+        }
+        self.current_sequence.lock().await.clear();
+        *self.current_clip_id.lock().await = None;
 
-        // 2. Find scheduled playlist (Better logic)
-        // a. Today's direct schedule
-        let mut schedule = sqlx::query_as::<_, Schedule>(
-            "SELECT s.*, p.name as playlist_name 
-             FROM schedule s 
-             JOIN playlists p ON s.playlist_id = p.id 
-             WHERE s.date = $1 AND s.repeat_pattern IS NULL
-             AND NOT EXISTS (
-                 SELECT 1 FROM schedule_exceptions se 
-                 WHERE se.schedule_id = s.id AND se.exception_date = $1
-             )
-             ORDER BY s.start_time ASC LIMIT 1",
+        let filename = if live {
+            source.input.clone()
+        } else {
+            file_name_of(&source.input)
+        };
+        self.as_run_start(
+            format!("override:{}", started_at.timestamp_millis()),
+            NewAsRun {
+                source: "override".to_string(),
+                media_id: source.media_id,
+                clip_path: source.input.clone(),
+                filename: Some(filename.clone()),
+                title: source.title.clone(),
+                planned_start: Some(started_at),
+                planned_end: info.until,
+                ..Default::default()
+            },
         )
-        .bind(current_date)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await;
 
-        // b. Daily repeats
-        let daily = sqlx::query_as::<_, Schedule>(
-            "SELECT s.*, p.name as playlist_name 
-             FROM schedule s 
-             JOIN playlists p ON s.playlist_id = p.id 
-             WHERE s.repeat_pattern = 'daily' AND s.date <= $1
-             AND NOT EXISTS (
-                 SELECT 1 FROM schedule_exceptions se 
-                 WHERE se.schedule_id = s.id AND se.exception_date = $1
-             )
-             ORDER BY s.date DESC LIMIT 1",
-        )
-        .bind(current_date)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-        schedule.extend(daily);
-
-        // c. Weekly repeats
-        let day_of_week = current_date.weekday().num_days_from_monday();
-        let weekly = sqlx::query_as::<_, Schedule>(
-            "SELECT s.*, p.name as playlist_name 
-             FROM schedule s 
-             JOIN playlists p ON s.playlist_id = p.id 
-             WHERE s.repeat_pattern = 'weekly' 
-             AND EXTRACT(DOW FROM s.date) = $1 AND s.date <= $2
-             AND NOT EXISTS (
-                 SELECT 1 FROM schedule_exceptions se 
-                 WHERE se.schedule_id = s.id AND se.exception_date = $2
-             )
-             ORDER BY s.date DESC LIMIT 1",
-        )
-        .bind(day_of_week as i32)
-        .bind(current_date)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-        schedule.extend(weekly);
+        let schedule_source = match info.until {
+            Some(until) => format!(
+                "Override (until {})",
+                self.wall_clock(until, "%H:%M:%S").await
+            ),
+            None => "Override (live)".to_string(),
+        };
+        {
+            let mut status = self.status.lock().await;
+            status.status = "playing".to_string();
+            status.current_clip = Some(ClipInfo {
+                filename: filename.clone(),
+                title: source.title,
+                duration: airtime.unwrap_or(0.0),
+                position: 0.0,
+            });
+            status.schedule_source = Some(schedule_source);
+            status.overriding = Some(info.clone());
+        }
+
+        log::warn!("🚨 Override on air: {} ({:?}s)", filename, airtime);
+        self.add_log(format!("🚨 Override on air: {}", filename))
+            .await;
+        Ok(info)
+    }
 
-        // Sort all by start_time descending to find the most recent one that started
-        schedule.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+    // Input to put on air for an override, its airtime (None: until a live stream ends) and
+    // the concat list written for it
+    async fn prepare_override(
+        &self,
+        source: &OverrideSource,
+        duration: Option<f64>,
+        settings: &Settings,
+    ) -> Result<(String, Option<f64>, Option<std::path::PathBuf>), String> {
+        if ffmpeg::is_live_input(&source.input) {
+            return Ok((source.input.clone(), duration, None));
+        }
+        let info = self
+            .preflight(&source.input)
+            .await
+            .map_err(|e| format!("{}: {}", file_name_of(&source.input), e))?;
+        if ffmpeg::is_still_image(&source.input) {
+            let seconds = duration.ok_or("A still image needs a duration")?;
+            return Ok((source.input.clone(), Some(seconds), None));
+        }
+        let clip = self
+            .conformed_path(&source.input, &info, None, settings)
+            .await?;
+        let (seconds, repeats) = override_fill(duration, info.duration)?;
+        let path = std::env::temp_dir().join(format!(
+            "override_{}_{}.txt",
+            self.channel.slug,
+            Local::now().timestamp_millis()
+        ));
+        let mut file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+        for _ in 0..repeats {
+            write_concat_entry(&mut file, &clip, None, None)?;
+        }
+        Ok((
+            path.to_string_lossy().to_string(),
+            Some(seconds),
+            Some(path),
+        ))
+    }
 
-        log::debug!("ALL retrieved schedules: {:?}", schedule);
+    /// Take the override off air before its time
+    pub async fn end_override(&self) -> Result<(), String> {
+        if self.finish_override(None).await {
+            Ok(())
+        } else {
+            Err("No override on air".to_string())
+        }
+    }
 
-        let mut active_playlist_id = None;
-        let mut playlist_start_time = None;
+    // Called by the tick: true while the override holds the air, ends it once its time is
+    // up or its encoder exits
+    async fn hold_override(&self) -> bool {
+        let Some(info) = self
+            .override_state
+            .lock()
+            .await
+            .as_ref()
+            .map(|o| o.info.clone())
+        else {
+            return false;
+        };
+        // Until the handover the encoder on air is still the schedule's
+        let pending = self.handover.lock().await.is_some();
+        let exit = self
+            .current_process
+            .lock()
+            .await
+            .as_mut()
+            .filter(|_| !pending)
+            .and_then(|c| match c.try_wait() {
+                Ok(None) => None,
+                Ok(Some(status)) => Some(status.success()),
+                Err(_) => Some(false),
+            });
 
-        log::debug!("Evaluating {} schedules for today", schedule.len());
+        let now = Utc::now();
+        match override_turn(&info, exit, now) {
+            OverrideTurn::Hold => {
+                self.cue_schedule.lock().unwrap().clear();
+                if let Some(clip) = self.status.lock().await.current_clip.as_mut() {
+                    clip.position = (now - info.started_at).num_milliseconds() as f64 / 1000.0;
+                }
+                return true;
+            }
+            OverrideTurn::End(reason) => {
+                if reason == Some(EndReason::Failed) {
+                    log::error!("🚨 Override encoder failed on {}", info.input);
+                }
+                self.finish_override(reason).await;
+            }
+        }
+        false
+    }
 
-        for s in schedule {
-            if let Some(st) = s.start_time {
-                if current_time >= st {
-                    // For direct schedules (not repeating), ensure we're on the correct date
-                    let is_valid_date = match s.repeat_pattern.as_deref() {
-                        Some("daily") | Some("weekly") => true, // Repeating schedules are always valid
-                        _ => s.date == current_date, // Direct schedules must match the date exactly
-                    };
+    // Take the override off air; the next tick rejoins the schedule at wall-clock
+    async fn finish_override(&self, reason: Option<EndReason>) -> bool {
+        let Some(state) = self.override_state.lock().await.take() else {
+            return false;
+        };
+        // Not on air yet: the schedule encoder still is and carries on
+        if !self.cancel_handover().await {
+            if let Some(mut child) = self.current_process.lock().await.take() {
+                child.kill().ok();
+                child.wait().ok();
+            }
+        }
+        self.close_override(state, reason).await;
+        true
+    }
 
-                    if !is_valid_date {
-                        log::debug!(
-                            "⏩ Skipping direct schedule from {} (ID: {}) - today is {}",
-                            s.date.format("%Y-%m-%d"),
-                            s.id,
-                            current_date.format("%Y-%m-%d")
-                        );
-                        continue;
-                    }
+    // Clear what an override left behind once its encoder is gone
+    async fn close_override(&self, state: OverrideState, reason: Option<EndReason>) {
+        if let Some(list) = state.list {
+            std::fs::remove_file(list).ok();
+        }
+        self.as_run_end(reason).await;
+        {
+            let mut status = self.status.lock().await;
+            status.current_clip = None;
+            status.schedule_source = None;
+            status.overriding = None;
+        }
+
+        log::info!("⏹ Override ended, back to the schedule");
+        self.add_log("⏹ Override ended, back to the schedule".to_string())
+            .await;
+    }
+
+    // Loop a still image (freeze frame / slate) on the master feed
+    fn start_still(&self, still_path: &str, settings: &Settings) -> Result<Child, String> {
+        self.start_master_input(still_path, settings, "[Pause Slate]")
+    }
+
+    // Push a single input (still image or concat list) to the master feed
+    fn start_master_input(
+        &self,
+        input: &str,
+        settings: &Settings,
+        label: &'static str,
+    ) -> Result<Child, String> {
+        self.spawn_master_input(input, settings, label)
+            .map(|(child, _)| child)
+    }
+
+    // `start_master_input`, with the feed source the encoder was attached as
+    fn spawn_master_input(
+        &self,
+        input: &str,
+        settings: &Settings,
+        label: &'static str,
+    ) -> Result<(Child, u64), String> {
+        let logo_path = resolve_logo_path(settings);
+        let mut child = FFmpegService::new().start_stream(
+            input,
+            MASTER_PIPE,
+            0.0,
+            settings,
+            None,
+            logo_path.as_deref(),
+            &self.base_layers(settings),
+            None,
+        )?;
+        let source = self.feed_master(&mut child, settings)?;
+
+        if let Some(stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                let reader = std::io::BufReader::new(stderr);
+                use std::io::BufRead;
+                for line in reader.lines().map_while(Result::ok) {
+                    if line.contains("Error") || line.contains("failed") {
+                        log::error!("{} {}", label, line);
+                    } else {
+                        log::debug!("{} {}", label, line);
+                    }
+                }
+            });
+        }
+        Ok((child, source))
+    }
+
+    // Put a master encoder on the program feed, (re)starting the publisher when it is down,
+    // its outputs (preview, DASH/MSS/RIST) changed or the encoder's format differs from the
+    // one it carries; the encoder is killed if it cannot be attached
+    fn feed_master(&self, child: &mut Child, settings: &Settings) -> Result<u64, String> {
+        let hls_dir = self.channel.hls_dir();
+        std::fs::create_dir_all(&hls_dir).ok();
+        let args = FFmpegService::new().feed_publisher_args(
+            &self.channel.master_url("mediamtx"),
+            &hls_dir.to_string_lossy(),
+            settings,
+        );
+        let format = ffmpeg::master_format(settings);
+        let mut feed = self.program_feed.lock().unwrap();
+        let attached = (|| {
+            if let Some(f) = feed.as_mut().filter(|f| f.format() != Some(&format)) {
+                if f.is_alive() {
+                    log::warn!(
+                        "Program feed: output format changes from {} to {}, switching break-before-make (the master path restarts)",
+                        f.format().unwrap_or("one the feed refused"),
+                        format
+                    );
+                }
+            }
+            if !feed
+                .as_mut()
+                .is_some_and(|f| f.is_alive() && f.publishes(&args) && f.format() == Some(&format))
+            {
+                // Free the path before publishing to it again
+                *feed = None;
+                *feed = Some(ProgramFeed::start(args, format)?);
+            }
+            feed.as_mut()
+                .ok_or("Program feed not running")?
+                .attach(child)
+        })();
+        if attached.is_err() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+        attached
+    }
+
+    fn feed_alive(&self) -> bool {
+        self.program_feed
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_some_and(|f| f.is_alive())
+    }
+
+    // Make-before-break: `child` (attached to the feed as `source`) replaces the encoder on
+    // air once it is on air itself; nothing waits for it here
+    async fn begin_handover(&self, child: Child, source: u64) {
+        let previous = self.handover.lock().await.replace(Handover {
+            child,
+            source,
+            deadline: Instant::now() + Duration::from_secs(CUTOVER_TIMEOUT_SECS),
+        });
+        if let Some(mut stale) = previous {
+            stale.child.kill().ok();
+            stale.child.wait().ok();
+        }
+    }
+
+    // Drop a replacement that has not taken over yet; true if there was one
+    async fn cancel_handover(&self) -> bool {
+        let Some(mut pending) = self.handover.lock().await.take() else {
+            return false;
+        };
+        pending.child.kill().ok();
+        pending.child.wait().ok();
+        true
+    }
+
+    // Called by the watchdog: swap in the replacement once its frames are on air (or it
+    // took too long), drop it if it exits first
+    async fn check_handover(&self) {
+        let (pending, on_air) = {
+            let mut handover = self.handover.lock().await;
+            let Some(h) = handover.as_mut() else {
+                drop(handover);
+                self.check_refused_start().await;
+                return;
+            };
+            let (on_air, refused) = self
+                .program_feed
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map_or((false, false), |f| {
+                    (f.is_live(h.source), f.take_refused() == Some(h.source))
+                });
+            if refused {
+                // Checked against the feed's format when it was attached, so the pinned
+                // output did not hold; the encoder on air stays and the next start reopens
+                // the feed for the new format
+                let mut rejected = handover.take().unwrap();
+                drop(handover);
+                rejected.child.kill().ok();
+                rejected.child.wait().ok();
+                if let Some(feed) = self.program_feed.lock().unwrap().as_mut() {
+                    feed.expire();
+                }
+                self.current_sequence.lock().await.clear();
+                if let Some(state) = self.override_state.lock().await.take() {
+                    log::error!(
+                        "🚨 Override encoder refused by the program feed: {}",
+                        state.info.input
+                    );
+                    self.close_override(state, Some(EndReason::Failed)).await;
+                } else {
+                    log::error!("Reconfigured encoder does not match the program feed's format, kept the one on air");
+                }
+                return;
+            }
+            let exited = !matches!(h.child.try_wait(), Ok(None));
+            if !on_air && !exited && Instant::now() < h.deadline {
+                return;
+            }
+            if exited && !on_air {
+                let mut failed = handover.take().unwrap();
+                drop(handover);
+                failed.child.wait().ok();
+                // The old encoder stays; the next tick builds the sequence again
+                self.current_sequence.lock().await.clear();
+                if let Some(state) = self.override_state.lock().await.take() {
+                    log::error!("🚨 Override encoder failed on {}", state.info.input);
+                    self.close_override(state, Some(EndReason::Failed)).await;
+                } else {
+                    log::warn!("Reconfigured encoder exited before going on air");
+                }
+                return;
+            }
+            (handover.take().unwrap(), on_air)
+        };
+
+        if on_air {
+            self.add_log("✓ Output reconfigured without interruption".to_string())
+                .await;
+        } else {
+            log::warn!("Reconfigured encoder not on air in time, cutting over anyway");
+        }
+        let old = self.current_process.lock().await.replace(pending.child);
+        if let Some(mut old) = old {
+            old.kill().ok();
+            old.wait().ok();
+        }
+    }
+
+    // An encoder put straight on air (nothing to hand over from) that the feed refused:
+    // none of it airs, so it starts over on a feed reopened for its format
+    async fn check_refused_start(&self) {
+        {
+            let mut feed = self.program_feed.lock().unwrap();
+            let Some(f) = feed.as_mut() else {
+                return;
+            };
+            if !f.take_refused().is_some_and(|source| f.is_latest(source)) {
+                return;
+            }
+            f.expire();
+        }
+        if let Some(mut child) = self.current_process.lock().await.take() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+        self.current_sequence.lock().await.clear();
+        if let Some(state) = self.override_state.lock().await.take() {
+            log::error!(
+                "🚨 Override encoder refused by the program feed: {}",
+                state.info.input
+            );
+            self.close_override(state, Some(EndReason::Failed)).await;
+        } else {
+            log::error!("Encoder refused by the program feed for its format, restarting it");
+        }
+    }
+
+    // Failover slate: default video looped through a concat list built once per source,
+    // default image when there is no video
+    async fn start_slate(&self, settings: &Settings) -> Result<Child, String> {
+        let fallback = self.filler_pool.lock().await.fallback.clone();
+        if let Some(video) = fallback {
+            let mut slate = self.slate_loop.lock().await;
+            let list = match slate.as_ref() {
+                Some((source, list)) if *source == video.path && list.exists() => list.clone(),
+                _ => {
+                    use std::io::Write;
+                    let list =
+                        std::env::temp_dir().join(format!("slate_loop_{}.txt", self.channel.slug));
+                    let mut file = std::fs::File::create(&list).map_err(|e| e.to_string())?;
+                    let repeats = ((SLATE_LOOP_SECS / video.duration.max(1.0)).ceil() as usize)
+                        .clamp(1, MAX_SLATE_REPEATS);
+                    for _ in 0..repeats {
+                        writeln!(file, "file '{}'", video.path).map_err(|e| e.to_string())?;
+                    }
+                    *slate = Some((video.path.clone(), list.clone()));
+                    list
+                }
+            };
+            return self.start_master_input(
+                list.to_str().unwrap_or_default(),
+                settings,
+                "[Failover Slate]",
+            );
+        }
+
+        let image = settings
+            .default_image_path
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(resolve_asset_path)
+            .filter(|p| std::path::Path::new(p).exists())
+            .ok_or("No default video or image configured for the failover slate")?;
+        self.start_master_input(&image, settings, "[Failover Slate]")
+    }
+
+    // Watchdog: notices a dead master encoder within WATCHDOG_INTERVAL_MS instead of the
+    // next tick, and bridges the gap with the slate so the master path never goes empty
+    async fn watchdog(self: Arc<Self>) {
+        while !*self.retired.lock().await {
+            sleep(Duration::from_millis(WATCHDOG_INTERVAL_MS)).await;
+            self.check_handover().await;
+            if let Err(e) = self.check_master().await {
+                log::error!("Watchdog: {}", e);
+            }
+        }
+    }
+
+    async fn check_master(&self) -> Result<(), String> {
+        if !*self.is_running.lock().await || self.is_paused().await {
+            return Ok(());
+        }
+        // The tick ends an override whose encoder exits
+        if self.override_state.lock().await.is_some() {
+            return Ok(());
+        }
+
+        // Intentional stops take the child out first, so an exited child here is unexpected
+        let exit = {
+            let mut proc_lock = self.current_process.lock().await;
+            match proc_lock.as_mut().map(|c| c.try_wait()) {
+                Some(Ok(Some(status))) if !status.success() => {
+                    proc_lock.take();
+                    status
+                }
+                _ => return Ok(()),
+            }
+        };
+
+        let settings = self.settings().await?;
+
+        let in_failover = self.failover.lock().await.is_some();
+        let clip_path = self.current_clip_path.lock().await.clone();
+        log::error!(
+            "🚨 Master encoder exited ({}) while playing {:?}",
+            exit,
+            clip_path
+        );
+
+        // A publisher that went away takes its encoder with it; that is not the clip's fault
+        let feed_down = !self.feed_alive();
+        if feed_down {
+            self.add_log("✗ Program feed publisher stopped, restarting".to_string())
+                .await;
+        }
+
+        let death = judge_encoder_death(
+            clip_path.as_deref(),
+            in_failover,
+            feed_down,
+            &mut *self.clip_failures.lock().await,
+            Instant::now(),
+        );
+        match (death, clip_path) {
+            (EncoderDeath::Failover(failures), Some(path)) => {
+                self.enter_failover(&path, failures).await;
+            }
+            (EncoderDeath::Retry(failures), Some(path)) => {
+                self.add_log(format!(
+                    "✗ Master encoder died on {} ({}/{}), restarting",
+                    file_name_of(&path),
+                    failures,
+                    MAX_CLIP_FAILURES
+                ))
+                .await;
+            }
+            _ => {}
+        }
+
+        // Bridge with the slate; the next tick replaces it with the programme (or keeps it
+        // while the failover holds the slot)
+        self.cancel_handover().await;
+        self.current_sequence.lock().await.clear();
+        *self.current_clip_id.lock().await = None;
+        *self.filler_plan.lock().await = None;
+        let child = self.start_slate(&settings).await?;
+        *self.current_process.lock().await = Some(child);
+        Ok(())
+    }
+
+    // Clip keeps failing: mark it suspect and hold the slate for the rest of its slot
+    async fn enter_failover(&self, path: &str, failures: u32) {
+        let reason = format!("Master encoder failed {} times", failures);
+        if let Err(e) = sqlx::query(
+            "UPDATE media SET is_suspect = TRUE, suspect_reason = $1, suspect_at = NOW()
+             WHERE path = $2",
+        )
+        .bind(&reason)
+        .bind(path)
+        .execute(&self.pool)
+        .await
+        {
+            log::error!("Failed to mark {} as suspect: {}", path, e);
+        }
+
+        let until = self
+            .as_run_open
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|o| o.planned_end)
+            .unwrap_or_else(Utc::now);
+        self.as_run_end(Some(EndReason::Failed)).await;
+        *self.failover.lock().await = Some(Failover {
+            clip_path: path.to_string(),
+            until,
+        });
+        self.clip_failures.lock().await.clear(path);
+
+        log::error!(
+            "⚠ {} marked suspect after {} failures, slate on air until {}",
+            path,
+            failures,
+            until
+        );
+        self.add_log(format!(
+            "⚠ {} marked suspect: failover slate until {}",
+            file_name_of(path),
+            self.wall_clock(until, "%H:%M:%S").await
+        ))
+        .await;
+        self.as_run_start(
+            format!("slate/{}", until),
+            NewAsRun {
+                source: "slate".to_string(),
+                clip_path: "failover-slate".to_string(),
+                filename: Some("Failover slate".to_string()),
+                planned_start: Some(Utc::now()),
+                planned_end: Some(until),
+                ..Default::default()
+            },
+        )
+        .await;
+    }
+
+    // True while the failover slate still owns the slot of the failing clip
+    async fn failover_holds(&self, on_air_path: Option<String>, now: DateTime<Utc>) -> bool {
+        let mut failover = self.failover.lock().await;
+        let holds = failover
+            .as_ref()
+            .is_some_and(|f| f.holds(on_air_path.as_deref(), now));
+        if !holds && failover.take().is_some() {
+            log::info!("Failover slot over, back to the schedule");
+        }
+        holds
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        log::info!("Playout Engine started for channel {}", self.channel.slug);
+
+        // Load initial state from DB
+        if let Ok(settings) = self.settings().await {
+            let mut r = self.is_running.lock().await;
+            *r = settings.is_running;
+            let mut err = self.last_error.lock().await;
+            *err = settings.last_error;
+
+            // If running, initialize start time
+            if settings.is_running {
+                let mut start_time = self.engine_start_time.lock().await;
+                *start_time = Some(Local::now());
+            }
+
+            // Sync clips counter from DB
+            let mut status = self.status.lock().await;
+            status.clips_played_today = settings.clips_played_today.unwrap_or(0);
+        }
+
+        tokio::spawn(self.clone().watchdog());
+        tokio::spawn(self.clone().preparer());
+
+        while !*self.retired.lock().await {
+            let is_running = *self.is_running.lock().await;
+            log::debug!("Engine tick loop active (is_running: {})", is_running);
+            if let Err(e) = self.tick().await {
+                log::error!("Engine tick error: {}", e);
+                let mut err = self.last_error.lock().await;
+                *err = Some(e.clone());
+
+                // Persist error to DB
+                let _ = sqlx::query("UPDATE settings SET last_error = $1 WHERE channel_id = $2")
+                    .bind(&e)
+                    .bind(self.channel.id)
+                    .execute(&self.pool)
+                    .await;
+            }
+
+            // --- Handle Skip Request ---
+            let mut skip = self.skip_requested.lock().await;
+            if *skip {
+                log::info!("Processing skip request...");
+                if let Err(e) = self.process_skip().await {
+                    log::error!("Failed to process skip: {}", e);
+                }
+                *skip = false;
+            }
+            // ---------------------------
+
+            self.publish_status().await;
+            sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn process_skip(&self) -> Result<(), String> {
+        if self.finish_override(Some(EndReason::Skipped)).await {
+            return Ok(());
+        }
+        if self.is_paused().await {
+            log::info!("Skip ignored while paused");
+            return Ok(());
+        }
+        let Some(key) = *self.active_schedule.lock().await else {
+            log::info!("Skip ignored: no scheduled playlist on air");
+            return Ok(());
+        };
+
+        let remaining = {
+            let status = self.status.lock().await;
+            match status.current_clip {
+                Some(ref current) => current.duration - current.position,
+                None => 0.0,
+            }
+        };
+
+        if remaining > 0.0 {
+            // Only today's occurrence moves; the schedule table stays untouched
+            let total = self.adjust_schedule_offset(key, remaining).await?;
+            log::info!(
+                "Skip: occurrence {} / {} advanced by {:.2}s (ledger offset {:.2}s). Stopping process for reload.",
+                key.0,
+                key.1,
+                remaining,
+                total
+            );
+
+            // Force immediate process stop to pick up new clip on next tick
+            self.as_run_end(Some(EndReason::Skipped)).await;
+            self.stop_process().await;
+        }
+        Ok(())
+    }
+
+    // Record a clip start in the as-run log (no-op while the same airing is on air);
+    // the previous clip is closed first
+    async fn as_run_start(&self, key: String, mut entry: NewAsRun) {
+        entry.channel_id = Some(self.channel.id);
+        let mut open = self.as_run_open.lock().await;
+        if open.as_ref().map(|o| o.key == key).unwrap_or(false) {
+            return;
+        }
+        let mut previous = None;
+        if let Some(prev) = open.take() {
+            let reason = as_run::reason_at(prev.planned_end, Utc::now());
+            if let Err(e) = as_run::close(&self.pool, prev.id, reason).await {
+                log::error!("As-run: failed to close entry {}: {}", prev.id, e);
+            }
+            previous = Some((prev.filename, reason));
+        }
+
+        let filename = entry
+            .filename
+            .clone()
+            .unwrap_or_else(|| file_name_of(&entry.clip_path));
+        self.emit(PlayoutEvent::ClipTransition {
+            previous: previous.as_ref().map(|(f, _)| f.clone()),
+            previous_reason: previous.map(|(_, r)| r),
+            current: filename.clone(),
+            title: entry.title.clone(),
+            source: entry.source.clone(),
+            playlist_name: entry.playlist_name.clone(),
+            planned_end: entry.planned_end,
+        });
+
+        match as_run::open(&self.pool, &entry).await {
+            Ok(id) => {
+                *open = Some(AsRunOpen {
+                    id,
+                    key,
+                    filename,
+                    planned_end: entry.planned_end,
+                })
+            }
+            Err(e) => log::error!("As-run: failed to record {}: {}", entry.clip_path, e),
+        }
+    }
+
+    // Close the clip on air in the as-run log; without a reason it is completed or
+    // interrupted depending on its planned end
+    async fn as_run_end(&self, reason: Option<EndReason>) {
+        let Some(prev) = self.as_run_open.lock().await.take() else {
+            return;
+        };
+        let reason = reason.unwrap_or_else(|| as_run::reason_at(prev.planned_end, Utc::now()));
+        if let Err(e) = as_run::close(&self.pool, prev.id, reason).await {
+            log::error!("As-run: failed to close entry {}: {}", prev.id, e);
+        }
+    }
+
+    // Record a shift of `delta` seconds for one schedule occurrence, applying from now: a
+    // skip moves it ahead, a resume holds it back by the time spent paused
+    async fn adjust_schedule_offset(
+        &self,
+        key: (Uuid, NaiveDate),
+        delta: f64,
+    ) -> Result<f64, String> {
+        sqlx::query(
+            "INSERT INTO schedule_offsets (schedule_id, occurrence_date, offset_seconds, applies_from)
+             VALUES ($1, $2, $3, NOW())",
+        )
+        .bind(key.0)
+        .bind(key.1)
+        .bind(delta)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(offset_seconds), 0) FROM schedule_offsets
+             WHERE schedule_id = $1 AND occurrence_date = $2",
+        )
+        .bind(key.0)
+        .bind(key.1)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    // Drop ledger entries once a new broadcast day starts (at day_start)
+    async fn roll_offset_ledger(&self, settings: &Settings) -> Result<(), String> {
+        let now = Utc::now();
+        let tz = clock::channel_tz(settings);
+        let broadcast_day =
+            broadcast_day::broadcast_date(now, broadcast_day::day_start(settings), &tz);
+
+        let mut ledger_day = self.ledger_day.lock().await;
+        if *ledger_day == Some(broadcast_day) {
+            return Ok(());
+        }
+
+        // Clear the entries of occurrences that are off the air by their resolved end, not
+        // by date: a block still running across day_start keeps its offsets until it ends
+        let window_start = now - chrono::Duration::days(1);
+        let input = broadcast_day::load_timeline_with(
+            &self.pool,
+            self.channel.id,
+            window_start,
+            now,
+            &tz,
+            Some(settings),
+        )
+        .await?;
+        let (schedule_ids, dates): (Vec<Uuid>, Vec<NaiveDate>) =
+            timeline::ended(&input, now).into_iter().unzip();
+
+        // Entries older than anything the engine resolves are stale whatever they held
+        let stale_before = broadcast_day - chrono::Duration::days(2);
+
+        sqlx::query(
+            "DELETE FROM schedule_offsets
+             WHERE schedule_id IN (SELECT id FROM schedule WHERE channel_id = $1)
+             AND (occurrence_date < $2
+                  OR (schedule_id, occurrence_date) IN
+                     (SELECT * FROM UNNEST($3::uuid[], $4::date[])))",
+        )
+        .bind(self.channel.id)
+        .bind(stale_before)
+        .bind(&schedule_ids)
+        .bind(&dates)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if ledger_day.is_some() {
+            log::info!(
+                "📅 New broadcast day {}: offset ledger cleared for {} ended occurrence(s)",
+                broadcast_day,
+                schedule_ids.len()
+            );
+        }
+        *ledger_day = Some(broadcast_day);
+        Ok(())
+    }
+
+    async fn tick(&self) -> Result<(), String> {
+        // Update Stream Stats and Distribution (even if engine stopped so protocols show)
+        let settings = self.settings().await?;
+
+        *self.channel_tz.lock().await = clock::channel_tz(&settings);
+        self.manage_distribution(&settings).await;
+        self.update_stream_stats(&settings).await;
+        self.roll_offset_ledger(&settings).await?;
+
+        // Check if engine is enabled
+        if !*self.is_running.lock().await {
+            return Ok(());
+        }
+
+        // Update uptime and sync logs
+        {
+            let mut status = self.status.lock().await;
+            if status.status == "playing" {
+                if let Some(start) = *self.engine_start_time.lock().await {
+                    status.uptime = (Local::now() - start).num_seconds();
+                }
+            }
+            let logs_buffer = self.logs.lock().await;
+            status.logs = logs_buffer.iter().cloned().collect();
+        }
+
+        // While paused, only keep the slate alive
+        if let Some(still_path) = self
+            .pause_state
+            .lock()
+            .await
+            .as_ref()
+            .map(|p| p.still_path.clone())
+        {
+            self.status.lock().await.status = "paused".to_string();
+            // Held content: the rundown (and its breaks) moves when we resume
+            self.cue_schedule.lock().unwrap().clear();
+            let mut proc_lock = self.current_process.lock().await;
+            let alive = proc_lock
+                .as_mut()
+                .map(|c| matches!(c.try_wait(), Ok(None)))
+                .unwrap_or(false);
+            if !alive {
+                log::warn!("Pause slate process exited. Restarting.");
+                *proc_lock = Some(self.start_still(&still_path, &settings)?);
+            }
+            return Ok(());
+        }
+
+        // An emergency override holds the air ahead of the schedule
+        if self.hold_override().await {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+
+        // Resolve the rundown up to the end of this broadcast day; start a day back,
+        // the block on air may have started before midnight or before day_start
+        let tz = clock::channel_tz(&settings);
+        let day_start = broadcast_day::day_start(&settings);
+        let bd = broadcast_day::broadcast_date(now, day_start, &tz);
+        let (_, day_end) = broadcast_day::day_bounds(bd, day_start, &tz);
+        let window_start = now - chrono::Duration::days(1);
+        let input = broadcast_day::load_timeline_with(
+            &self.pool,
+            self.channel.id,
+            window_start,
+            day_end,
+            &tz,
+            Some(&settings),
+        )
+        .await?;
+        let rundown = timeline::resolve(&input, window_start, day_end);
+        let on_air = timeline::at(&rundown, now);
+        self.update_cues(&rundown, &settings);
+        self.queue_preparation(&rundown, now, &settings).await;
+
+        log::debug!(
+            "Resolved {} timeline entries for broadcast day {}",
+            rundown.len(),
+            bd
+        );
+
+        // End of any gap we may have to fill
+        let gap_end = on_air.map(|e| e.end).unwrap_or(day_end);
+
+        let clip = on_air.filter(|e| e.kind == EntryKind::Clip);
+        let occurrence = clip.and_then(|e| {
+            input
+                .occurrences
+                .iter()
+                .find(|o| Some(o.key()) == e.schedule_id.zip(e.occurrence_date))
+        });
+
+        let clip_path = clip.and_then(|e| e.item.as_ref()).map(|i| i.get_path());
+        if self.failover_holds(clip_path, now).await {
+            let until = self.failover.lock().await.as_ref().map(|f| f.until);
+            let mut status = self.status.lock().await;
+            status.status = "playing".to_string();
+            status.schedule_source = until.map(|u| {
+                format!(
+                    "Failover slate (until {})",
+                    clock::local(&tz, u).format("%H:%M:%S")
+                )
+            });
+            status.current_clip = Some(ClipInfo {
+                filename: "Failover slate".to_string(),
+                title: None,
+                duration: until
+                    .map(|u| (u - now).num_milliseconds() as f64 / 1000.0)
+                    .unwrap_or(0.0),
+                position: 0.0,
+            });
+            return Ok(());
+        }
+
+        if let (Some(entry), Some(occ)) = (clip, occurrence) {
+            let s = &occ.schedule;
+            *self.active_schedule.lock().await = Some(occ.key());
+
+            log::debug!(
+                "📅 Match found! Schedule {} (Playlist ID: {}) started at {}",
+                s.id,
+                s.playlist_id,
+                occ.start
+            );
+
+            let source_desc = match (s.recurrence.as_deref(), s.repeat_pattern.as_deref()) {
+                (Some(rrule), _) if !rrule.is_empty() => {
+                    format!("Recurring {} (from {})", rrule, s.date.format("%Y-%m-%d"))
+                }
+                (_, Some("daily")) => format!("Daily (from {})", s.date.format("%Y-%m-%d")),
+                (_, Some("weekly")) => format!(
+                    "Weekly (DOW {}, from {})",
+                    s.date.weekday().num_days_from_monday(),
+                    s.date.format("%Y-%m-%d")
+                ),
+                (_, Some("monthly")) => format!("Monthly (from {})", s.date.format("%Y-%m-%d")),
+                _ => format!("Direct ({})", s.date.format("%Y-%m-%d")),
+            };
+
+            {
+                let mut status = self.status.lock().await;
+                status.schedule_source = Some(source_desc);
+                status.current_playlist_id = Some(s.playlist_id);
+                status.current_playlist_name = s.playlist_name.clone();
+            }
+
+            let items = input
+                .playlists
+                .get(&s.playlist_id)
+                .map(|v| v.as_slice())
+                .unwrap_or_default();
+            if self
+                .play_from_playlist(items, entry, now, &settings)
+                .await?
+            {
+                *self.filler_plan.lock().await = None;
+            } else {
+                // Nothing to show while the item is prepared: filler holds its slot
+                self.play_filler(entry.end, &settings).await?;
+            }
+        } else {
+            *self.active_schedule.lock().await = None;
+            // Clear schedule source in status
+            {
+                let mut status = self.status.lock().await;
+                status.schedule_source = None;
+                status.current_playlist_id = None;
+                status.current_playlist_name = None;
+            }
+
+            if self.play_filler(gap_end, &settings).await? {
+                return Ok(());
+            }
+
+            let mut status = self.status.lock().await;
+            if status.status != "stopped" && status.status != "idle" {
+                log::info!("No active schedule found. Stopping playout.");
+            }
+            status.status = "idle".to_string(); // Change to idle instead of stopped so we know engine is on
+            status.current_clip = None;
+            status.next_clips.clear();
+            drop(status);
+            self.as_run_end(None).await;
+            self.stop_process().await;
+        }
+
+        Ok(())
+    }
+
+    // Put the timeline entry on air at its wall-clock position, as a gapless sequence with
+    // the items that follow it. False while the entry is still being prepared and nothing
+    // but filler is on air.
+    async fn play_from_playlist(
+        &self,
+        items: &[PlaylistItem],
+        entry: &TimelineEntry,
+        now: DateTime<Utc>,
+        settings: &Settings,
+    ) -> Result<bool, String> {
+        let target_index = entry.item_index.unwrap_or(0);
+        let offset = entry.position_at(now);
+        let item = items.get(target_index).ok_or("Playlist item not found")?;
+        let clip_path = item.get_path();
+        if clip_path.is_empty() {
+            return Err("Missing clip path".to_string());
+        }
+        let clip_path = clip_path.as_str();
+
+        // Try to get the original filename (and library id) from the media library
+        let media_row =
+            sqlx::query("SELECT id, filename, metadata FROM media WHERE path = $1 LIMIT 1")
+                .bind(clip_path)
+                .fetch_one(&self.pool)
+                .await
+                .ok();
+        let media_id = item
+            .media_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .or_else(|| media_row.as_ref().and_then(|r| r.try_get("id").ok()));
+        let title = clip_title(item, media_row.as_ref());
+        let filename = media_row
+            .and_then(|r| r.try_get::<String, _>("filename").ok())
+            // Fallback to the item's own name, then the path (slideshows have no library row)
+            .or_else(|| item.slideshow.as_ref().and(item.filename.clone()))
+            .unwrap_or_else(|| file_name_of(clip_path));
+
+        let clip_id = item.id.as_deref().unwrap_or(clip_path);
+        let duration = item.effective_duration();
+        *self.current_clip_path.lock().await = Some(clip_path.to_string());
+
+        // Update Status
+        {
+            let mut status = self.status.lock().await;
+
+            // Only log if changing clip
+            if status
+                .current_clip
+                .as_ref()
+                .map(|c| c.filename != filename)
+                .unwrap_or(true)
+            {
+                log::info!("Now playing: {} (offset: {:.2}s)", filename, offset);
+            }
+
+            status.status = "playing".to_string();
+            status.current_clip = Some(ClipInfo {
+                filename: filename.clone(),
+                title,
+                duration,
+                position: offset,
+            });
+
+            // Set next clips
+            let mut next_clips_vec = Vec::new();
+            for it in items.iter().skip(target_index + 1).take(5) {
+                let it_path_buf = it.get_path();
+                let it_path = it_path_buf.as_str();
+
+                // Try to get the original filename (and title) from the media library
+                let it_row =
+                    sqlx::query("SELECT filename, metadata FROM media WHERE path = $1 LIMIT 1")
+                        .bind(it_path)
+                        .fetch_one(&self.pool)
+                        .await
+                        .ok();
+                let it_title = clip_title(it, it_row.as_ref());
+                let it_filename = if let Some(media_row) = it_row {
+                    media_row
+                        .try_get::<String, _>("filename")
+                        .unwrap_or_else(|_| {
+                            std::path::Path::new(it_path)
+                                .file_name()
+                                .and_then(|n| n.to_str())
+                                .unwrap_or(it_path)
+                                .to_string()
+                        })
+                } else if let Some(name) = it.slideshow.as_ref().and(it.filename.clone()) {
+                    name
+                } else {
+                    // Fallback to path-based filename if not found in media library
+                    std::path::Path::new(it_path)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(it_path)
+                        .to_string()
+                };
+
+                next_clips_vec.push(ClipInfo {
+                    filename: it_filename,
+                    title: it_title,
+                    duration: it.effective_duration(),
+                    position: 0.0,
+                });
+            }
+            status.next_clips = next_clips_vec;
+        }
+        if settings.upnext_enabled {
+            self.update_up_next(settings).await;
+        }
+
+        let filler_on_air = self.filler_plan.lock().await.is_some();
+        let mut current_id = self.current_clip_id.lock().await;
+        let mut proc_lock = self.current_process.lock().await;
+        let mut last_opacity = self.last_overlay_opacity.lock().await;
+        let mut last_scale = self.last_overlay_scale.lock().await;
+        let mut last_url = self.last_output_url.lock().await;
+        let mut last_res = self.last_resolution.lock().await;
+        let mut last_vb = self.last_video_bitrate.lock().await;
+        let mut last_ab = self.last_audio_bitrate.lock().await;
+        let mut last_text = self.last_text_layers.lock().await;
+        let mut last_transition = self.last_transition.lock().await;
+
+        let current_opacity = settings.overlay_opacity.unwrap_or(1.0);
+        let current_scale = settings.overlay_scale.unwrap_or(1.0);
+
+        // Detect overlay changes
+        let overlay_changed = settings.overlay_enabled
+            && ((current_opacity - *last_opacity).abs() > 0.01
+                || (current_scale - *last_scale).abs() > 0.01);
+
+        // Text layers toggled or restyled (changes to their text need no restart)
+        let current_text = graphics::text_settings_key(settings);
+        let text_changed = current_text != *last_text;
+
+        // The running sequence overlaps its items by the transitions it was built with
+        let current_transition = settings.default_transition();
+        let transition_changed = current_transition != *last_transition;
+
+        // Detect output settings changes (Restart required)
+        let settings_changed = settings.output_url != *last_url
+            || settings.resolution != *last_res
+            || settings.video_bitrate != *last_vb
+            || settings.audio_bitrate != *last_ab;
+
+        let alive = if let Some(ref mut child) = *proc_lock {
+            match child.try_wait() {
+                Ok(None) => true,
+                // Crashed encoder: the watchdog counts the failure and restarts it
+                Ok(Some(exit)) if !exit.success() => return Ok(true),
+                _ => false,
+            }
+        } else {
+            false
+        };
+        // Force restart if settings changed
+        let reconfigure = overlay_changed || settings_changed || text_changed || transition_changed;
+        let is_running = alive && !reconfigure;
+
+        // ---------------------------------------------------------
+        // GAPLESS PLAYOUT LOGIC (Replace single-clip with sequence)
+        // ---------------------------------------------------------
+
+        // 1. Determine if we are "covered" by the current sequence
+        let mut seq = self.current_sequence.lock().await;
+        let current_clip_id_str = clip_id.to_string();
+
+        // If the process is healthy AND the target clip is inside the currently running sequence...
+        // We assume FFmpeg is handling the transition internally.
+        let in_sequence = is_running && seq.contains(&current_clip_id_str);
+
+        if !in_sequence {
+            // 2. Build the concat playlist: the current item and the ones after it, as far
+            // as the preparer has got; bad items are swapped for filler
+            let playlist_filename = format!(
+                "playlist_{}_{}.txt",
+                self.channel.slug,
+                Local::now().timestamp_millis()
+            );
+            let playlist_path = std::env::temp_dir().join(&playlist_filename);
+            let mut playlist_file =
+                std::fs::File::create(&playlist_path).map_err(|e| e.to_string())?;
+
+            let mut sequence_ids = Vec::new();
+            let mut sequence_items = Vec::new();
+            let mut substitutes = std::collections::HashSet::new();
+            let mut filler_pool = None;
+            let following = items.iter().skip(target_index + 1).take(SEQUENCE_AHEAD);
+            for next_item in std::iter::once(item).chain(following) {
+                let next_path = next_item.get_path();
+                if next_path.is_empty() {
+                    continue;
+                }
+                let next_id = next_item.id.clone().unwrap_or(next_path);
+                match self
+                    .write_checked_entry(&mut playlist_file, next_item, &mut filler_pool, settings)
+                    .await?
+                {
+                    Some(substituted) => {
+                        if substituted {
+                            substitutes.insert(next_id.clone());
+                        }
+                    }
+                    None => break,
+                }
+                sequence_ids.push(next_id);
+                sequence_items.push(next_item);
+            }
+            drop(playlist_file);
+
+            if sequence_items.is_empty() {
+                // Not prepared yet: whatever is on air stays until it is
+                std::fs::remove_file(&playlist_path).ok();
+                log::debug!("Waiting for {} to be prepared", filename);
+                return Ok(alive && !filler_on_air);
+            }
+            *self.substituted_clips.lock().await = substitutes;
+
+            log::info!(
+                "Starting NEW sequence starting with: {} (offset {:.2}s)",
+                clip_id,
+                offset
+            );
+            log::info!(
+                "Generated gapless sequence with {} items at {:?}",
+                sequence_ids.len(),
+                playlist_path
+            );
+
+            if text_changed {
+                log::info!("Text layers changed. Restarting stream.");
+            }
+            if transition_changed {
+                log::info!("Default transition changed. Restarting stream.");
+            }
+            if overlay_changed || settings_changed {
+                log::info!(
+                    "Stream settings changed (URL: {}->{}, Res: {}->{}, Bitrate: {}/{}->{}/{}). Restarting stream.",
+                    *last_url, settings.output_url,
+                    *last_res, settings.resolution,
+                    *last_vb, *last_ab, settings.video_bitrate, settings.audio_bitrate
+                );
+            }
+            // Whatever was waiting to take over is superseded by this sequence
+            self.cancel_handover().await;
+            // Reconfiguring a healthy encoder: keep it on air until its replacement is
+            if !(alive && reconfigure) {
+                if let Some(mut child) = proc_lock.take() {
+                    child.kill().ok();
+                }
+            }
+
+            let ffmpeg = FFmpegService::new();
+
+            // Position as of now, not of the start of the tick
+            let offset = entry.position_at(Utc::now());
+            let logo_path = resolve_logo_path(settings);
+            let layers = self
+                .sequence_layers(&sequence_items, offset, settings)
+                .await;
+
+            // Main engine always pushes to the channel's internal master feed
+            let mut child = ffmpeg.start_stream(
+                playlist_path.to_str().unwrap(),
+                MASTER_PIPE,
+                offset,
+                settings,
+                None,
+                logo_path.as_deref(),
+                &layers,
+                settings.scte35_enabled.then_some(&self.cue_schedule),
+            )?;
+            let source = self.feed_master(&mut child, settings)?;
+
+            // Capture stderr to system logs for debugging Master Feed issues
+            if let Some(stderr) = child.stderr.take() {
+                std::thread::spawn(move || {
+                    let reader = std::io::BufReader::new(stderr);
+                    use std::io::BufRead;
+                    for line in reader.lines().map_while(Result::ok) {
+                        // Log and also filter for errors
+                        if line.contains("Error")
+                            || line.contains("failed")
+                            || line.contains("panic")
+                        {
+                            log::error!("[Master Feed] {}", line);
+                        } else if line.contains("Opening") || line.contains("Output") {
+                            log::info!("[Master Feed] {}", line);
+                        } else {
+                            log::debug!("[Master Feed] {}", line);
+                        }
+                    }
+                });
+            }
+
+            // The encoder is up: the sequence and the settings it was built with are current
+            *seq = sequence_ids;
+            *last_text = current_text;
+            *last_transition = current_transition;
+            *last_opacity = current_opacity;
+            *last_scale = current_scale;
+            *last_url = settings.output_url.clone();
+            *last_res = settings.resolution.clone();
+            *last_vb = settings.video_bitrate.clone();
+            *last_ab = settings.audio_bitrate.clone();
+
+            if proc_lock.is_some() {
+                self.begin_handover(child, source).await;
+            } else {
+                *proc_lock = Some(child);
+            }
+            *current_id = Some(clip_id.to_string());
+            log::info!("FFmpeg GAPLESS process started for sequence.");
+            self.add_log("✓ Playout engine started successfully".to_string())
+                .await;
+
+            // Clips Played Today Counter
+            // logic here is tricky in gapless. We'll count "starts" for now.
+            if current_id.as_ref() != Some(&clip_id.to_string()) {
+                let mut status = self.status.lock().await;
+                status.clips_played_today += 1;
+                let new_count = status.clips_played_today;
+                let pool = self.pool.clone();
+                let channel_id = self.channel.id;
+                tokio::spawn(async move {
+                    let _ = sqlx::query(
+                        "UPDATE settings SET clips_played_today = $1 WHERE channel_id = $2",
+                    )
+                    .bind(new_count)
+                    .bind(channel_id)
+                    .execute(&pool)
+                    .await;
+                });
+            }
+            *current_id = Some(clip_id.to_string());
+        } else {
+            // We are IN SEQUENCE. Just update metadata (clips played) if ID changed
+            if current_id.as_ref() != Some(&clip_id.to_string()) {
+                log::info!(
+                    "Gapless Transition: Detected crossing into next clip: {}",
+                    filename
+                );
+                let mut status = self.status.lock().await;
+                status.clips_played_today += 1;
+                let new_count = status.clips_played_today;
+                let pool = self.pool.clone();
+                let channel_id = self.channel.id;
+                tokio::spawn(async move {
+                    let _ = sqlx::query(
+                        "UPDATE settings SET clips_played_today = $1 WHERE channel_id = $2",
+                    )
+                    .bind(new_count)
+                    .bind(channel_id)
+                    .execute(&pool)
+                    .await;
+                });
+                *current_id = Some(clip_id.to_string());
+            }
+        }
+
+        // Substituted items air filler in their slot
+        let source = if self.substituted_clips.lock().await.contains(clip_id) {
+            "substitute"
+        } else {
+            "schedule"
+        };
+        self.as_run_start(
+            format!(
+                "{}/{}/{}",
+                target_index,
+                entry.start.timestamp_millis(),
+                clip_id
+            ),
+            NewAsRun {
+                source: source.to_string(),
+                media_id,
+                clip_path: clip_path.to_string(),
+                filename: Some(filename),
+                title: entry.title.clone(),
+                playlist_id: entry.playlist_id,
+                playlist_name: entry.playlist_name.clone(),
+                schedule_id: entry.schedule_id,
+                occurrence_date: entry.occurrence_date,
+                planned_start: Some(entry.start),
+                planned_end: Some(entry.end),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        Ok(true)
+    }
+
+    // Splice points of the rundown for the SCTE-35 injectors and the CUE tags of the HLS
+    // playlist; only replaced when the rundown moves them
+    fn update_cues(&self, rundown: &[TimelineEntry], settings: &Settings) {
+        if !settings.scte35_enabled {
+            self.cue_schedule.lock().unwrap().clear();
+            return;
+        }
+        let now = Utc::now();
+        let cues: Vec<scte35::SpliceCue> = scte35::schedule_from(rundown)
+            .into_iter()
+            .filter(|c| c.at > now - chrono::Duration::hours(1))
+            .collect();
+
+        let mut schedule = self.cue_schedule.lock().unwrap();
+        if *schedule != cues {
+            log::debug!("SCTE-35: {} splice points scheduled", cues.len());
+            *schedule = cues;
+        }
+    }
+
+    /// HLS playlist with `#EXT-X-CUE-*` tags for the splice points around its segments
+    pub fn decorate_hls(&self, m3u8: &str) -> String {
+        let cues = self.cue_schedule.lock().unwrap();
+        if cues.is_empty() {
+            m3u8.to_string()
+        } else {
+            scte35::decorate_playlist(m3u8, &cues)
+        }
+    }
+
+    // Pre-flight: the file exists, is not flagged suspect and ffprobe can read a stream
+    // from it. Probe results are cached per path until the file changes.
+    async fn preflight(&self, path: &str) -> Result<MediaInfo, String> {
+        if let Some(result) = self.checked(path).await {
+            return result;
+        }
+        let modified = std::fs::metadata(path).ok().and_then(|m| m.modified().ok());
+        let result = probe(path)
+            .await
+            .and_then(|info| {
+                if info.has_video || info.has_audio {
+                    Ok(info)
+                } else {
+                    Err("no audio or video stream".to_string())
+                }
+            })
+            .map_err(|e| format!("unreadable: {}", e));
+        self.preflight_cache
+            .lock()
+            .await
+            .insert(path.to_string(), (modified, result.clone()));
+        result
+    }
+
+    // Pre-flight outcome for `path` without probing: None until the file has been probed
+    // (as it is now)
+    async fn checked(&self, path: &str) -> Option<Result<MediaInfo, String>> {
+        let modified = match std::fs::metadata(path) {
+            Ok(meta) => meta.modified().ok(),
+            Err(_) => return Some(Err("file not found".to_string())),
+        };
+
+        let suspect =
+            sqlx::query("SELECT suspect_reason FROM media WHERE path = $1 AND is_suspect = TRUE")
+                .bind(path)
+                .fetch_optional(&self.pool)
+                .await
+                .ok()
+                .flatten();
+        if let Some(row) = suspect {
+            let reason: Option<String> = row.try_get("suspect_reason").ok().flatten();
+            return Some(Err(format!(
+                "marked suspect ({})",
+                reason.unwrap_or_default()
+            )));
+        }
+
+        self.preflight_cache
+            .lock()
+            .await
+            .get(path)
+            .filter(|(checked, _)| *checked == modified)
+            .map(|(_, result)| result.clone())
+    }
+
+    // Hand the items coming up in the rundown (nearest first) to the preparer
+    async fn queue_preparation(
+        &self,
+        rundown: &[TimelineEntry],
+        now: DateTime<Utc>,
+        settings: &Settings,
+    ) {
+        let mut items: Vec<PlaylistItem> = Vec::new();
+        for item in rundown
+            .iter()
+            .filter(|e| e.end > now)
+            .filter_map(|e| e.item.as_ref())
+        {
+            if !items.contains(item) {
+                items.push(item.clone());
+                if items.len() > SEQUENCE_AHEAD {
+                    break;
+                }
+            }
+        }
+
+        let params = render_params(settings);
+        let mut queue = self.prepare_queue.lock().await;
+        if queue.items != items || queue.params != params {
+            *queue = PrepareQueue {
+                items,
+                settings: Some(settings.clone()),
+                params,
+            };
+            self.prepare_wake.notify_one();
+        }
+    }
+
+    // Background preparation: works through the queue nearest first and starts over when
+    // it changes, so building a sequence only picks up finished work
+    async fn preparer(self: Arc<Self>) {
+        while !*self.retired.lock().await {
+            let (items, settings, params) = {
+                let queue = self.prepare_queue.lock().await;
+                (queue.items.clone(), queue.settings.clone(), queue.params)
+            };
+            self.prepared
+                .lock()
+                .await
+                .retain(|_, p| p.at.elapsed() < Duration::from_secs(PREPARED_TTL_SECS * 6));
+
+            let mut interrupted = false;
+            if let Some(settings) = settings {
+                for item in &items {
+                    self.prepare(item, &settings).await;
+                    if self.queue_changed(&items, params).await {
+                        interrupted = true;
+                        break;
+                    }
+                }
+                if !interrupted {
+                    interrupted = !self.prepare_filler(&items, &settings, params).await;
+                }
+            }
+            if !interrupted {
+                tokio::time::timeout(
+                    Duration::from_secs(PREPARE_RECHECK_SECS),
+                    self.prepare_wake.notified(),
+                )
+                .await
+                .ok();
+            }
+        }
+    }
+
+    async fn queue_changed(&self, items: &[PlaylistItem], params: u64) -> bool {
+        let queue = self.prepare_queue.lock().await;
+        queue.items != items || queue.params != params
+    }
+
+    // Check the filler library and the default video, conforming clips missing a stream;
+    // the pool is replaced once all of it went through. False when the queue changed first.
+    async fn prepare_filler(
+        &self,
+        items: &[PlaylistItem],
+        settings: &Settings,
+        params: u64,
+    ) -> bool {
+        let candidates = self.load_filler_candidates(settings).await;
+        let fallback = self.default_video_candidate(settings).await;
+        let mut sources = HashMap::new();
+        for candidate in candidates.iter().chain(fallback.as_ref()) {
+            if sources.contains_key(&candidate.path) {
+                continue;
+            }
+            let source = match self.preflight(&candidate.path).await {
+                Ok(info) => {
+                    self.conformed_path(&candidate.path, &info, None, settings)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match source {
+                Ok(source) => {
+                    sources.insert(candidate.path.clone(), source);
+                }
+                Err(e) => log::warn!("Filler {} left out: {}", candidate.path, e),
+            }
+            if self.queue_changed(items, params).await {
+                return false;
+            }
+        }
+
+        *self.filler_pool.lock().await = FillerPool {
+            candidates: candidates
+                .into_iter()
+                .filter(|c| sources.contains_key(&c.path))
+                .collect(),
+            fallback: fallback.filter(|c| sources.contains_key(&c.path)),
+            sources,
+            params,
+        };
+        true
+    }
+
+    // Filler ready for these settings. Renders made for other settings are left out until
+    // the preparer catches up; clips that needed none still air.
+    async fn ready_filler(&self, settings: &Settings) -> FillerPool {
+        let mut pool = self.filler_pool.lock().await.clone();
+        if pool.params != render_params(settings) {
+            pool.sources.retain(|path, source| path == source);
+            let sources = &pool.sources;
+            pool.candidates.retain(|c| sources.contains_key(&c.path));
+            pool.fallback = pool.fallback.filter(|c| sources.contains_key(&c.path));
+        }
+        pool
+    }
+
+    // Pre-flight an upcoming item and render what it needs to air: stills and slideshows
+    // to the item's airtime, a conformed copy for clips missing a stream
+    async fn prepare(&self, item: &PlaylistItem, settings: &Settings) {
+        let path = item.get_path();
+        if path.is_empty() {
+            return;
+        }
+        let key = prepare_key(item, settings);
+        if self
+            .prepared
+            .lock()
+            .await
+            .get(&key)
+            .is_some_and(Prepared::is_fresh)
+        {
+            return;
+        }
+
+        let result = if let Some(show) = &item.slideshow {
+            self.slideshow_path(item, show, settings).await
+        } else {
+            match self.preflight(&path).await {
+                Ok(_) if ffmpeg::is_still_image(&path) => self.still_path(item, settings).await,
+                Ok(info) => self
+                    .conformed_path(&path, &info, Some(item), settings)
+                    .await
+                    .map(|source| (source, true)),
+                Err(e) => Err(e),
+            }
+        };
+        self.prepared.lock().await.insert(
+            key,
+            Prepared {
+                at: Instant::now(),
+                result,
+            },
+        );
+    }
+
+    // What the preparer made of `item` under these settings, None if it has not got to it.
+    // Files are checked again (gone, changed or flagged suspect since).
+    async fn prepared(
+        &self,
+        item: &PlaylistItem,
+        settings: &Settings,
+    ) -> Option<Result<Source, String>> {
+        if item.slideshow.is_none() {
+            if let Err(e) = self.checked(&item.get_path()).await? {
+                return Some(Err(e));
+            }
+        }
+        self.prepared
+            .lock()
+            .await
+            .get(&prepare_key(item, settings))
+            .filter(|p| p.is_fresh())
+            .map(|p| p.result.clone())
+    }
+
+    // Lower third text for the next clip, rewritten when the next clip changes
+    async fn update_up_next(&self, settings: &Settings) {
+        let next = self.status.lock().await.next_clips.first().map(|c| {
+            c.title
+                .clone()
+                .unwrap_or_else(|| strip_extension(&c.filename))
+        });
+        let label = settings
+            .upnext_label
+            .as_deref()
+            .filter(|l| !l.is_empty())
+            .unwrap_or("Up next");
+        let text = next
+            .map(|title| format!("{}: {}", label, title))
+            .unwrap_or_default();
+
+        let mut last = self.last_up_next.lock().await;
+        if *last != text {
+            let dir = self.channel.text_dir();
+            match graphics::write_text_file(&dir, graphics::UPNEXT_FILE, &text) {
+                Ok(()) => *last = text,
+                Err(e) => log::warn!("Could not write up next text: {}", e),
+            }
+        }
+    }
+
+    // Layers on air whatever is playing: the ticker, static text and clock
+    fn base_layers(&self, settings: &Settings) -> EventLayers {
+        EventLayers {
+            text: self.text_layers(settings, &[]),
+            ..Default::default()
+        }
+    }
+
+    // Text layer filters; their files are created empty on first use since drawtext
+    // fails on a missing file
+    fn text_layers(&self, settings: &Settings, items: &[(f64, f64)]) -> Vec<String> {
+        let dir = self.channel.text_dir();
+        for file in [
+            graphics::TICKER_FILE,
+            graphics::TEXT_FILE,
+            graphics::UPNEXT_FILE,
+        ] {
+            let path = dir.join(file);
+            if !path.exists() {
+                if let Err(e) = graphics::write_text_file(&dir, file, "") {
+                    log::warn!("Could not create {:?}: {}", path, e);
+                }
+            }
+        }
+        graphics::text_filters(settings, &dir, items)
+    }
+
+    // Secondary events of a new sequence; graphics whose file is missing are left out
+    // rather than failing the encoder
+    async fn sequence_layers(
+        &self,
+        items: &[&PlaylistItem],
+        offset: f64,
+        settings: &Settings,
+    ) -> EventLayers {
+        let mut layers =
+            EventLayers::for_sequence(items, offset, settings.default_transition().as_ref());
+        layers.text = self.text_layers(settings, &layers.items);
+        layers.gains = self.loudness_gains(items, &layers.items, settings).await;
+        let mut missing = Vec::new();
+        layers.graphics.retain_mut(|g| {
+            g.path = resolve_asset_path(&g.path);
+            let exists = std::path::Path::new(&g.path).exists();
+            if !exists {
+                missing.push(g.path.clone());
+            }
+            exists
+        });
+        for path in missing {
+            log::warn!("Secondary event graphic not found: {}", path);
+            self.add_log(format!("⚠ Graphic not found: {}", file_name_of(&path)))
+                .await;
+        }
+        layers
+    }
+
+    // Gain bringing each item to the channel's loudness target, from the measurement stored
+    // at ingest; items not measured, or swapped for filler, air at unity gain
+    async fn loudness_gains(
+        &self,
+        items: &[&PlaylistItem],
+        windows: &[(f64, f64)],
+        settings: &Settings,
+    ) -> Vec<(f64, f64, f64)> {
+        let target = settings
+            .loudness_target
+            .unwrap_or(loudness::DEFAULT_TARGET_LUFS);
+        let paths: Vec<String> = items.iter().map(|i| i.get_path()).collect();
+        let rows = sqlx::query(
+            "SELECT path, metadata->'loudness' AS loudness FROM media WHERE path = ANY($1)",
+        )
+        .bind(&paths)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+        let measured: HashMap<String, Loudness> = rows
+            .iter()
+            .filter_map(|r| {
+                let path: String = r.try_get("path").ok()?;
+                let value: serde_json::Value = r.try_get("loudness").ok()?;
+                Some((path, serde_json::from_value(value).ok()?))
+            })
+            .collect();
+
+        let substitutes = self.substituted_clips.lock().await;
+        items
+            .iter()
+            .zip(windows)
+            .filter_map(|(item, &(start, end))| {
+                let path = item.get_path();
+                let id = item.id.clone().unwrap_or_else(|| path.clone());
+                if end <= start || substitutes.contains(&id) {
+                    return None;
+                }
+                let gain = loudness::gain_db(measured.get(&path)?, target);
+                Some((start, end, loudness::linear(gain)))
+            })
+            .collect()
+    }
+
+    // Concat entry for `item` after pre-flight. A bad item is replaced by filler of the same
+    // airtime so the rest of the sequence keeps its timing; returns true when substituted,
+    // None (and writes nothing) while the item has not been checked yet.
+    async fn write_checked_entry(
+        &self,
+        file: &mut std::fs::File,
+        item: &PlaylistItem,
+        filler_pool: &mut Option<FillerPool>,
+        settings: &Settings,
+    ) -> Result<Option<bool>, String> {
+        let path = item.get_path();
+        // Stills and slideshows are rendered to the item's airtime; clips keep their in/out
+        let Some(checked) = self.prepared(item, settings).await else {
+            return Ok(None);
+        };
+        let reason = match checked {
+            Ok(source) => {
+                let (source, inpoint, outpoint) = source_entry(item, source);
+                write_concat_entry(file, &source, inpoint, outpoint)?;
+                return Ok(Some(false));
+            }
+            Err(reason) => reason,
+        };
+
+        if filler_pool.is_none() {
+            *filler_pool = Some(self.ready_filler(settings).await);
+        }
+        let pool = filler_pool.as_ref().unwrap();
+        let duration = item.effective_duration();
+        let segments = pool.substitute(item);
+        pool.write_entries(file, &segments)?;
+
+        let name = item.filename.clone().unwrap_or_else(|| file_name_of(&path));
+        log::warn!(
+            "Pre-flight: {} {} - replaced with {} filler segment(s) ({:.1}s)",
+            path,
+            reason,
+            segments.len(),
+            filler::plan_duration(&segments)
+        );
+        if segments.is_empty() {
+            self.add_log(format!(
+                "✗ Pre-flight: {} {}, no filler available, dropped",
+                name, reason
+            ))
+            .await;
+        } else {
+            self.add_log(format!(
+                "⚠ Pre-flight: {} {}, replaced with {:.0}s of filler",
+                name, reason, duration
+            ))
+            .await;
+        }
+        Ok(Some(true))
+    }
 
-                    active_playlist_id = Some(s.playlist_id);
-                    playlist_start_time = Some(st);
+    // Path to put in a concat list for a clip: itself when it has audio and video, otherwise
+    // a cached copy with silence or a picture added (see `audio_picture`). Every entry of a
+    // concat sequence must carry both streams or the encoder loses one mid-air.
+    async fn conformed_path(
+        &self,
+        path: &str,
+        info: &MediaInfo,
+        item: Option<&PlaylistItem>,
+        settings: &Settings,
+    ) -> Result<String, String> {
+        if info.has_audio && info.has_video {
+            return Ok(path.to_string());
+        }
+        let picture = if info.has_video {
+            AudioPicture::default()
+        } else {
+            self.audio_picture(path, info, item, settings).await
+        };
 
-                    log::info!(
-                        "📅 Match found! Schedule {} (Playlist ID: {}) starting at {}",
-                        s.id,
-                        s.playlist_id,
-                        st
-                    );
+        let mut files = vec![path];
+        files.extend(picture.background.as_deref());
+        let key = render_key(&files, (&picture, &settings.resolution, &settings.fps));
+        let missing = if info.has_video { "silence" } else { "picture" };
+        let (input, info) = (path.to_string(), info.clone());
+        let (resolution, fps) = (settings.resolution.clone(), settings.fps.clone());
+        self.cached_render(
+            key,
+            format!("Conforming {}: adding {}", file_name_of(path), missing),
+            move |output| {
+                FFmpegService::new().conform_streams(
+                    &input,
+                    output,
+                    &info,
+                    &picture,
+                    &resolution,
+                    &fps,
+                )
+            },
+        )
+        .await
+        .map_err(|e| format!("cannot add missing stream: {}", e))
+    }
 
-                    let source_desc = match s.repeat_pattern.as_deref() {
-                        Some("daily") => format!("Daily (from {})", s.date.format("%Y-%m-%d")),
-                        Some("weekly") => format!(
-                            "Weekly (DOW {}, from {})",
-                            day_of_week,
-                            s.date.format("%Y-%m-%d")
-                        ),
-                        _ => format!("Direct ({})", s.date.format("%Y-%m-%d")),
-                    };
-
-                    {
-                        let mut status = self.status.lock().await;
-                        status.schedule_source = Some(source_desc);
-                        status.current_playlist_id = Some(s.playlist_id);
-                        status.current_playlist_name = s.playlist_name.clone();
-                    }
-                    break;
-                } else {
-                    log::debug!(
-                        "⌛ Schedule {} starts at {} (too early, now: {})",
-                        s.id,
-                        st,
-                        current_time
-                    );
-                }
+    // Picture under an audio clip: the item's visual settings, else the channel's. The
+    // now-playing line takes title and artist from the item's metadata, the library's, then
+    // the file's tags.
+    async fn audio_picture(
+        &self,
+        path: &str,
+        info: &MediaInfo,
+        item: Option<&PlaylistItem>,
+        settings: &Settings,
+    ) -> AudioPicture {
+        let visual = settings.audio_visual(item.and_then(|i| i.visual.as_ref()));
+        let background = match visual.background.as_deref().map(resolve_asset_path) {
+            Some(bg) if !std::path::Path::new(&bg).exists() => {
+                log::warn!("Audio background not found: {}, using black", bg);
+                None
             }
-        }
+            bg => bg,
+        };
 
-        if let Some(pid) = active_playlist_id {
-            log::debug!("Loading playlist {}", pid);
-            let playlist = sqlx::query_as::<_, Playlist>("SELECT * FROM playlists WHERE id = $1")
-                .bind(pid)
+        let text = if visual.now_playing {
+            let library = sqlx::query("SELECT metadata FROM media WHERE path = $1 LIMIT 1")
+                .bind(path)
                 .fetch_one(&self.pool)
                 .await
-                .map_err(|e| e.to_string())?;
-
-            self.play_from_playlist(playlist, playlist_start_time.unwrap(), &settings)
-                .await?;
+                .ok()
+                .and_then(|r| r.try_get::<serde_json::Value, _>("metadata").ok());
+            let field = |key: &str| {
+                [item.and_then(|i| i.metadata.as_ref()), library.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|m| m.get(key).and_then(|v| v.as_str()))
+                    .map(str::trim)
+                    .find(|v| !v.is_empty())
+                    .map(str::to_string)
+            };
+            let title = field("title")
+                .or_else(|| info.title.clone())
+                .unwrap_or_else(|| strip_extension(&file_name_of(path)));
+            Some(match field("artist").or_else(|| info.artist.clone()) {
+                Some(artist) => format!("{} - {}", artist, title),
+                None => title,
+            })
         } else {
-            // Clear schedule source in status
-            {
-                let mut status = self.status.lock().await;
-                status.schedule_source = None;
-                status.current_playlist_id = None;
-                status.current_playlist_name = None;
-                if status.status != "stopped" && status.status != "idle" {
-                    log::info!("No active schedule found. Stopping playout.");
-                }
-            }
+            None
+        };
 
-            let mut status = self.status.lock().await;
-            status.status = "idle".to_string(); // Change to idle instead of stopped so we know engine is on
-            status.current_clip = None;
-            status.next_clips.clear();
-            self.stop_process().await;
+        AudioPicture {
+            background,
+            visualizer: visual.visualizer,
+            text,
+            font: settings
+                .text_font_path
+                .clone()
+                .filter(|p| !p.trim().is_empty()),
         }
-
-        Ok(())
     }
 
-    async fn play_from_playlist(
+    // An image item as a clip of its duration
+    async fn still_path(
         &self,
-        playlist: Playlist,
-        start_time: NaiveTime,
+        item: &PlaylistItem,
         settings: &Settings,
-    ) -> Result<(), String> {
-        let now = Local::now().time();
-        let duration_since_start = now - start_time;
-        let seconds_since_start = duration_since_start.num_milliseconds() as f64 / 1000.0;
+    ) -> Result<(String, bool), String> {
+        let duration = item.effective_duration();
+        if duration <= 0.0 {
+            return Err("image item has no duration".to_string());
+        }
+        let images = vec![item.get_path()];
+        self.render_slides(images, None, duration, duration, item.ken_burns, settings)
+            .await
+            .map(|source| (source, false))
+    }
 
-        if seconds_since_start < 0.0 {
-            return Ok(());
+    // A slideshow item as a clip of its duration: the folder's images in filename order
+    async fn slideshow_path(
+        &self,
+        item: &PlaylistItem,
+        show: &Slideshow,
+        settings: &Settings,
+    ) -> Result<(String, bool), String> {
+        let duration = item.effective_duration();
+        if duration <= 0.0 {
+            return Err("slideshow has no duration".to_string());
+        }
+        let images: Vec<String> =
+            sqlx::query("SELECT path FROM media WHERE folder_id = $1 ORDER BY filename")
+                .bind(show.folder_id)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| e.to_string())?
+                .iter()
+                .map(|row| row.get::<String, _>("path"))
+                .filter(|p| ffmpeg::is_still_image(p) && std::path::Path::new(p).exists())
+                .collect();
+        if images.is_empty() {
+            return Err("no images in the slideshow folder".to_string());
         }
 
-        let items = if playlist.content.is_array() {
-            playlist.content.as_array().unwrap()
-        } else if let Some(program) = playlist.content.get("program").and_then(|p| p.as_array()) {
-            program
-        } else {
-            return Err("Invalid playlist content".to_string());
+        let audio = show
+            .audio
+            .as_deref()
+            .filter(|a| !a.is_empty())
+            .map(resolve_asset_path);
+        let audio = match audio {
+            Some(bed) if !std::path::Path::new(&bed).exists() => {
+                log::warn!("Slideshow audio bed not found: {}", bed);
+                self.add_log(format!(
+                    "⚠ Slideshow audio {} not found, playing silence",
+                    file_name_of(&bed)
+                ))
+                .await;
+                None
+            }
+            audio => audio,
         };
+        self.render_slides(
+            images,
+            audio,
+            show.image_duration.max(1.0),
+            duration,
+            item.ken_burns,
+            settings,
+        )
+        .await
+        .map(|source| (source, false))
+    }
 
-        let mut accumulated_time = 0.0;
-        let mut target_item = None;
-        let mut offset = 0.0;
-        let mut target_index = 0;
-
-        for (i, item) in items.iter().enumerate() {
-            let duration = item["duration"].as_f64().unwrap_or(0.0);
-            if (seconds_since_start as f64) < (accumulated_time + duration) {
-                target_item = Some(item);
-                offset = (seconds_since_start as f64) - accumulated_time;
-                target_index = i;
-                break;
-            }
-            accumulated_time += duration;
-        }
-
-        if let Some(item) = target_item {
-            let clip_path = item["source"]
-                .as_str()
-                .or_else(|| item["path"].as_str())
-                .ok_or("Missing clip path")?;
-
-            // Try to get the original filename from the media library
-            let filename = if let Ok(media_row) =
-                sqlx::query("SELECT filename FROM media WHERE path = $1 LIMIT 1")
-                    .bind(clip_path)
-                    .fetch_one(&self.pool)
-                    .await
-            {
-                media_row
-                    .try_get::<String, _>("filename")
-                    .unwrap_or_else(|_| {
-                        std::path::Path::new(clip_path)
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or(clip_path)
-                            .to_string()
-                    })
-            } else {
-                // Fallback to path-based filename if not found in media library
-                std::path::Path::new(clip_path)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or(clip_path)
-                    .to_string()
-            };
-
-            let clip_id = item["id"].as_str().unwrap_or(clip_path);
-            let duration = item["duration"].as_f64().unwrap_or(0.0);
-
-            // Update Status
-            {
-                let mut status = self.status.lock().await;
-
-                // Only log if changing clip
-                if status
-                    .current_clip
-                    .as_ref()
-                    .map(|c| c.filename != filename)
-                    .unwrap_or(true)
-                {
-                    log::info!("Now playing: {} (offset: {:.2}s)", filename, offset);
-                }
+    async fn render_slides(
+        &self,
+        images: Vec<String>,
+        audio: Option<String>,
+        per_image: f64,
+        total: f64,
+        ken_burns: bool,
+        settings: &Settings,
+    ) -> Result<String, String> {
+        let mut files: Vec<&str> = images.iter().map(String::as_str).collect();
+        files.extend(audio.as_deref());
+        let key = render_key(
+            &files,
+            (
+                per_image.to_bits(),
+                total.to_bits(),
+                ken_burns,
+                audio.is_some(),
+                &settings.resolution,
+                &settings.fps,
+            ),
+        );
+        let what = match images.as_slice() {
+            [image] => format!("Rendering still {} ({:.0}s)", file_name_of(image), total),
+            _ => format!(
+                "Rendering slideshow of {} images ({:.0}s)",
+                images.len(),
+                total
+            ),
+        };
+        let (resolution, fps) = (settings.resolution.clone(), settings.fps.clone());
+        self.cached_render(key, what, move |output| {
+            FFmpegService::new().render_slides(
+                &images,
+                output,
+                audio.as_deref(),
+                per_image,
+                total,
+                ken_burns,
+                &resolution,
+                &fps,
+            )
+        })
+        .await
+    }
 
-                status.status = "playing".to_string();
-                status.current_clip = Some(ClipInfo {
-                    filename: filename.clone(),
-                    duration,
-                    position: offset,
-                });
+    // Clip made by `render` (given the path to write), cached under `key` in the render
+    // directory; it is written under a temporary name so a failed run never leaves half a file
+    async fn cached_render<F>(&self, key: u64, what: String, render: F) -> Result<String, String>
+    where
+        F: FnOnce(&str) -> Result<(), String> + Send + 'static,
+    {
+        let dir = std::env::temp_dir().join(RENDER_DIR);
+        let target = dir.join(format!("{:016x}.mkv", key));
+        if target.exists() {
+            return Ok(target.to_string_lossy().to_string());
+        }
 
-                // Set next clips
-                let mut next_clips_vec = Vec::new();
-                for it in items.iter().skip(target_index + 1).take(5) {
-                    let it_path = it["source"]
-                        .as_str()
-                        .or_else(|| it["path"].as_str())
-                        .unwrap_or("");
-
-                    // Try to get the original filename from the media library
-                    let it_filename = if let Ok(media_row) =
-                        sqlx::query("SELECT filename FROM media WHERE path = $1 LIMIT 1")
-                            .bind(it_path)
-                            .fetch_one(&self.pool)
-                            .await
-                    {
-                        media_row
-                            .try_get::<String, _>("filename")
-                            .unwrap_or_else(|_| {
-                                std::path::Path::new(it_path)
-                                    .file_name()
-                                    .and_then(|n| n.to_str())
-                                    .unwrap_or(it_path)
-                                    .to_string()
-                            })
-                    } else {
-                        // Fallback to path-based filename if not found in media library
-                        std::path::Path::new(it_path)
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or(it_path)
-                            .to_string()
-                    };
-
-                    next_clips_vec.push(ClipInfo {
-                        filename: it_filename,
-                        duration: it["duration"].as_f64().unwrap_or(0.0),
-                        position: 0.0,
-                    });
-                }
-                status.next_clips = next_clips_vec;
-            }
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        prune_renders(&dir);
+        log::info!("{} to {:?}", what, target);
+        self.add_log(format!("⚙ {}", what)).await;
 
-            let mut current_id = self.current_clip_id.lock().await;
-            let mut proc_lock = self.current_process.lock().await;
-            let mut last_opacity = self.last_overlay_opacity.lock().await;
-            let mut last_scale = self.last_overlay_scale.lock().await;
-            let mut last_url = self.last_output_url.lock().await;
-            let mut last_res = self.last_resolution.lock().await;
-            let mut last_vb = self.last_video_bitrate.lock().await;
-            let mut last_ab = self.last_audio_bitrate.lock().await;
-
-            let current_opacity = settings.overlay_opacity.unwrap_or(1.0);
-            let current_scale = settings.overlay_scale.unwrap_or(1.0);
-
-            // Detect overlay changes
-            let overlay_changed = settings.overlay_enabled
-                && ((current_opacity - *last_opacity).abs() > 0.01
-                    || (current_scale - *last_scale).abs() > 0.01);
-
-            // Detect output settings changes (Restart required)
-            let settings_changed = settings.output_url != *last_url
-                || settings.resolution != *last_res
-                || settings.video_bitrate != *last_vb
-                || settings.audio_bitrate != *last_ab;
+        let partial = dir.join(format!("{:016x}.partial.mkv", key));
+        let output = partial.to_string_lossy().to_string();
+        let result = tokio::task::spawn_blocking(move || render(&output))
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = result {
+            std::fs::remove_file(&partial).ok();
+            return Err(e);
+        }
+        std::fs::rename(&partial, &target).map_err(|e| e.to_string())?;
+        Ok(target.to_string_lossy().to_string())
+    }
 
-            if overlay_changed || settings_changed {
-                log::info!(
-                    "Stream settings changed (URL: {}->{}, Res: {}->{}, Bitrate: {}/{}->{}/{}). Restarting stream.",
-                    *last_url, settings.output_url,
-                    *last_res, settings.resolution,
-                    *last_vb, *last_ab, settings.video_bitrate, settings.audio_bitrate
-                );
-                *last_opacity = current_opacity;
-                *last_scale = current_scale;
-                *last_url = settings.output_url.clone();
-                *last_res = settings.resolution.clone();
-                *last_vb = settings.video_bitrate.clone();
-                *last_ab = settings.audio_bitrate.clone();
+    // Fill airtime until `gap_end` with filler clips (falls back to default video/image).
+    // Returns false when there is nothing to put on air.
+    async fn play_filler(
+        &self,
+        gap_end: DateTime<Utc>,
+        settings: &Settings,
+    ) -> Result<bool, String> {
+        let now = Utc::now();
+        let until = clock::local(&clock::channel_tz(settings), gap_end).format("%H:%M:%S");
+        let alive = self
+            .current_process
+            .lock()
+            .await
+            .as_mut()
+            .map(|c| matches!(c.try_wait(), Ok(None)))
+            .unwrap_or(false);
+
+        // Keep the plan on air, extend it when its window runs out, or plan the gap anew
+        let (needs_plan, carried) = match self.filler_plan.lock().await.as_ref() {
+            Some(plan) if plan.gap_end == gap_end && alive => {
+                let window_end = plan.window_end();
+                let extend = window_end < gap_end
+                    && window_end - now < chrono::Duration::seconds(FILLER_EXTEND_SECS);
+                (extend, extend.then(|| plan.remainder(now)))
             }
+            _ => (true, None),
+        };
 
-            let is_running = if let Some(ref mut child) = *proc_lock {
-                match child.try_wait() {
-                    Ok(None) => !overlay_changed && !settings_changed, // Force restart if settings changed
-                    _ => false,
-                }
+        if needs_plan {
+            let extending = carried.is_some();
+            let (mut segments, offset, started_at) = carried.unwrap_or((Vec::new(), 0.0, now));
+            let planned_until = started_at + seconds(filler::plan_duration(&segments));
+            let gap = (gap_end - planned_until).num_milliseconds() as f64 / 1000.0;
+
+            let pool = self.ready_filler(settings).await;
+            let next = filler::plan_fill(
+                gap.min(FILLER_WINDOW_SECS),
+                &pool.candidates,
+                pool.fallback.as_ref(),
+            );
+            if extending && next.is_empty() {
+                // Nothing ready to add: the running plan plays out
+                log::debug!("No filler ready to extend the plan until {}", until);
+            } else if !extending && gap < 1.0 {
+                return Ok(false);
             } else {
-                false
-            };
+                segments.extend(next);
+                if !extending {
+                    // Filler is not tracked per clip by the watchdog
+                    *self.current_clip_path.lock().await = None;
+                }
+                if !self
+                    .start_filler(&mut segments, offset, gap, extending, settings)
+                    .await?
+                {
+                    *self.filler_plan.lock().await = None;
+                    return Ok(false);
+                }
 
-            // ---------------------------------------------------------
-            // GAPLESS PLAYOUT LOGIC (Replace single-clip with sequence)
-            // ---------------------------------------------------------
+                if extending {
+                    log::info!(
+                        "🧩 Filler extended to {:.0}s planned, gap ends {}",
+                        filler::plan_duration(&segments),
+                        until
+                    );
+                } else {
+                    self.current_sequence.lock().await.clear();
+                    *self.current_clip_id.lock().await = None;
+                    log::info!(
+                        "🧩 Filling {:.0}s gap until {} with {} segment(s) ({:.0}s planned)",
+                        gap,
+                        until,
+                        segments.len(),
+                        filler::plan_duration(&segments)
+                    );
+                    self.add_log(format!(
+                        "🧩 Filler on air until {} ({} clip(s))",
+                        until,
+                        segments.len()
+                    ))
+                    .await;
+                }
 
-            // 1. Determine if we are "covered" by the current sequence
-            let mut seq = self.current_sequence.lock().await;
-            let current_clip_id_str = clip_id.to_string();
+                *self.filler_plan.lock().await = Some(FillerPlan {
+                    gap_end,
+                    started_at,
+                    segments,
+                });
+            }
+        }
 
-            // If the process is healthy AND the target clip is inside the currently running sequence...
-            // We assume FFmpeg is handling the transition internally.
-            let in_sequence = is_running && seq.contains(&current_clip_id_str);
+        // Update status from the plan position
+        if let Some(plan) = self.filler_plan.lock().await.as_ref() {
+            let (index, acc) = plan.position(now);
+            let elapsed = (now - plan.started_at).num_milliseconds() as f64 / 1000.0;
 
-            if !in_sequence {
-                log::info!(
-                    "Starting NEW sequence starting with: {} (offset {:.2}s)",
-                    clip_id,
-                    offset
-                );
+            let mut status = self.status.lock().await;
+            status.status = "playing".to_string();
+            status.schedule_source = Some(format!("Filler (until {})", until));
+            status.current_playlist_id = None;
+            status.current_playlist_name = None;
+            status.current_clip = plan.segments.get(index).map(|seg| ClipInfo {
+                filename: seg.filename.clone(),
+                title: None,
+                duration: seg.duration,
+                position: (elapsed - acc).max(0.0),
+            });
+            status.next_clips = plan
+                .segments
+                .iter()
+                .skip(index + 1)
+                .take(5)
+                .map(|seg| ClipInfo {
+                    filename: seg.filename.clone(),
+                    title: None,
+                    duration: seg.duration,
+                    position: 0.0,
+                })
+                .collect();
+            drop(status);
+
+            if let Some(seg) = plan.segments.get(index) {
+                // Keyed by airtime, which an extended plan keeps for the segment on air
+                let planned_start = plan.started_at + seconds(acc);
+                self.as_run_start(
+                    format!("filler/{}", planned_start.timestamp_millis()),
+                    NewAsRun {
+                        source: "filler".to_string(),
+                        clip_path: seg.path.clone(),
+                        filename: Some(seg.filename.clone()),
+                        planned_start: Some(planned_start),
+                        planned_end: Some(planned_start + seconds(seg.duration)),
+                        ..Default::default()
+                    },
+                )
+                .await;
+            }
+        }
 
-                if let Some(mut child) = proc_lock.take() {
-                    child.kill().ok();
-                }
+        Ok(true)
+    }
 
-                // 2. Build the Concat Playlist (Current + Next 5 items)
-                let mut sequence_ids = Vec::new();
-                use std::io::Write;
-
-                // Temp file for playlist
-                let playlist_filename = format!("playlist_{}.txt", Local::now().timestamp_millis());
-                let playlist_path = std::env::temp_dir().join(&playlist_filename);
-                let mut playlist_file =
-                    std::fs::File::create(&playlist_path).map_err(|e| e.to_string())?;
-
-                // Add Current Item
-                writeln!(playlist_file, "file '{}'", clip_path).map_err(|e| e.to_string())?;
-                sequence_ids.push(current_clip_id_str.clone());
-
-                // Add Next Items (Limit to 50 to avoid frequent restarts)
-                for next_item in items.iter().skip(target_index + 1).take(50) {
-                    let next_path = next_item["source"]
-                        .as_str()
-                        .or_else(|| next_item["path"].as_str())
-                        .unwrap_or("");
-
-                    if !next_path.is_empty() {
-                        writeln!(playlist_file, "file '{}'", next_path)
-                            .map_err(|e| e.to_string())?;
-                        let next_id = next_item["id"].as_str().unwrap_or(next_path).to_string();
-                        sequence_ids.push(next_id);
+    // Put a filler plan on air from `offset` into its first segment, or the default image for
+    // the whole gap when there are no segments. An extension takes over make-before-break;
+    // false when there is nothing to air.
+    async fn start_filler(
+        &self,
+        segments: &mut Vec<FillerSegment>,
+        offset: f64,
+        gap: f64,
+        extending: bool,
+        settings: &Settings,
+    ) -> Result<bool, String> {
+        if !extending {
+            self.cancel_handover().await;
+            if let Some(mut child) = self.current_process.lock().await.take() {
+                child.kill().ok();
+                child.wait().ok();
+            }
+        }
+        let (child, source) = if !segments.is_empty() {
+            let playlist_path = std::env::temp_dir().join(format!(
+                "filler_{}_{}.txt",
+                self.channel.slug,
+                Utc::now().timestamp_millis()
+            ));
+            let mut playlist_file =
+                std::fs::File::create(&playlist_path).map_err(|e| e.to_string())?;
+            self.ready_filler(settings)
+                .await
+                .write_entries(&mut playlist_file, segments)?;
+            drop(playlist_file);
+
+            let logo_path = resolve_logo_path(settings);
+
+            let mut child = FFmpegService::new().start_stream(
+                playlist_path.to_str().unwrap(),
+                MASTER_PIPE,
+                offset,
+                settings,
+                None,
+                logo_path.as_deref(),
+                &self.base_layers(settings),
+                None,
+            )?;
+            let source = self.feed_master(&mut child, settings)?;
+            if let Some(stderr) = child.stderr.take() {
+                std::thread::spawn(move || {
+                    let reader = std::io::BufReader::new(stderr);
+                    use std::io::BufRead;
+                    for line in reader.lines().map_while(Result::ok) {
+                        if line.contains("Error") || line.contains("failed") {
+                            log::error!("[Filler] {}", line);
+                        } else {
+                            log::debug!("[Filler] {}", line);
+                        }
                     }
-                }
+                });
+            }
+            (child, source)
+        } else if let Some(image) = settings
+            .default_image_path
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(resolve_asset_path)
+            .filter(|p| std::path::Path::new(p).exists())
+        {
+            // Last resort: hold the default image for the whole gap
+            let child = self.start_still(&image, settings)?;
+            segments.push(FillerSegment {
+                filename: file_name_of(&image),
+                path: image,
+                duration: gap,
+                outpoint: None,
+            });
+            (child, 0)
+        } else {
+            return Ok(false);
+        };
 
-                // Update Sequence State
-                *seq = sequence_ids;
-                log::info!(
-                    "Generated gapless sequence with {} items at {:?}",
-                    seq.len(),
-                    playlist_path
-                );
+        if extending {
+            self.begin_handover(child, source).await;
+        } else {
+            *self.current_process.lock().await = Some(child);
+        }
+        Ok(true)
+    }
 
-                let ffmpeg = FFmpegService::new();
-                let hls_preview_path_str = std::env::var("HLS_PATH")
-                    .unwrap_or_else(|_| "/var/lib/onepa-playout/hls".to_string());
-                let hls_preview_path = hls_preview_path_str.as_str();
-                std::fs::create_dir_all(hls_preview_path).ok();
-
-                // Main engine always pushes to an internal master feed
-                let output_url = "rtmp://mediamtx:1935/live/master".to_string();
-
-                let logo_path = if settings.overlay_enabled {
-                    settings
-                        .logo_path
-                        .as_ref()
-                        .map(|p| {
-                            if p.starts_with("/assets/") {
-                                let assets_path =
-                                    std::env::var("ASSETS_PATH").unwrap_or_else(|_| {
-                                        "/var/lib/onepa-playout/assets".to_string()
-                                    });
-                                p.replace("/assets/", &format!("{}/", assets_path))
-                            } else {
-                                p.clone()
-                            }
-                        })
-                        .as_deref()
-                        .filter(|s| !s.is_empty())
-                        .map(|s| s.to_string())
-                } else {
-                    None
-                };
+    // Media flagged as filler or stored under fillers_path
+    async fn load_filler_candidates(&self, settings: &Settings) -> Vec<FillerCandidate> {
+        let fillers_prefix = format!("{}/%", settings.fillers_path.trim_end_matches('/'));
+        let rows = sqlx::query(
+            "SELECT path, filename, duration FROM media
+             WHERE (is_filler = TRUE OR path LIKE $1)
+             AND media_type = 'video' AND duration > 0",
+        )
+        .bind(&fillers_prefix)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
 
-                let mut child = ffmpeg.start_stream(
-                    playlist_path.to_str().unwrap(),
-                    &output_url,
-                    offset,
-                    &settings,
-                    Some(hls_preview_path),
-                    logo_path.as_deref(),
-                )?;
-
-                // Capture stderr to system logs for debugging Master Feed issues
-                if let Some(stderr) = child.stderr.take() {
-                    std::thread::spawn(move || {
-                        let reader = std::io::BufReader::new(stderr);
-                        use std::io::BufRead;
-                        for line in reader.lines() {
-                            if let Ok(line) = line {
-                                // Log and also filter for errors
-                                if line.contains("Error")
-                                    || line.contains("failed")
-                                    || line.contains("panic")
-                                {
-                                    log::error!("[Master Feed] {}", line);
-                                } else if line.contains("Opening") || line.contains("Output") {
-                                    log::info!("[Master Feed] {}", line);
-                                } else {
-                                    log::debug!("[Master Feed] {}", line);
-                                }
-                            }
-                        }
-                    });
+        rows.iter()
+            .filter_map(|row| {
+                let path: String = row.try_get("path").ok()?;
+                if !std::path::Path::new(&path).exists() {
+                    return None;
                 }
+                Some(FillerCandidate {
+                    filename: row
+                        .try_get("filename")
+                        .unwrap_or_else(|_| file_name_of(&path)),
+                    duration: row.try_get::<Option<f64>, _>("duration").ok()??,
+                    path,
+                })
+            })
+            .collect()
+    }
 
-                *proc_lock = Some(child);
-                *current_id = Some(clip_id.to_string());
-                log::info!("FFmpeg GAPLESS process started for sequence.");
-                self.add_log("✓ Playout engine started successfully".to_string())
-                    .await;
-
-                // Clips Played Today Counter
-                // logic here is tricky in gapless. We'll count "starts" for now.
-                if current_id.as_ref() != Some(&clip_id.to_string()) {
-                    let mut status = self.status.lock().await;
-                    status.clips_played_today += 1;
-                    let new_count = status.clips_played_today;
-                    let pool = self.pool.clone();
-                    tokio::spawn(async move {
-                        let _ = sqlx::query(
-                            "UPDATE settings SET clips_played_today = $1 WHERE id = TRUE",
-                        )
-                        .bind(new_count)
-                        .execute(&pool)
-                        .await;
-                    });
-                }
-                *current_id = Some(clip_id.to_string());
-            } else {
-                // We are IN SEQUENCE. Just update metadata (clips played) if ID changed
-                if current_id.as_ref() != Some(&clip_id.to_string()) {
-                    log::info!(
-                        "Gapless Transition: Detected crossing into next clip: {}",
-                        filename
-                    );
-                    let mut status = self.status.lock().await;
-                    status.clips_played_today += 1;
-                    let new_count = status.clips_played_today;
-                    let pool = self.pool.clone();
-                    tokio::spawn(async move {
-                        let _ = sqlx::query(
-                            "UPDATE settings SET clips_played_today = $1 WHERE id = TRUE",
-                        )
-                        .bind(new_count)
-                        .execute(&pool)
-                        .await;
-                    });
-                    *current_id = Some(clip_id.to_string());
-                }
-            }
-        } else {
-            // Playlist finished or gap
-            log::info!("Playlist finished or gap detected. Entering idle state.");
-            let mut status = self.status.lock().await;
-            status.status = "idle".to_string();
-            status.current_clip = None;
-            status.next_clips.clear();
-            self.stop_process().await;
-        }
+    async fn default_video_candidate(&self, settings: &Settings) -> Option<FillerCandidate> {
+        let path = settings
+            .default_video_path
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(resolve_asset_path)
+            .filter(|p| std::path::Path::new(p).exists())?;
+
+        let known = sqlx::query("SELECT duration FROM media WHERE path = $1 LIMIT 1")
+            .bind(&path)
+            .fetch_optional(&self.pool)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|row| row.try_get::<Option<f64>, _>("duration").ok().flatten());
+        let duration = match known {
+            Some(d) => d,
+            None => self.preflight(&path).await.ok()?.duration?,
+        };
 
-        Ok(())
+        Some(FillerCandidate {
+            filename: file_name_of(&path),
+            path,
+            duration,
+        })
     }
 
     async fn stop_process(&self) {
+        *self.filler_plan.lock().await = None;
+        self.cancel_handover().await;
         let mut proc_lock = self.current_process.lock().await;
         if let Some(mut child) = proc_lock.take() {
             log::info!("Stopping playout process");
             child.kill().ok();
         }
+        self.program_feed.lock().unwrap().take();
         let mut current_id = self.current_clip_id.lock().await;
         *current_id = None;
 
@@ -888,9 +3292,10 @@ impl PlayoutEngine {
                 if let Ok(json) = resp.json::<serde_json::Value>().await {
                     if let Some(items) = json.get("items") {
                         let process_item = |data: &serde_json::Value| -> PathInfo {
-                            let mut info = PathInfo::default();
-                            info.ready =
-                                data.get("ready").and_then(|v| v.as_bool()).unwrap_or(false);
+                            let mut info = PathInfo {
+                                ready: data.get("ready").and_then(|v| v.as_bool()).unwrap_or(false),
+                                ..Default::default()
+                            };
 
                             if let Some(readers) = data.get("readers").and_then(|v| v.as_array()) {
                                 for r in readers {
@@ -948,7 +3353,7 @@ impl PlayoutEngine {
         }
 
         // 0. Master Feed Status
-        let master_info = mediamtx_paths.get("live/master");
+        let master_info = mediamtx_paths.get(&self.channel.master_path());
         streams.push(ActiveStream {
             protocol: "MASTER".to_string(),
             status: if master_info.map(|i| i.ready).unwrap_or(false) {
@@ -1167,9 +3572,8 @@ impl PlayoutEngine {
     }
 
     async fn cleanup_cache(&self) {
-        let hls_path =
-            std::env::var("HLS_PATH").unwrap_or_else(|_| "/var/lib/onepa-playout/hls".to_string());
-        log::info!("Cleaning up HLS cache in {}", hls_path);
+        let hls_path = self.channel.hls_dir();
+        log::info!("Cleaning up HLS cache in {}", hls_path.display());
 
         if let Ok(entries) = std::fs::read_dir(&hls_path) {
             for entry in entries.flatten() {
@@ -1186,15 +3590,16 @@ impl PlayoutEngine {
 
         // Update database
         let query = match protocol {
-            "rtmp" => "UPDATE settings SET rtmp_enabled = $1 WHERE id = TRUE",
-            "srt" => "UPDATE settings SET srt_enabled = $1 WHERE id = TRUE",
-            "udp" => "UPDATE settings SET udp_enabled = $1 WHERE id = TRUE",
-            "hls" => "UPDATE settings SET hls_enabled = $1 WHERE id = TRUE",
+            "rtmp" => "UPDATE settings SET rtmp_enabled = $1 WHERE channel_id = $2",
+            "srt" => "UPDATE settings SET srt_enabled = $1 WHERE channel_id = $2",
+            "udp" => "UPDATE settings SET udp_enabled = $1 WHERE channel_id = $2",
+            "hls" => "UPDATE settings SET hls_enabled = $1 WHERE channel_id = $2",
             _ => return Err(format!("Unknown protocol: {}", protocol)),
         };
 
         sqlx::query(query)
             .bind(enabled)
+            .bind(self.channel.id)
             .execute(&self.pool)
             .await
             .map_err(|e| e.to_string())?;
@@ -1220,12 +3625,14 @@ impl PlayoutEngine {
             log::debug!("Engine not running, but will manage distribution based on DB flags");
         }
 
-        static INACTIVE_COUNT: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
         let master_feed_active = self.check_master_feed_active().await;
         if !master_feed_active {
             tokio::time::sleep(Duration::from_millis(500)).await;
             if !self.check_master_feed_active().await {
-                let count = INACTIVE_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                let count = self
+                    .master_inactive_count
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                    + 1;
                 if count < 10 {
                     log::debug!("[DEBUG-RELAY] Master feed is inactive (count={}/10). Waiting for stabilization.", count);
                     return;
@@ -1243,10 +3650,12 @@ impl PlayoutEngine {
                 }
                 return;
             } else {
-                INACTIVE_COUNT.store(0, std::sync::atomic::Ordering::Relaxed);
+                self.master_inactive_count
+                    .store(0, std::sync::atomic::Ordering::Relaxed);
             }
         } else {
-            INACTIVE_COUNT.store(0, std::sync::atomic::Ordering::Relaxed);
+            self.master_inactive_count
+                .store(0, std::sync::atomic::Ordering::Relaxed);
         }
 
         // REMOVED 2S SLEEP - it slows down the tick loop and causes sync issues
@@ -1255,7 +3664,7 @@ impl PlayoutEngine {
         let ffmpeg = FFmpegService::new();
         let mediamtx_host =
             std::env::var("MEDIAMTX_HOST").unwrap_or_else(|_| "localhost".to_string());
-        let master_url = format!("rtmp://{}:1935/live/master", mediamtx_host);
+        let master_url = self.channel.master_url(&mediamtx_host);
 
         // 1. RTMP
         let rtmp_enabled = settings.rtmp_enabled
@@ -1282,12 +3691,16 @@ impl PlayoutEngine {
                 &master_url,
                 &mut procs,
                 &ffmpeg,
+                None,
             )
             .await;
         } else {
             procs.remove("rtmp");
         }
 
+        // SRT and UDP carry the ad-break cues as SCTE-35
+        let cues = settings.scte35_enabled.then_some(&self.cue_schedule);
+
         // 2. SRT
         let srt_enabled = settings.srt_enabled
             || (settings.output_type == "srt" && settings.auto_start_protocols);
@@ -1313,6 +3726,7 @@ impl PlayoutEngine {
                 &master_url,
                 &mut procs,
                 &ffmpeg,
+                cues,
             )
             .await;
         } else {
@@ -1345,6 +3759,7 @@ impl PlayoutEngine {
                 &master_url,
                 &mut procs,
                 &ffmpeg,
+                cues,
             )
             .await;
         } else {
@@ -1352,6 +3767,7 @@ impl PlayoutEngine {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn handle_relay(
         &self,
         key: &str,
@@ -1360,6 +3776,7 @@ impl PlayoutEngine {
         master_url: &str,
         procs: &mut HashMap<String, Child>,
         ffmpeg: &FFmpegService,
+        cues: Option<&CueSchedule>,
     ) {
         const COOLDOWN_SECS: u64 = 5;
 
@@ -1404,7 +3821,13 @@ impl PlayoutEngine {
 
         let mut last_urls = self.last_relay_urls.lock().await;
         let last_url_opt = last_urls.get(key).map(|s| s.as_str());
-        let current_url = url.trim();
+        // Toggling SCTE-35 restarts the relay like a URL change
+        let current_url = if cues.is_some() {
+            format!("{} (scte35)", url.trim())
+        } else {
+            url.trim().to_string()
+        };
+        let current_url = current_url.as_str();
         let url_changed = if let Some(last_url) = last_url_opt {
             let changed = last_url != current_url;
             if changed {
@@ -1435,7 +3858,7 @@ impl PlayoutEngine {
             // Fall through to !is_running block below
         }
 
-        let is_running_after_check = if procs.contains_key(key) { true } else { false };
+        let is_running_after_check = procs.contains_key(key);
 
         if enabled && !is_running_after_check {
             // Check cooldown
@@ -1463,7 +3886,7 @@ impl PlayoutEngine {
                 url,
                 start_reason
             );
-            match ffmpeg.start_relay(master_url, url) {
+            match ffmpeg.start_relay(master_url, url, cues) {
                 Ok(mut child) => {
                     log::info!(
                         "✓ Phase 1: FFmpeg spawned for {} (PID: {:?})",
@@ -1476,14 +3899,12 @@ impl PlayoutEngine {
                         std::thread::spawn(move || {
                             let reader = std::io::BufReader::new(stderr);
                             use std::io::BufRead;
-                            for line in reader.lines() {
-                                if let Ok(line) = line {
-                                    // Log and also filter for errors
-                                    if line.contains("Error") || line.contains("failed") {
-                                        log::error!("[Relay {}] {}", key_clone, line);
-                                    } else {
-                                        log::debug!("[Relay {}] {}", key_clone, line);
-                                    }
+                            for line in reader.lines().map_while(Result::ok) {
+                                // Log and also filter for errors
+                                if line.contains("Error") || line.contains("failed") {
+                                    log::error!("[Relay {}] {}", key_clone, line);
+                                } else {
+                                    log::debug!("[Relay {}] {}", key_clone, line);
                                 }
                             }
                         });
@@ -1555,15 +3976,14 @@ impl PlayoutEngine {
                     if let Ok(json) = resp.json::<serde_json::Value>().await {
                         if let Some(items) = json.get("items") {
                             // MediaMTX path items can be a list or a map
+                            let name = self.channel.master_path();
                             let master_path = if items.is_object() {
-                                items.get("live/master")
+                                items.get(&name)
                             } else if items.is_array() {
                                 items.as_array().and_then(|arr| {
                                     arr.iter().find(|item| {
-                                        item.get("name")
-                                            == Some(&serde_json::Value::String(
-                                                "live/master".to_string(),
-                                            ))
+                                        item.get("name").and_then(|n| n.as_str())
+                                            == Some(name.as_str())
                                     })
                                 })
                             } else {
@@ -1576,7 +3996,10 @@ impl PlayoutEngine {
                                 {
                                     let is_ready = ready.as_bool().unwrap_or(false);
                                     if !is_ready {
-                                        log::debug!("[DEBUG-RELAY] Master feed live/master found but NOT READY");
+                                        log::debug!(
+                                            "[DEBUG-RELAY] Master feed {} found but NOT READY",
+                                            name
+                                        );
                                     }
                                     return is_ready;
                                 }
@@ -1596,7 +4019,10 @@ impl PlayoutEngine {
         }
 
         // Extract port
-        let port = url.split(':').last().and_then(|p| p.parse::<u16>().ok());
+        let port = url
+            .split(':')
+            .next_back()
+            .and_then(|p| p.parse::<u16>().ok());
         if let Some(port) = port {
             // Check if we are in listener mode (url contains @ or is empty host or has listen=1)
             let is_listener = url.contains("@")
@@ -1650,3 +4076,327 @@ impl PlayoutEngine {
         0
     }
 }
+
+// Map public /assets/ URLs to the assets directory on disk
+fn resolve_asset_path(p: &str) -> String {
+    if p.starts_with("/assets/") {
+        let assets_path = std::env::var("ASSETS_PATH")
+            .unwrap_or_else(|_| "/var/lib/onepa-playout/assets".to_string());
+        p.replace("/assets/", &format!("{}/", assets_path))
+    } else {
+        p.to_string()
+    }
+}
+
+fn resolve_logo_path(settings: &Settings) -> Option<String> {
+    if !settings.overlay_enabled {
+        return None;
+    }
+    settings
+        .logo_path
+        .as_deref()
+        .map(resolve_asset_path)
+        .filter(|s| !s.is_empty())
+}
+
+// Title of a playlist item: its own metadata first, then the media library's
+fn clip_title(item: &PlaylistItem, media_row: Option<&sqlx::postgres::PgRow>) -> Option<String> {
+    let library = media_row.and_then(|r| r.try_get::<serde_json::Value, _>("metadata").ok());
+    let title = [item.metadata.as_ref(), library.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.get("title").and_then(|t| t.as_str()))
+        .map(str::trim)
+        .find(|t| !t.is_empty())
+        .map(str::to_string);
+    title
+}
+
+// Filename without its extension, the fallback for clips without a title
+fn strip_extension(filename: &str) -> String {
+    std::path::Path::new(filename)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or(filename)
+        .to_string()
+}
+
+fn file_name_of(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+// ffprobe is a blocking child process: keep it off the async workers
+async fn probe(path: &str) -> Result<MediaInfo, String> {
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || FFmpegService::new().get_media_info(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// Settings a prepared item depends on: the frame, and the picture made for audio items
+// (background files as they are now)
+fn render_params(settings: &Settings) -> u64 {
+    let backgrounds: Vec<String> = [
+        &settings.audio_background_path,
+        &settings.default_image_path,
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|p| !p.trim().is_empty())
+    .map(|p| resolve_asset_path(p))
+    .collect();
+    let files: Vec<&str> = backgrounds.iter().map(String::as_str).collect();
+    render_key(
+        &files,
+        (
+            &settings.resolution,
+            &settings.fps,
+            &settings.audio_visualizer,
+            settings.audio_now_playing,
+            &settings.text_font_path,
+        ),
+    )
+}
+
+fn seconds(s: f64) -> chrono::Duration {
+    chrono::Duration::milliseconds((s * 1000.0) as i64)
+}
+
+// Cache key of a prepared item: the item, its file as it is now and the render settings
+fn prepare_key(item: &PlaylistItem, settings: &Settings) -> u64 {
+    let item_json = serde_json::to_string(item).unwrap_or_default();
+    render_key(&[&item.get_path()], (item_json, render_params(settings)))
+}
+
+// Cache key of a render: its source files as they are now, and its parameters
+fn render_key(files: &[&str], params: impl std::hash::Hash) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for file in files {
+        file.hash(&mut hasher);
+        std::fs::metadata(file)
+            .and_then(|m| m.modified())
+            .ok()
+            .hash(&mut hasher);
+    }
+    params.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Drop renders not rebuilt for a week
+fn prune_renders(dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .map(|age| age > RENDER_MAX_AGE)
+            .unwrap_or(false);
+        if stale {
+            std::fs::remove_file(entry.path()).ok();
+        }
+    }
+}
+
+// Concat entry of an item that passed pre-flight: source, inpoint and outpoint. A render
+// is cut to the item's airtime already; the file itself still needs the item's points.
+fn source_entry(
+    item: &PlaylistItem,
+    (source, trims): Source,
+) -> (String, Option<f64>, Option<f64>) {
+    if trims {
+        (source, item.inpoint(), item.outpoint())
+    } else {
+        (source, None, None)
+    }
+}
+
+// Concat demuxer entry, trimmed at the head and/or tail when the points are set
+fn write_concat_entry(
+    file: &mut std::fs::File,
+    source: &str,
+    inpoint: Option<f64>,
+    outpoint: Option<f64>,
+) -> Result<(), String> {
+    use std::io::Write;
+    writeln!(file, "file '{}'", source).map_err(|e| e.to_string())?;
+    if let Some(inpoint) = inpoint {
+        writeln!(file, "inpoint {:.3}", inpoint).map_err(|e| e.to_string())?;
+    }
+    if let Some(outpoint) = outpoint {
+        writeln!(file, "outpoint {:.3}", outpoint).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_third_failure_in_the_window_fails_over() {
+        let mut failures = ClipFailures::default();
+        let t0 = Instant::now();
+        let judge = |failures: &mut ClipFailures, secs: u64| {
+            let now = t0 + Duration::from_secs(secs);
+            judge_encoder_death(Some("/media/a.mp4"), false, false, failures, now)
+        };
+
+        assert_eq!(judge(&mut failures, 0), EncoderDeath::Retry(1));
+        assert_eq!(judge(&mut failures, 60), EncoderDeath::Retry(2));
+        assert_eq!(judge(&mut failures, 120), EncoderDeath::Failover(3));
+        failures.clear("/media/a.mp4");
+        assert_eq!(judge(&mut failures, 130), EncoderDeath::Retry(1));
+        // Clean for the whole window: the count starts over
+        assert_eq!(
+            judge(&mut failures, 130 + FAILURE_WINDOW_SECS),
+            EncoderDeath::Retry(1)
+        );
+    }
+
+    #[test]
+    fn test_deaths_not_down_to_the_clip_are_not_counted() {
+        let mut failures = ClipFailures::default();
+        let now = Instant::now();
+        let path = Some("/media/a.mp4");
+
+        for _ in 0..MAX_CLIP_FAILURES {
+            // Publisher gone, or the failover slate itself on air
+            assert_eq!(
+                judge_encoder_death(path, false, true, &mut failures, now),
+                EncoderDeath::Bridge
+            );
+            assert_eq!(
+                judge_encoder_death(path, true, false, &mut failures, now),
+                EncoderDeath::Bridge
+            );
+        }
+        assert_eq!(
+            judge_encoder_death(None, false, false, &mut failures, now),
+            EncoderDeath::Bridge
+        );
+        assert_eq!(
+            judge_encoder_death(path, false, false, &mut failures, now),
+            EncoderDeath::Retry(1)
+        );
+    }
+
+    fn trimmed_item() -> PlaylistItem {
+        serde_json::from_value(serde_json::json!({
+            "path": "/media/a.mp4",
+            "duration": 100.0,
+            "in": 10.0,
+            "out": 70.0,
+        }))
+        .unwrap()
+    }
+
+    fn candidate(name: &str, duration: f64) -> FillerCandidate {
+        FillerCandidate {
+            path: format!("/fillers/{}", name),
+            filename: name.to_string(),
+            duration,
+        }
+    }
+
+    #[test]
+    fn test_checked_item_keeps_its_points_on_the_file_only() {
+        let item = trimmed_item();
+        assert_eq!(
+            source_entry(&item, ("/media/a.mp4".to_string(), true)),
+            ("/media/a.mp4".to_string(), Some(10.0), Some(70.0))
+        );
+        // A render was made to the airtime already
+        assert_eq!(
+            source_entry(&item, ("/tmp/render.mkv".to_string(), false)),
+            ("/tmp/render.mkv".to_string(), None, None)
+        );
+    }
+
+    #[test]
+    fn test_failed_item_is_substituted_for_its_airtime() {
+        let item = trimmed_item();
+        let pool = FillerPool {
+            candidates: vec![candidate("ident", 20.0), candidate("promo", 30.0)],
+            fallback: Some(candidate("default", 15.0)),
+            ..Default::default()
+        };
+        let segments = pool.substitute(&item);
+        assert!((filler::plan_duration(&segments) - 60.0).abs() < 0.001);
+        assert!(segments.iter().all(|s| s.duration <= 30.0));
+
+        // Nothing to fill with: the item is dropped
+        assert!(FillerPool::default().substitute(&item).is_empty());
+    }
+
+    #[test]
+    fn test_override_file_repeats_to_fill_its_time() {
+        assert_eq!(override_fill(Some(100.0), Some(40.0)), Ok((100.0, 3)));
+        // Without a duration it plays once
+        assert_eq!(override_fill(None, Some(40.0)), Ok((40.0, 1)));
+        assert_eq!(override_fill(Some(10.0), Some(40.0)), Ok((10.0, 1)));
+        assert_eq!(
+            override_fill(Some(1e9), Some(1.0)),
+            Ok((1e9, MAX_SLATE_REPEATS))
+        );
+        assert!(override_fill(Some(60.0), None).is_err());
+        assert!(override_fill(Some(60.0), Some(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_override_holds_until_its_time_is_up_or_its_encoder_exits() {
+        let t0 = Utc::now();
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+        let mut info = OverrideInfo {
+            input: "/media/breaking.mp4".to_string(),
+            live: false,
+            title: None,
+            started_at: t0,
+            until: Some(at(60)),
+        };
+
+        assert_eq!(override_turn(&info, None, at(30)), OverrideTurn::Hold);
+        assert_eq!(override_turn(&info, None, at(60)), OverrideTurn::End(None));
+        // Played through before its time, or the encoder failed
+        assert_eq!(
+            override_turn(&info, Some(true), at(10)),
+            OverrideTurn::End(None)
+        );
+        assert_eq!(
+            override_turn(&info, Some(false), at(10)),
+            OverrideTurn::End(Some(EndReason::Failed))
+        );
+
+        // A live stream without a duration stays until it ends
+        info.live = true;
+        info.until = None;
+        assert_eq!(override_turn(&info, None, at(36_000)), OverrideTurn::Hold);
+        assert_eq!(
+            override_turn(&info, Some(true), at(36_000)),
+            OverrideTurn::End(None)
+        );
+    }
+
+    #[test]
+    fn test_failover_holds_the_slot_of_the_failing_clip() {
+        let t0 = Utc::now();
+        let failover = Failover {
+            clip_path: "/media/a.mp4".to_string(),
+            until: t0 + chrono::Duration::seconds(60),
+        };
+
+        assert!(failover.holds(Some("/media/a.mp4"), t0));
+        // Its slot is up, or the schedule moved on to another clip or a gap
+        assert!(!failover.holds(Some("/media/a.mp4"), t0 + chrono::Duration::seconds(60)));
+        assert!(!failover.holds(Some("/media/b.mp4"), t0));
+        assert!(!failover.holds(None, t0));
+    }
+}
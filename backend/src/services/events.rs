@@ -0,0 +1,103 @@
+use crate::models::as_run::EndReason;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Topics a WebSocket client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topic {
+    Status,
+    Clips,
+    Logs,
+    Relays,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 4] = [Topic::Status, Topic::Clips, Topic::Logs, Topic::Relays];
+
+    pub fn parse(s: &str) -> Option<Topic> {
+        match s.trim().to_lowercase().as_str() {
+            "status" => Some(Topic::Status),
+            "clips" => Some(Topic::Clips),
+            "logs" => Some(Topic::Logs),
+            "relays" => Some(Topic::Relays),
+            _ => None,
+        }
+    }
+}
+
+/// Something that happened in the engine, pushed to `/api/ws` subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlayoutEvent {
+    /// Full `PlayoutStatus`, sent when a client subscribes to status
+    Snapshot { status: Value },
+    /// Top-level `PlayoutStatus` fields that changed since the last push
+    StatusDiff { changes: Map<String, Value> },
+    /// A new clip went on air
+    ClipTransition {
+        previous: Option<String>,
+        previous_reason: Option<EndReason>,
+        current: String,
+        title: Option<String>,
+        source: String,
+        playlist_name: Option<String>,
+        planned_end: Option<DateTime<Utc>>,
+    },
+    /// Dashboard log line (same text as `add_log`)
+    Log { message: String },
+    /// A relay output changed state (active, starting, error, idle)
+    Relay {
+        protocol: String,
+        status: String,
+        previous: Option<String>,
+        up: bool,
+    },
+}
+
+impl PlayoutEvent {
+    pub fn topic(&self) -> Topic {
+        match self {
+            PlayoutEvent::Snapshot { .. } | PlayoutEvent::StatusDiff { .. } => Topic::Status,
+            PlayoutEvent::ClipTransition { .. } => Topic::Clips,
+            PlayoutEvent::Log { .. } => Topic::Logs,
+            PlayoutEvent::Relay { .. } => Topic::Relays,
+        }
+    }
+}
+
+// Pushed on their own topic, not repeated in status diffs
+const DIFF_EXCLUDED: [&str; 1] = ["logs"];
+
+/// Top-level fields of `current` that differ from `previous` (all of them without a previous)
+pub fn status_diff(previous: Option<&Value>, current: &Value) -> Map<String, Value> {
+    let Some(fields) = current.as_object() else {
+        return Map::new();
+    };
+    fields
+        .iter()
+        .filter(|(k, _)| !DIFF_EXCLUDED.contains(&k.as_str()))
+        .filter(|(k, v)| previous.and_then(|p| p.get(k.as_str())) != Some(*v))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_status_diff_only_changed_fields() {
+        let before = json!({"status": "playing", "uptime": 10, "logs": ["a"]});
+        let after = json!({"status": "playing", "uptime": 11, "logs": ["a", "b"]});
+        let diff = status_diff(Some(&before), &after);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.get("uptime"), Some(&json!(11)));
+
+        let full = status_diff(None, &after);
+        assert_eq!(full.len(), 2);
+        assert!(!full.contains_key("logs"));
+    }
+}
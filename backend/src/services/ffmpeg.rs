@@ -1,9 +1,14 @@
+use crate::models::playlist::Visualizer;
 use crate::models::settings::Settings;
+use crate::services::graphics::{self, EventLayers};
+use crate::services::loudness::{self, Loudness};
+use crate::services::scte35::{self, CueSchedule};
+use crate::services::transitions;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::process::Command;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaInfo {
     pub duration: Option<f64>,
     pub width: Option<i32>,
@@ -12,6 +17,23 @@ pub struct MediaInfo {
     pub bitrate: Option<i64>,
     pub has_audio: bool,
     pub has_video: bool,
+    /// Title and artist tags of the container (ID3 and the like)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+}
+
+/// Picture made under an audio-only clip when it is conformed
+#[derive(Debug, Clone, Default, Hash)]
+pub struct AudioPicture {
+    /// Image, or video looped, filling the frame; black when None
+    pub background: Option<String>,
+    pub visualizer: Visualizer,
+    /// Now-playing line, drawn from a text file next to the output
+    pub text: Option<String>,
+    /// Font of the now-playing line, Sans when None
+    pub font: Option<String>,
 }
 
 pub struct FFmpegService {
@@ -30,7 +52,7 @@ impl FFmpegService {
     /// Extract media information using ffprobe
     pub fn get_media_info(&self, file_path: &str) -> Result<MediaInfo, String> {
         let output = Command::new(&self.ffprobe_path)
-            .args(&[
+            .args([
                 "-v",
                 "quiet",
                 "-print_format",
@@ -58,6 +80,8 @@ impl FFmpegService {
             bitrate: None,
             has_audio: false,
             has_video: false,
+            title: None,
+            artist: None,
         };
 
         // Extract duration from format
@@ -68,6 +92,19 @@ impl FFmpegService {
             if let Some(bitrate_str) = format.get("bit_rate").and_then(|b| b.as_str()) {
                 info.bitrate = bitrate_str.parse::<i64>().ok();
             }
+            if let Some(tags) = format.get("tags").and_then(|t| t.as_object()) {
+                // Tag names are upper case in some containers (Vorbis, FLAC)
+                let tag = |name: &str| {
+                    tags.iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                        .and_then(|(_, v)| v.as_str())
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .map(String::from)
+                };
+                info.title = tag("title");
+                info.artist = tag("artist");
+            }
         }
 
         // Extract stream information
@@ -75,8 +112,14 @@ impl FFmpegService {
             for stream in streams {
                 let codec_type = stream.get("codec_type").and_then(|t| t.as_str());
 
+                // Cover art embedded in audio files is not a video track
+                let attached_pic = stream
+                    .pointer("/disposition/attached_pic")
+                    .and_then(|v| v.as_i64())
+                    == Some(1);
+
                 match codec_type {
-                    Some("video") => {
+                    Some("video") if !attached_pic => {
                         info.has_video = true;
                         info.width = stream
                             .get("width")
@@ -110,7 +153,7 @@ impl FFmpegService {
         timestamp: f64,
     ) -> Result<(), String> {
         let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+            .args([
                 "-ss",
                 &timestamp.to_string(),
                 "-i",
@@ -133,6 +176,145 @@ impl FFmpegService {
         Ok(())
     }
 
+    /// Extract a single full-resolution frame (used as a freeze frame on pause)
+    pub fn extract_frame(
+        &self,
+        input_path: &str,
+        output_path: &str,
+        timestamp: f64,
+    ) -> Result<(), String> {
+        let output = Command::new(&self.ffmpeg_path)
+            .args([
+                "-ss",
+                &timestamp.max(0.0).to_string(),
+                "-i",
+                input_path,
+                "-vframes",
+                "1",
+                "-q:v",
+                "2",
+                "-y",
+                output_path,
+            ])
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("FFmpeg frame extraction failed: {}", error));
+        }
+
+        Ok(())
+    }
+
+    /// Copy of a clip with the stream it lacks synthesized (silence, or the `picture` of an
+    /// audio clip), so it can sit in a concat sequence with clips that have both
+    pub fn conform_streams(
+        &self,
+        input_path: &str,
+        output_path: &str,
+        info: &MediaInfo,
+        picture: &AudioPicture,
+        resolution: &str,
+        fps: &str,
+    ) -> Result<(), String> {
+        let args = conform_args(input_path, output_path, info, picture, resolution, fps)?;
+        let text_path = format!("{}.txt", output_path);
+        if let Some(text) = picture.text.as_deref().filter(|_| !info.has_video) {
+            std::fs::write(&text_path, text).map_err(|e| e.to_string())?;
+        }
+        let output = Command::new(&self.ffmpeg_path)
+            .args(&args)
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e));
+        std::fs::remove_file(&text_path).ok();
+        let output = output?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("FFmpeg conform failed: {}", error));
+        }
+
+        Ok(())
+    }
+
+    /// Render still images into a clip of `total` seconds: each image holds `per_image` seconds
+    /// (the list repeats as needed), over `audio` looped and faded out, or silence
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_slides(
+        &self,
+        images: &[String],
+        output_path: &str,
+        audio: Option<&str>,
+        per_image: f64,
+        total: f64,
+        ken_burns: bool,
+        resolution: &str,
+        fps: &str,
+    ) -> Result<(), String> {
+        if images.is_empty() {
+            return Err("no images".to_string());
+        }
+        let slides = (total / per_image).ceil().max(1.0) as usize;
+        let list_path = format!("{}.txt", output_path);
+        let list: String = images
+            .iter()
+            .cycle()
+            .take(slides)
+            .map(|image| format!("file '{}'\n", image.replace('\'', "'\\''")))
+            .collect();
+        std::fs::write(&list_path, list).map_err(|e| e.to_string())?;
+
+        let args = slides_args(
+            &list_path,
+            output_path,
+            audio,
+            per_image,
+            total,
+            ken_burns,
+            resolution,
+            fps,
+        );
+        let output = Command::new(&self.ffmpeg_path)
+            .args(&args)
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e));
+        std::fs::remove_file(&list_path).ok();
+        let output = output?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("FFmpeg slide rendering failed: {}", error));
+        }
+
+        Ok(())
+    }
+
+    /// EBU R128 measurement pass (loudnorm analysis, nothing is written)
+    pub fn measure_loudness(&self, input_path: &str) -> Result<Loudness, String> {
+        let output = Command::new(&self.ffmpeg_path)
+            .args([
+                "-hide_banner",
+                "-nostats",
+                "-i",
+                input_path,
+                "-vn",
+                "-af",
+                "loudnorm=print_format=json",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            return Err(format!("FFmpeg loudness analysis failed: {}", stderr));
+        }
+        loudness::parse_loudnorm(&stderr).ok_or_else(|| "No measurable audio".to_string())
+    }
+
     /// Validate media file
     #[allow(dead_code)]
     pub fn validate_media(&self, file_path: &str) -> Result<bool, String> {
@@ -159,7 +341,7 @@ impl FFmpegService {
     #[allow(dead_code)]
     pub fn check_audio_muted(&self, file_path: &str) -> Result<bool, String> {
         let output = Command::new(&self.ffmpeg_path)
-            .args(&["-i", file_path, "-af", "volumedetect", "-f", "null", "-"])
+            .args(["-i", file_path, "-af", "volumedetect", "-f", "null", "-"])
             .output()
             .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
 
@@ -183,7 +365,7 @@ impl FFmpegService {
         fps: i32,
     ) -> Result<(), String> {
         let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+            .args([
                 "-i",
                 input_path,
                 "-vf",
@@ -227,7 +409,7 @@ impl FFmpegService {
 
         // Use VP9 for transparency support in web/overlay context
         let output = Command::new(&self.ffmpeg_path)
-            .args(&[
+            .args([
                 "-i",
                 input_path,
                 "-vf",
@@ -293,7 +475,7 @@ impl FFmpegService {
                                 let after_publish = &final_url[pos + 8..];
                                 // The pathname ends at the first '?' or '&' or end of string
                                 let end_pos = after_publish
-                                    .find(|c| c == '?' || c == '&')
+                                    .find(['?', '&'])
                                     .unwrap_or(after_publish.len());
                                 let pathname = &after_publish[..end_pos];
 
@@ -333,7 +515,73 @@ impl FFmpegService {
         }
     }
 
-    /// Start a live stream from a file with HLS preview
+    // MPEG-TS through the SCTE-35 injector: the encoder muxes to stdout, the pump adds the
+    // splice sections and a second ffmpeg copies the bytes unchanged to `url`. The encoder is
+    // returned; the forwarder ends with it (EOF on its stdin) and vice versa (broken pipe).
+    fn spawn_with_cues(
+        &self,
+        args: &[String],
+        url: &str,
+        schedule: &CueSchedule,
+        tz: Option<&str>,
+    ) -> Result<std::process::Child, String> {
+        let mut forwarder = Command::new(&self.ffmpeg_path)
+            .args([
+                "-hide_banner",
+                "-loglevel",
+                "error",
+                "-f",
+                "data",
+                "-i",
+                "pipe:0",
+                "-map",
+                "0",
+                "-c",
+                "copy",
+                "-f",
+                "data",
+                url,
+            ])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to spawn ffmpeg forwarder: {}", e))?;
+
+        let mut command = Command::new(&self.ffmpeg_path);
+        command
+            .args(args)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        if let Some(tz) = tz {
+            command.env("TZ", tz);
+        }
+        let mut encoder = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                forwarder.kill().ok();
+                forwarder.wait().ok();
+                return Err(format!("Failed to spawn ffmpeg: {}", e));
+            }
+        };
+
+        let reader = encoder.stdout.take().ok_or("No encoder stdout")?;
+        let writer = forwarder.stdin.take().ok_or("No forwarder stdin")?;
+        let schedule = schedule.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = scte35::pump(reader, writer, schedule) {
+                log::warn!("SCTE-35 injector stopped: {}", e);
+            }
+            forwarder.kill().ok();
+            forwarder.wait().ok();
+        });
+        log::info!("📺 SCTE-35 markers enabled on {}", url);
+        Ok(encoder)
+    }
+
+    /// Start a live stream from a file with HLS preview. With `cues`, SRT/UDP (MPEG-TS)
+    /// outputs carry them as SCTE-35 splice inserts.
+    #[allow(clippy::too_many_arguments)]
     pub fn start_stream(
         &self,
         input_path: &str,
@@ -342,11 +590,12 @@ impl FFmpegService {
         settings: &Settings,
         hls_preview_path: Option<&str>,
         logo_path: Option<&str>,
+        layers: &EventLayers,
+        cues: Option<&CueSchedule>,
     ) -> Result<std::process::Child, String> {
         let resolution = &settings.resolution;
         let video_bitrate = &settings.video_bitrate;
         let audio_bitrate = &settings.audio_bitrate;
-        let logo_position = settings.logo_position.as_deref();
         let overlay_opacity = settings.overlay_opacity;
         let overlay_scale = settings.overlay_scale;
         let overlay_x = settings.overlay_x.unwrap_or(50);
@@ -360,9 +609,12 @@ impl FFmpegService {
         // Log the final URL
         log::info!("✅ Final output URL: {}", effective_output_url);
 
-        let mut args = vec![
-            "-re".to_string(), // Read at native frame rate
-        ];
+        // Read at native frame rate, unless transitions pace the programme themselves or
+        // a live stream arrives at its own pace
+        let mut args = Vec::new();
+        if layers.transitions.is_empty() && !is_live_input(input_path) {
+            args.push("-re".to_string());
+        }
 
         // 1. INPUTS
 
@@ -376,6 +628,12 @@ impl FFmpegService {
             ]);
         }
 
+        // Still images (pause slate / freeze frame) are looped indefinitely
+        let is_still = is_still_image(input_path);
+        if is_still {
+            args.extend(vec!["-loop".to_string(), "1".to_string()]);
+        }
+
         if offset > 0.0 {
             args.extend(vec!["-ss".to_string(), offset.to_string()]);
         }
@@ -384,20 +642,8 @@ impl FFmpegService {
         // Input 1: Logo/Overlay (if present)
         let has_logo = if let Some(logo_path) = logo_path {
             if !logo_path.is_empty() {
-                // Add loop for video files (INPUT OPTION)
-                if logo_path.ends_with(".mp4") || logo_path.ends_with(".webm") {
-                    args.extend(vec!["-stream_loop".to_string(), "-1".to_string()]);
-                }
-
-                // Add loop for image files to ensure they persist
-                if logo_path.ends_with(".jpg")
-                    || logo_path.ends_with(".jpeg")
-                    || logo_path.ends_with(".png")
-                    || logo_path.ends_with(".webp")
-                {
-                    args.extend(vec!["-loop".to_string(), "1".to_string()]);
-                }
-
+                // Loop images and videos so the logo persists
+                args.extend(graphics::loop_input_args(logo_path));
                 args.extend(vec!["-i".to_string(), logo_path.to_string()]);
                 true
             } else {
@@ -407,46 +653,140 @@ impl FFmpegService {
             false
         };
 
+        // Silent audio input for stills (they carry no audio track)
+        let audio_input = if is_still {
+            args.extend(vec![
+                "-f".to_string(),
+                "lavfi".to_string(),
+                "-i".to_string(),
+                "anullsrc=channel_layout=stereo:sample_rate=44100".to_string(),
+            ]);
+            if has_logo {
+                "[2:a]"
+            } else {
+                "[1:a]"
+            }
+        } else {
+            "[0:a]"
+        };
+
+        // Timed graphics (secondary events) follow the other inputs
+        let first_graphic = 1 + has_logo as usize + is_still as usize;
+        for graphic in &layers.graphics {
+            args.extend(graphics::loop_input_args(&graphic.path));
+            args.extend(vec!["-i".to_string(), graphic.path.clone()]);
+        }
+        let has_graphics = !layers.graphics.is_empty()
+            || !layers.text.is_empty()
+            || !layers.transitions.is_empty();
+
         // 2. FILTER COMPLEX
         let mut filter_complex = String::new();
 
-        // Video Chain
+        // Video Chain: stages applied in turn to the programme, each with the
+        // source chain of its overlay input when it has one
+        let master = output_url == MASTER_PIPE;
+        let scale = if master {
+            // Square pixels whatever the source says, the aspect is part of the SPS
+            format!("scale={},setsar=1", resolution)
+        } else {
+            format!("scale={}", resolution)
+        };
+        let mut stages = vec![(None, scale)];
         if has_logo {
             // Get opacity and scale values with defaults
             let opacity = overlay_opacity.unwrap_or(1.0).clamp(0.0, 1.0);
             let scale = overlay_scale.unwrap_or(1.0).clamp(0.1, 2.0);
 
-            // Determine overlay position coordinates based on anchor + offsets
             // Anchors allow us to keep 50:50 spacing from corners easily
-            let pos_coords = match overlay_anchor {
-                "top-left" => format!("{}:{}", overlay_x, overlay_y),
-                "bottom-left" => format!("{}:H-h-{}", overlay_x, overlay_y),
-                "bottom-right" => format!("W-w-{}:H-h-{}", overlay_x, overlay_y),
-                _ => format!("W-w-{}:{}", overlay_x, overlay_y), // top-right default
-            };
+            let mut overlay = graphics::overlay_position(overlay_anchor, overlay_x, overlay_y);
+            if !layers.logo_hidden.is_empty() {
+                overlay.push_str(&format!(
+                    ":enable='not({})'",
+                    graphics::between_expr(&layers.logo_hidden)
+                ));
+            }
+            stages.push((
+                Some(format!(
+                    "[1:v]scale=iw*{}:ih*{},format=rgba,colorchannelmixer=aa={}",
+                    scale, scale, opacity
+                )),
+                format!("overlay={}", overlay),
+            ));
+        }
+        for (i, graphic) in layers.graphics.iter().enumerate() {
+            stages.push((
+                Some(format!(
+                    "[{}:v]scale=iw*{}:ih*{},format=rgba,colorchannelmixer=aa={}",
+                    first_graphic + i,
+                    graphic.scale,
+                    graphic.scale,
+                    graphic.opacity
+                )),
+                format!(
+                    "overlay={}:enable='{}'",
+                    graphics::overlay_position(&graphic.anchor, graphic.x, graphic.y),
+                    graphics::between_expr(&[(graphic.start, graphic.end)])
+                ),
+            ));
+        }
 
-            filter_complex.push_str(&format!(
-                "[0:v]scale={}[bg];[1:v]scale=iw*{}:ih*{},format=rgba,colorchannelmixer=aa={}[logo];[bg][logo]overlay={}[v_out];",
-                resolution, scale, scale, opacity, pos_coords
+        if !layers.text.is_empty() {
+            stages.push((None, layers.text.join(",")));
+        }
+
+        let mut video = "0:v".to_string();
+        let mut audio_input = audio_input;
+        if !layers.transitions.is_empty() {
+            filter_complex.push_str(&transitions::filter_graph(
+                &video,
+                audio_input,
+                &layers.transitions,
+                resolution,
+                fps,
             ));
-        } else {
-            filter_complex.push_str(&format!("[0:v]scale={}[v_out];", resolution));
+            video = "v_prog".to_string();
+            audio_input = "[a_prog]";
+        }
+        for (n, (source, filter)) in stages.iter().enumerate() {
+            let out = if n + 1 == stages.len() {
+                "v_out".to_string()
+            } else {
+                format!("v{}", n)
+            };
+            match source {
+                Some(source) => filter_complex.push_str(&format!(
+                    "{}[layer{}];[{}][layer{}]{}[{}];",
+                    source, n, video, n, filter, out
+                )),
+                None => filter_complex.push_str(&format!("[{}]{}[{}];", video, filter, out)),
+            }
+            video = out;
         }
 
-        // Audio Chain (Standardize to EBU R128)
-        filter_complex.push_str("[0:a]volume=0.8[a_out]");
+        // Audio Chain: per-clip gain to the loudness target, unity for clips not measured
+        let gain = if layers.gains.is_empty() {
+            "anull".to_string()
+        } else {
+            format!("volume='{}':eval=frame", loudness::gain_expr(&layers.gains))
+        };
+        filter_complex.push_str(&format!("{}{}[a_out]", audio_input, gain));
 
         // 3. CODEC SELECTION LOGIC
         // Force transcoding if logo/overlay is enabled, even if "copy" was selected.
         // Filters require re-encoding.
-        let v_codec = if has_logo && settings.video_codec == "copy" {
+        let v_codec = if master {
+            master_codecs(settings).0
+        } else if (has_logo || has_graphics || is_still) && settings.video_codec == "copy" {
             log::info!("[FFmpeg] Logo enabled, forcing libx264 transcoding instead of 'copy'");
             "h264"
         } else {
             &settings.video_codec
         };
 
-        let a_codec = if has_logo && settings.audio_codec == "copy" {
+        let a_codec = if master {
+            master_codecs(settings).1
+        } else if (has_logo || has_graphics || is_still) && settings.audio_codec == "copy" {
             log::info!("[FFmpeg] Logo/Filters enabled, forcing aac transcoding instead of 'copy'");
             "aac"
         } else {
@@ -591,6 +931,21 @@ impl FFmpegService {
             }
         }
 
+        // Pinned for the program feed (see `master_format`): frame rate, channels and PIDs
+        // never follow the source
+        if master {
+            args.extend(vec![
+                "-r".to_string(),
+                fps.to_string(),
+                "-ac".to_string(),
+                "2".to_string(),
+                "-mpegts_pmt_start_pid".to_string(),
+                "4096".to_string(),
+                "-mpegts_start_pid".to_string(),
+                "256".to_string(),
+            ]);
+        }
+
         // Determine output format based on protocol and apply packet size limits for UDP/SRT
         let mut final_output_url = effective_output_url.clone();
 
@@ -697,6 +1052,8 @@ impl FFmpegService {
 
         let output_format = if final_output_url.starts_with("rtmp://") {
             "flv"
+        } else if final_output_url == MASTER_PIPE {
+            "mpegts"
         } else if final_output_url.starts_with("srt://") || final_output_url.starts_with("udp://") {
             // Add pkt_size=1316 for MPEG-TS over UDP/SRT to avoid fragmentation
             if !final_output_url.contains("pkt_size=") {
@@ -711,14 +1068,19 @@ impl FFmpegService {
         } else {
             "flv" // Default fallback
         };
+        let cues = cues.filter(|_| output_format == "mpegts" && final_output_url != MASTER_PIPE);
 
         // 4. OUTPUT MAPPING & FORMAT (Tee or Single)
-        // Explicitly map [v_out] and [a_out] from the filter complex
-        if let Some(hls_path) = hls_preview_path {
+        // Explicitly map [v_out] and [a_out] from the filter complex. The program feed
+        // writes the preview of the master path, not its encoders.
+        if let Some(hls_path) = hls_preview_path.filter(|_| final_output_url != MASTER_PIPE) {
             // Escape any existing single quotes for the tee muxer
             let escaped_url = final_output_url.replace("'", "'\\''");
 
-            let slave_url = if final_output_url.starts_with("srt://") {
+            let slave_url = if cues.is_some() {
+                // Muxed by the injector pipeline (see spawn_with_cues)
+                "[f=mpegts]pipe:1".to_string()
+            } else if final_output_url.starts_with("srt://") {
                 // SRT NEEDS fifo + onfail=ignore to prevents blocking the whole pipeline
                 // restart_with_keyframe=1: Ensures we only send complete GOPs after a drop/connect
                 format!(
@@ -741,35 +1103,8 @@ impl FFmpegService {
             // 1. Primary Distribution Output (RTMP/SRT)
             let mut tee_outputs = vec![slave_url];
 
-            // 2. Mandatory HLS Output (for internal preview)
-            tee_outputs.push(format!(
-                "[f=hls:hls_time=2:hls_list_size=10:hls_flags=delete_segments+independent_segments]{}/stream.m3u8",
-                hls_path
-            ));
-
-            // 3. Optional DASH Output
-            if settings.dash_enabled {
-                if let Some(ref url) = settings.dash_output_url {
-                    tee_outputs.push(format!(
-                        "[f=dash:window_size=5:extra_window_size=5:remove_at_exit=1:dash_segment_type=webm]'{}'",
-                        url
-                    ));
-                }
-            }
-
-            // 4. Optional MSS Output (Smooth Streaming)
-            if settings.mss_enabled {
-                if let Some(ref url) = settings.mss_output_url {
-                    tee_outputs.push(format!("[f=ismv]'{}'", url));
-                }
-            }
-
-            // 5. Optional RIST Output (Reliable Transport)
-            if settings.rist_enabled {
-                if let Some(ref url) = settings.rist_output_url {
-                    tee_outputs.push(format!("[f=rist:pkt_size=1316]'{}'", url));
-                }
-            }
+            // 2. HLS preview and the optional DASH/MSS/RIST outputs
+            tee_outputs.extend(preview_outputs(hls_path, settings));
 
             args.extend(vec![
                 "-f".to_string(),
@@ -789,27 +1124,93 @@ impl FFmpegService {
                 "[v_out]".to_string(),
                 "-map".to_string(),
                 "[a_out]".to_string(),
-                final_output_url.to_string(),
+                if cues.is_some() {
+                    "pipe:1".to_string()
+                } else {
+                    final_output_url.to_string()
+                },
             ]);
         }
 
         // Log the complete FFmpeg command for debugging
         log::info!("FFmpeg command: {} {}", self.ffmpeg_path, args.join(" "));
 
-        let child = Command::new(&self.ffmpeg_path)
-            .args(&args)
-            .stderr(std::process::Stdio::piped())
+        // drawtext's clock shows local time of the channel
+        let tz = settings.timezone.as_deref().filter(|tz| !tz.is_empty());
+        if let Some(schedule) = cues {
+            return self.spawn_with_cues(&args, &final_output_url, schedule, tz);
+        }
+
+        let mut command = Command::new(&self.ffmpeg_path);
+        command.args(&args).stderr(std::process::Stdio::piped());
+        if final_output_url == MASTER_PIPE {
+            command.stdout(std::process::Stdio::piped());
+        }
+        if let Some(tz) = tz {
+            command.env("TZ", tz);
+        }
+        let child = command
             .spawn()
             .map_err(|e| format!("Failed to spawn ffmpeg: {}", e))?;
 
         Ok(child)
     }
 
-    /// Start a relay process that copies a stream to a destination
+    /// Arguments of the program feed publisher: remuxes the MPEG-TS written to its stdin to
+    /// RTMP, riding out MediaMTX restarts like the master encoder did, and writes the HLS
+    /// preview (plus DASH/MSS/RIST when enabled) from the same stream
+    pub fn feed_publisher_args(
+        &self,
+        output_url: &str,
+        hls_path: &str,
+        settings: &Settings,
+    ) -> Vec<String> {
+        let url = self.map_output_url(output_url).replace("'", "'\\''");
+        let mut outputs = vec![format!(
+            "[f=fifo:fifo_format=flv:onfail=ignore:queue_size=60000:attempt_recovery=1:recovery_wait_time=3:drop_pkts_on_overflow=1:restart_with_keyframe=1]'{}'",
+            url
+        )];
+        outputs.extend(preview_outputs(hls_path, settings));
+
+        [
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-f",
+            "mpegts",
+            "-i",
+            "pipe:0",
+            "-map",
+            "0",
+            "-c",
+            "copy",
+            "-f",
+            "tee",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .chain(std::iter::once(outputs.join("|")))
+        .collect()
+    }
+
+    pub fn start_feed_publisher(&self, args: &[String]) -> Result<std::process::Child, String> {
+        log::info!("📺 Program feed: {} {}", self.ffmpeg_path, args.join(" "));
+        Command::new(&self.ffmpeg_path)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to spawn feed publisher: {}", e))
+    }
+
+    /// Start a relay process that copies a stream to a destination; with `cues`, SRT/UDP
+    /// destinations get them as SCTE-35 splice inserts
     pub fn start_relay(
         &self,
         input_url: &str,
         output_url: &str,
+        cues: Option<&CueSchedule>,
     ) -> Result<std::process::Child, String> {
         let final_input_url = self.map_output_url(input_url);
         let mut final_output_url = self.map_output_url(output_url);
@@ -879,21 +1280,29 @@ impl FFmpegService {
                 final_output_url = format!("{}{}pkt_size=1316", final_output_url, separator3);
             }
 
-            args.extend(vec![
-                "-f".to_string(),
-                "fifo".to_string(),
-                "-fifo_format".to_string(),
-                "mpegts".to_string(),
-                "-queue_size".to_string(),
-                "60000".to_string(),
-                "-attempt_recovery".to_string(),
-                "1".to_string(),
-                "-drop_pkts_on_overflow".to_string(),
-                "1".to_string(),
-                "-recovery_wait_time".to_string(),
-                "1".to_string(),
-                final_output_url.to_string(),
-            ]);
+            if cues.is_some() {
+                args.extend(vec![
+                    "-f".to_string(),
+                    "mpegts".to_string(),
+                    "pipe:1".to_string(),
+                ]);
+            } else {
+                args.extend(vec![
+                    "-f".to_string(),
+                    "fifo".to_string(),
+                    "-fifo_format".to_string(),
+                    "mpegts".to_string(),
+                    "-queue_size".to_string(),
+                    "60000".to_string(),
+                    "-attempt_recovery".to_string(),
+                    "1".to_string(),
+                    "-drop_pkts_on_overflow".to_string(),
+                    "1".to_string(),
+                    "-recovery_wait_time".to_string(),
+                    "1".to_string(),
+                    final_output_url.to_string(),
+                ]);
+            }
         } else if final_output_url.starts_with("udp://") {
             // Very basic multicast range check (224.x.x.x to 239.x.x.x)
             let is_multicast = final_output_url.contains("://224.")
@@ -983,15 +1392,25 @@ impl FFmpegService {
             args.extend(vec![
                 "-f".to_string(),
                 "mpegts".to_string(),
-                final_output_url.to_string(),
+                if cues.is_some() {
+                    "pipe:1".to_string()
+                } else {
+                    final_output_url.to_string()
+                },
             ]);
         } else {
-            args.push(final_output_url);
+            args.push(final_output_url.clone());
         }
 
         let cmd_str = format!("{} {}", self.ffmpeg_path, args.join(" "));
         log::info!("FFmpeg Relay command: {}", cmd_str);
 
+        let is_ts =
+            final_output_url.starts_with("srt://") || final_output_url.starts_with("udp://");
+        if let Some(schedule) = cues.filter(|_| is_ts) {
+            return self.spawn_with_cues(&args, &final_output_url, schedule, None);
+        }
+
         match Command::new(&self.ffmpeg_path)
             .args(&args)
             .stdout(std::process::Stdio::piped())
@@ -1014,6 +1433,282 @@ impl FFmpegService {
     }
 }
 
+/// Arguments of `conform_streams`: the clip's own stream is copied, the missing one is
+/// generated and ends with it. An audio clip's picture is one filter graph: the background
+/// scaled to fill the frame, the visualizer along the bottom, the now-playing line on top.
+fn conform_args(
+    input_path: &str,
+    output_path: &str,
+    info: &MediaInfo,
+    picture: &AudioPicture,
+    resolution: &str,
+    fps: &str,
+) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec!["-hide_banner".into(), "-y".into()];
+    match (info.has_video, info.has_audio) {
+        (true, false) => {
+            args.extend(["-i".into(), input_path.to_string()]);
+            args.extend([
+                "-f".into(),
+                "lavfi".into(),
+                "-i".into(),
+                format!(
+                    "anullsrc=channel_layout=stereo:sample_rate={}",
+                    CONFORM_SAMPLE_RATE
+                ),
+            ]);
+            args.extend(["-map".into(), "0:v:0".into(), "-map".into(), "1:a:0".into()]);
+            args.extend(["-c:v".into(), "copy".into(), "-c:a".into(), "aac".into()]);
+        }
+        (false, true) => {
+            let (w, h) = frame_size(resolution);
+            let still = match picture.background.as_deref() {
+                Some(image) if is_still_image(image) => {
+                    args.extend(["-loop", "1", "-framerate", fps, "-i", image].map(String::from));
+                    true
+                }
+                Some(video) => {
+                    args.extend(["-stream_loop", "-1", "-i", video].map(String::from));
+                    false
+                }
+                None => {
+                    args.extend([
+                        "-f".into(),
+                        "lavfi".into(),
+                        "-i".into(),
+                        format!("color=c=black:s={}:r={}", resolution, fps),
+                    ]);
+                    true
+                }
+            };
+            args.extend(["-i".into(), input_path.to_string()]);
+
+            let mut graph = format!(
+                "[0:v]scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h},\
+                 setsar=1,fps={fps},format=yuv420p[bg]"
+            );
+            let mut video = "bg";
+            let band = h / 4;
+            let visualizer = match picture.visualizer {
+                Visualizer::None => None,
+                Visualizer::Waves => Some(format!(
+                    "showwaves=s={w}x{band}:mode=cline:rate={fps}:colors=white"
+                )),
+                Visualizer::Spectrum => Some(format!(
+                    "showspectrum=s={w}x{band}:mode=combined:slide=scroll:color=intensity,\
+                     fps={fps},format=yuva420p,colorchannelmixer=aa=0.7"
+                )),
+            };
+            if let Some(visualizer) = &visualizer {
+                graph.push_str(&format!(
+                    ";[1:a]{visualizer}[vis];[bg][vis]overlay=0:H-h-{margin}[vv]",
+                    margin = h / 20
+                ));
+                video = "vv";
+            }
+            if picture.text.is_some() {
+                let font = match picture.font.as_deref() {
+                    Some(path) => format!("fontfile='{}'", path),
+                    None => "font='Sans'".to_string(),
+                };
+                graph.push_str(&format!(
+                    ";[{video}]drawtext={font}:textfile='{output_path}.txt':fontsize={size}:\
+                     fontcolor=white:box=1:boxcolor=black@0.5:boxborderw={pad}:x={pad}:y={pad}[vt]",
+                    size = h / 24,
+                    pad = h / 40
+                ));
+                video = "vt";
+            }
+
+            args.extend([
+                "-filter_complex".into(),
+                graph,
+                "-map".into(),
+                format!("[{}]", video),
+                "-map".into(),
+                "1:a:0".into(),
+                "-c:v".into(),
+                "libx264".into(),
+                "-preset".into(),
+                "ultrafast".into(),
+            ]);
+            // Nothing moves on a still background without a visualizer
+            if still && visualizer.is_none() {
+                args.extend(["-tune".into(), "stillimage".into()]);
+            }
+            args.extend(["-c:a".into(), "copy".into()]);
+        }
+        _ => return Err("nothing to conform".to_string()),
+    }
+    args.extend(["-shortest".into(), output_path.to_string()]);
+    Ok(args)
+}
+
+/// Arguments of `render_slides`: the image list goes through the concat demuxer, one frame
+/// per image, and `zoompan` turns every frame into `per_image` seconds of video
+#[allow(clippy::too_many_arguments)]
+fn slides_args(
+    list_path: &str,
+    output_path: &str,
+    audio: Option<&str>,
+    per_image: f64,
+    total: f64,
+    ken_burns: bool,
+    resolution: &str,
+    fps: &str,
+) -> Vec<String> {
+    let (w, h) = frame_size(resolution);
+    let frames = (per_image * fps.parse::<f64>().unwrap_or(25.0))
+        .round()
+        .max(1.0);
+    // Zoom in on one image and out on the next, 20% over the image's time on screen
+    let zoom = if ken_burns {
+        format!(
+            "if(mod(in,2),1.2-0.2*mod(on,{f})/{f},1+0.2*mod(on,{f})/{f})",
+            f = frames
+        )
+    } else {
+        "1".to_string()
+    };
+    // Fitted at twice the output size so the zoom does not step from pixel to pixel
+    let video_filter = format!(
+        "scale={w2}:{h2}:force_original_aspect_ratio=decrease,pad={w2}:{h2}:(ow-iw)/2:(oh-ih)/2,setsar=1,\
+         zoompan=z='{zoom}':x='iw/2-(iw/zoom/2)':y='ih/2-(ih/zoom/2)':d={frames}:s={w}x{h}:fps={fps},\
+         format=yuv420p",
+        w2 = w * 2,
+        h2 = h * 2,
+    );
+
+    let mut args: Vec<String> = vec!["-hide_banner".into(), "-y".into()];
+    args.extend(["-f", "concat", "-safe", "0", "-i", list_path].map(String::from));
+    match audio {
+        Some(bed) => {
+            args.extend(["-stream_loop", "-1", "-i", bed].map(String::from));
+        }
+        None => args.extend([
+            "-f".into(),
+            "lavfi".into(),
+            "-i".into(),
+            format!(
+                "anullsrc=channel_layout=stereo:sample_rate={}",
+                CONFORM_SAMPLE_RATE
+            ),
+        ]),
+    }
+    let fade = (total / 10.0).min(2.0);
+    args.extend([
+        "-map".into(),
+        "0:v:0".into(),
+        "-map".into(),
+        "1:a:0".into(),
+        "-vf".into(),
+        video_filter,
+        "-af".into(),
+        format!(
+            "aresample={},afade=t=out:st={:.3}:d={:.3}",
+            CONFORM_SAMPLE_RATE,
+            total - fade,
+            fade
+        ),
+        "-c:v".into(),
+        "libx264".into(),
+        "-preset".into(),
+        "veryfast".into(),
+        "-c:a".into(),
+        "aac".into(),
+        "-t".into(),
+        format!("{:.3}", total),
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Width and height of a `WxH` (or `W:H`) resolution, 1920x1080 when unreadable
+fn frame_size(resolution: &str) -> (u32, u32) {
+    resolution
+        .split_once(['x', ':'])
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .unwrap_or((1920, 1080))
+}
+
+/// Output of master encoders: MPEG-TS on stdout, read by the channel's program feed
+pub const MASTER_PIPE: &str = "pipe:1";
+
+// Codecs of a master encoder: the program feed splices its encoders, so "copy" (and
+// anything unknown) is encoded like any other source
+fn master_codecs(settings: &Settings) -> (&str, &str) {
+    let video = match settings.video_codec.as_str() {
+        codec @ ("hevc" | "vp8" | "vp9" | "av1") => codec,
+        _ => "h264",
+    };
+    let audio = match settings.audio_codec.as_str() {
+        "opus" => "opus",
+        _ => "aac",
+    };
+    (video, audio)
+}
+
+/// Stream format master encoders built from `settings` put on the program feed. Their
+/// output parameters are pinned, so encoders of one format splice into one another and the
+/// feed's publisher has to restart only when this changes.
+pub fn master_format(settings: &Settings) -> String {
+    let (video, audio) = master_codecs(settings);
+    format!(
+        "{} {} {}fps, {} stereo",
+        video, settings.resolution, settings.fps, audio
+    )
+}
+
+// Tee outputs for the HLS preview in `hls_path` and the optional DASH/MSS/RIST outputs.
+// A restarted writer appends to the preview playlist after a discontinuity.
+fn preview_outputs(hls_path: &str, settings: &Settings) -> Vec<String> {
+    let mut outputs = vec![format!(
+        "[f=hls:hls_time=2:hls_list_size=10:hls_flags=delete_segments+independent_segments+program_date_time+append_list+discont_start]{}/stream.m3u8",
+        hls_path
+    )];
+    if settings.dash_enabled {
+        if let Some(ref url) = settings.dash_output_url {
+            outputs.push(format!(
+                "[f=dash:window_size=5:extra_window_size=5:remove_at_exit=1:dash_segment_type=webm]'{}'",
+                url
+            ));
+        }
+    }
+    if settings.mss_enabled {
+        if let Some(ref url) = settings.mss_output_url {
+            outputs.push(format!("[f=ismv]'{}'", url));
+        }
+    }
+    if settings.rist_enabled {
+        if let Some(ref url) = settings.rist_output_url {
+            outputs.push(format!("[f=rist:pkt_size=1316]'{}'", url));
+        }
+    }
+    outputs
+}
+
+/// Sample rate of the silence added to clips without audio
+const CONFORM_SAMPLE_RATE: u32 = 48000;
+
+/// Whether the path points to a still image rather than a video/audio clip
+pub fn is_still_image(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.ends_with(".jpg")
+        || lower.ends_with(".jpeg")
+        || lower.ends_with(".png")
+        || lower.ends_with(".webp")
+        || lower.ends_with(".bmp")
+}
+
+/// Whether the input is a network stream (live break-in) rather than a file
+pub fn is_live_input(input: &str) -> bool {
+    [
+        "rtmp://", "rtmps://", "rtsp://", "srt://", "udp://", "http://", "https://",
+    ]
+    .iter()
+    .any(|scheme| input.starts_with(scheme))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1024,4 +1719,96 @@ mod tests {
         assert!(!service.ffmpeg_path.is_empty());
         assert!(!service.ffprobe_path.is_empty());
     }
+
+    #[test]
+    fn test_conform_args() {
+        let mut info = MediaInfo {
+            duration: Some(60.0),
+            width: Some(1920),
+            height: Some(1080),
+            codec: Some("h264".to_string()),
+            bitrate: None,
+            has_audio: false,
+            has_video: true,
+            title: None,
+            artist: None,
+        };
+        let black = AudioPicture::default();
+        let args = conform_args("in.mp4", "out.mkv", &info, &black, "1920x1080", "25").unwrap();
+        assert!(args.contains(&"anullsrc=channel_layout=stereo:sample_rate=48000".to_string()));
+        assert!(args.windows(2).any(|w| w == ["-c:v", "copy"]));
+        assert_eq!(args.last().map(String::as_str), Some("out.mkv"));
+
+        info.has_audio = true;
+        info.has_video = false;
+        let args = conform_args("in.mp3", "out.mkv", &info, &black, "1920x1080", "25").unwrap();
+        assert!(args.contains(&"color=c=black:s=1920x1080:r=25".to_string()));
+        assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+        assert!(args.windows(2).any(|w| w == ["-tune", "stillimage"]));
+
+        let mut picture = AudioPicture {
+            background: Some("bg.png".to_string()),
+            ..Default::default()
+        };
+        let args = conform_args("in.mp3", "out.mkv", &info, &picture, "1920x1080", "25").unwrap();
+        assert!(args.windows(2).any(|w| w == ["-i", "bg.png"]));
+
+        picture.background = Some("loop.mp4".to_string());
+        picture.visualizer = Visualizer::Waves;
+        picture.text = Some("Artist - Title".to_string());
+        let args = conform_args("in.mp3", "out.mkv", &info, &picture, "1280x720", "25").unwrap();
+        assert!(args
+            .windows(4)
+            .any(|w| w == ["-stream_loop", "-1", "-i", "loop.mp4"]));
+        let graph = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert!(graph.contains("[1:a]showwaves=s=1280x180:mode=cline:rate=25:colors=white[vis]"));
+        assert!(graph.contains("[bg][vis]overlay=0:H-h-36[vv]"));
+        assert!(graph.contains(";[vv]drawtext=font='Sans':textfile='out.mkv.txt':"));
+        assert!(args.windows(2).any(|w| w == ["-map", "[vt]"]));
+        assert!(!args.contains(&"stillimage".to_string()));
+
+        info.has_audio = false;
+        assert!(conform_args("in", "out.mkv", &info, &black, "1920x1080", "25").is_err());
+    }
+
+    #[test]
+    fn test_is_live_input() {
+        assert!(is_live_input("srt://10.0.0.5:9000?mode=caller"));
+        assert!(is_live_input("https://example.com/live/index.m3u8"));
+        assert!(!is_live_input(
+            "/var/lib/onepa-playout/assets/media/alert.mp4"
+        ));
+    }
+
+    #[test]
+    fn test_slides_args() {
+        assert_eq!(frame_size("1280x720"), (1280, 720));
+        assert_eq!(frame_size("1280:720"), (1280, 720));
+        assert_eq!(frame_size("hd"), (1920, 1080));
+
+        let args = slides_args(
+            "list.txt", "out.mkv", None, 5.0, 20.0, true, "1280x720", "25",
+        );
+        let vf = &args[args.iter().position(|a| a == "-vf").unwrap() + 1];
+        assert!(vf.starts_with("scale=2560:1440:"));
+        assert!(vf.contains(":d=125:s=1280x720:fps=25"));
+        assert!(vf.contains("1+0.2*mod(on,125)/125"));
+        assert!(args.contains(&"aresample=48000,afade=t=out:st=18.000:d=2.000".to_string()));
+        assert!(args.contains(&"anullsrc=channel_layout=stereo:sample_rate=48000".to_string()));
+
+        let args = slides_args(
+            "list.txt",
+            "out.mkv",
+            Some("bed.mp3"),
+            10.0,
+            10.0,
+            false,
+            "1920x1080",
+            "25",
+        );
+        assert!(args
+            .windows(4)
+            .any(|w| w == ["-stream_loop", "-1", "-i", "bed.mp3"]));
+        assert!(args.iter().any(|a| a.contains("zoompan=z='1':")));
+    }
 }
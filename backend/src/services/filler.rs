@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+/// A clip that can be used to fill airtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillerCandidate {
+    pub path: String,
+    pub filename: String,
+    pub duration: f64,
+}
+
+/// One entry of a filler plan, played back-to-back through the concat demuxer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillerSegment {
+    pub path: String,
+    pub filename: String,
+    /// Airtime of this segment (already trimmed)
+    pub duration: f64,
+    /// Set when the clip must be cut short to land on the next start
+    pub outpoint: Option<f64>,
+}
+
+// Remainders shorter than this are not worth a separate segment
+const MIN_SEGMENT: f64 = 0.5;
+
+/// Fill `gap` seconds with filler clips so the last one ends on time.
+///
+/// Clips are rotated (least used first, longest first on ties) while they still
+/// fit; the remainder is covered by a trimmed `fallback` clip (default video),
+/// or by trimming the shortest filler when no fallback is available.
+pub fn plan_fill(
+    gap: f64,
+    candidates: &[FillerCandidate],
+    fallback: Option<&FillerCandidate>,
+) -> Vec<FillerSegment> {
+    let mut plan = Vec::new();
+    if gap < MIN_SEGMENT {
+        return plan;
+    }
+
+    let usable: Vec<&FillerCandidate> = candidates
+        .iter()
+        .filter(|c| c.duration >= MIN_SEGMENT)
+        .collect();
+    let mut uses = vec![0usize; usable.len()];
+    let mut last: Option<usize> = None;
+    let mut remaining = gap;
+
+    loop {
+        let fitting = |avoid: Option<usize>| {
+            usable
+                .iter()
+                .enumerate()
+                .filter(|(i, c)| c.duration <= remaining && Some(*i) != avoid)
+                .min_by(|(ia, a), (ib, b)| {
+                    uses[*ia]
+                        .cmp(&uses[*ib])
+                        .then(b.duration.total_cmp(&a.duration))
+                })
+                .map(|(i, _)| i)
+        };
+        // Avoid repeating a clip back-to-back unless it is the only one that fits
+        let pick = fitting(last).or_else(|| fitting(None));
+
+        let Some(i) = pick else { break };
+        let c = usable[i];
+        plan.push(FillerSegment {
+            path: c.path.clone(),
+            filename: c.filename.clone(),
+            duration: c.duration,
+            outpoint: None,
+        });
+        uses[i] += 1;
+        last = Some(i);
+        remaining -= c.duration;
+    }
+
+    // Cover whatever is left with trimmed clips
+    let trim_source = fallback.filter(|f| f.duration >= MIN_SEGMENT).or_else(|| {
+        usable
+            .iter()
+            .min_by(|a, b| a.duration.total_cmp(&b.duration))
+            .copied()
+    });
+
+    if let Some(src) = trim_source {
+        while remaining >= MIN_SEGMENT {
+            let take = remaining.min(src.duration);
+            plan.push(FillerSegment {
+                path: src.path.clone(),
+                filename: src.filename.clone(),
+                duration: take,
+                outpoint: if take < src.duration {
+                    Some(take)
+                } else {
+                    None
+                },
+            });
+            remaining -= take;
+        }
+    }
+
+    plan
+}
+
+/// Total airtime of a plan
+pub fn plan_duration(plan: &[FillerSegment]) -> f64 {
+    plan.iter().map(|s| s.duration).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(name: &str, duration: f64) -> FillerCandidate {
+        FillerCandidate {
+            path: format!("/fillers/{}", name),
+            filename: name.to_string(),
+            duration,
+        }
+    }
+
+    #[test]
+    fn test_plan_ends_on_time() {
+        let fillers = vec![
+            clip("a.mp4", 30.0),
+            clip("b.mp4", 20.0),
+            clip("c.mp4", 10.0),
+        ];
+        let fallback = clip("default.mp4", 60.0);
+        let plan = plan_fill(137.0, &fillers, Some(&fallback));
+        assert!((plan_duration(&plan) - 137.0).abs() < 0.001);
+        let last = plan.last().unwrap();
+        assert_eq!(last.filename, "default.mp4");
+        assert_eq!(last.outpoint, Some(7.0));
+    }
+
+    #[test]
+    fn test_plan_rotates_fillers() {
+        let fillers = vec![clip("a.mp4", 30.0), clip("b.mp4", 30.0)];
+        let plan = plan_fill(120.0, &fillers, None);
+        let names: Vec<_> = plan.iter().map(|s| s.filename.as_str()).collect();
+        assert_eq!(names, vec!["a.mp4", "b.mp4", "a.mp4", "b.mp4"]);
+    }
+
+    #[test]
+    fn test_plan_without_fillers_uses_fallback() {
+        let fallback = clip("default.mp4", 50.0);
+        let plan = plan_fill(120.0, &[], Some(&fallback));
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[2].outpoint, Some(20.0));
+        assert!(plan_fill(120.0, &[], None).is_empty());
+    }
+}
@@ -0,0 +1,434 @@
+use crate::models::playlist::{self, PlaylistItem, SecondaryKind, Transition};
+use crate::models::settings::Settings;
+use crate::services::transitions::TimedTransition;
+use std::path::Path;
+
+/// Graphic inputs per encoder; later events wait for the next sequence
+pub const MAX_GRAPHICS: usize = 8;
+
+/// Text layer files in the channel's text directory
+pub const TICKER_FILE: &str = "ticker.txt";
+pub const TEXT_FILE: &str = "text.txt";
+pub const UPNEXT_FILE: &str = "upnext.txt";
+
+/// A graphic on screen between two timestamps of the running encoder
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedGraphic {
+    pub path: String,
+    pub start: f64,
+    pub end: f64,
+    pub anchor: String,
+    pub x: i32,
+    pub y: i32,
+    pub opacity: f32,
+    pub scale: f32,
+}
+
+/// What changes over the airtime of a concat sequence, in seconds since the encoder started
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventLayers {
+    pub graphics: Vec<TimedGraphic>,
+    /// Windows during which the channel logo is off screen
+    pub logo_hidden: Vec<(f64, f64)>,
+    /// drawbox/drawtext filters drawn on top of everything else
+    pub text: Vec<String>,
+    /// Airtime of each item of the sequence
+    pub items: Vec<(f64, f64)>,
+    /// Loudness gain (linear) over an item's airtime
+    pub gains: Vec<(f64, f64, f64)>,
+    /// Fades and crossfades between items
+    pub transitions: Vec<TimedTransition>,
+}
+
+impl EventLayers {
+    /// Layers of `items` played back to back, starting `offset` seconds into the first one.
+    ///
+    /// An item's airtime ends where the transition into the next one begins. Events are
+    /// clipped to their item; those already over when the encoder starts are dropped.
+    pub fn for_sequence(
+        items: &[&PlaylistItem],
+        offset: f64,
+        default_transition: Option<&Transition>,
+    ) -> Self {
+        let mut layers = Self::default();
+        let transitions = playlist::transitions(items.iter().copied(), default_transition);
+        let mut item_start = -offset;
+        let mut input_start = -offset;
+        for (index, item) in items.iter().enumerate() {
+            if let Some(t) = transitions[index] {
+                layers.transitions.push(TimedTransition {
+                    kind: t.kind,
+                    cut: input_start,
+                    start: item_start,
+                    duration: t.duration,
+                });
+            }
+            let overlap = transitions
+                .get(index + 1)
+                .copied()
+                .flatten()
+                .map_or(0.0, |t| t.duration);
+            let item_end = item_start + item.effective_duration() - overlap;
+            input_start += item.effective_duration();
+            for event in &item.events {
+                let start = item_start + event.offset.max(0.0);
+                let end = event
+                    .duration
+                    .map(|d| start + d.max(0.0))
+                    .unwrap_or(item_end)
+                    .min(item_end);
+                if end <= start || end <= 0.0 {
+                    continue;
+                }
+                let window = (start.max(0.0), end);
+                match event.kind {
+                    SecondaryKind::HideLogo => layers.logo_hidden.push(window),
+                    SecondaryKind::Graphic => {
+                        let Some(path) = event.path.as_deref().filter(|p| !p.is_empty()) else {
+                            continue;
+                        };
+                        if layers.graphics.len() >= MAX_GRAPHICS {
+                            continue;
+                        }
+                        layers.graphics.push(TimedGraphic {
+                            path: path.to_string(),
+                            start: window.0,
+                            end: window.1,
+                            anchor: event
+                                .anchor
+                                .clone()
+                                .unwrap_or_else(|| "top-right".to_string()),
+                            x: event.x.unwrap_or(50),
+                            y: event.y.unwrap_or(50),
+                            opacity: event.opacity.unwrap_or(1.0).clamp(0.0, 1.0),
+                            scale: event.scale.unwrap_or(1.0).clamp(0.1, 2.0),
+                        });
+                    }
+                }
+            }
+            layers.items.push((item_start.max(0.0), item_end.max(0.0)));
+            item_start = item_end;
+        }
+        layers
+    }
+}
+
+/// Text layers for the channel settings, reading their text from files in `dir`.
+///
+/// drawtext reloads the files on every frame, so the API (ticker, static text) and the
+/// engine (up next) change the text without a restart. `items` are the airtime windows of
+/// the sequence on air, for the up next lower third and the countdown.
+pub fn text_filters(settings: &Settings, dir: &Path, items: &[(f64, f64)]) -> Vec<String> {
+    let size = settings.text_font_size.unwrap_or(36).clamp(8, 200);
+    let color = non_empty(&settings.text_color).unwrap_or("white");
+    let box_color = non_empty(&settings.text_box_color).unwrap_or("black@0.6");
+    let font = match non_empty(&settings.text_font_path) {
+        Some(path) => format!("fontfile='{}'", path),
+        None => "font='Sans'".to_string(),
+    };
+    let drawtext = |source: &str| {
+        format!(
+            "drawtext={}:{}:fontsize={}:fontcolor={}",
+            font, source, size, color
+        )
+    };
+    // %{...} sequences in file text are printed as is
+    let from_file = |file: &str| {
+        format!(
+            "textfile='{}':reload=1:expansion=none",
+            dir.join(file).to_string_lossy()
+        )
+    };
+    let boxed = if settings.text_box {
+        format!(":box=1:boxcolor={}:boxborderw={}", box_color, size / 3)
+    } else {
+        String::new()
+    };
+
+    let mut filters = Vec::new();
+    let band = size * 2;
+    let ticker_top = settings.ticker_position.as_deref() == Some("top");
+    if settings.ticker_enabled {
+        let band_y = if ticker_top {
+            "0".to_string()
+        } else {
+            format!("ih-{}", band)
+        };
+        if settings.text_box {
+            filters.push(format!(
+                "drawbox=x=0:y={}:w=iw:h={}:color={}:t=fill",
+                band_y, band, box_color
+            ));
+        }
+        let speed = settings.ticker_speed.unwrap_or(120).clamp(10, 1000);
+        let text_y = if ticker_top {
+            format!("({}-th)/2", band)
+        } else {
+            format!("h-{}+({}-th)/2", band, band)
+        };
+        filters.push(format!(
+            "{}:x='w-mod(t*{},w+tw)':y='{}'",
+            drawtext(&from_file(TICKER_FILE)),
+            speed,
+            text_y
+        ));
+    }
+
+    // Corner placement, keeping clear of the ticker band on the same edge
+    let top = if settings.ticker_enabled && ticker_top {
+        50 + band
+    } else {
+        50
+    };
+    let bottom = if settings.ticker_enabled && !ticker_top {
+        50 + band
+    } else {
+        50
+    };
+    let anchored = |anchor: &str| match anchor {
+        "top-right" => format!(":x=w-tw-50:y={}", top),
+        "bottom-left" => format!(":x=50:y=h-th-{}", bottom),
+        "bottom-right" => format!(":x=w-tw-50:y=h-th-{}", bottom),
+        _ => format!(":x=50:y={}", top),
+    };
+
+    if settings.text_enabled {
+        let anchor = settings.text_anchor.as_deref().unwrap_or("top-left");
+        filters.push(format!(
+            "{}{}{}",
+            drawtext(&from_file(TEXT_FILE)),
+            boxed,
+            anchored(anchor)
+        ));
+    }
+    if settings.upnext_enabled {
+        let lead = settings.upnext_lead.unwrap_or(10).clamp(1, 300) as f64;
+        let windows: Vec<_> = items
+            .iter()
+            .map(|&(start, end)| ((end - lead).max(start), end))
+            .filter(|(start, end)| end > start)
+            .collect();
+        if !windows.is_empty() {
+            filters.push(format!(
+                "{}{}{}:enable='{}'",
+                drawtext(&from_file(UPNEXT_FILE)),
+                boxed,
+                anchored("bottom-left"),
+                between_expr(&windows)
+            ));
+        }
+    }
+    let clock_anchor = settings.clock_anchor.as_deref().unwrap_or("bottom-right");
+    match settings.clock_mode.as_deref() {
+        // Wall clock of the encoder process, which runs in the channel timezone
+        Some("clock") => filters.push(format!(
+            "{}{}{}",
+            drawtext("text='%{localtime\\:%X}'"),
+            boxed,
+            anchored(clock_anchor)
+        )),
+        Some("countdown") if !items.is_empty() => {
+            let left = remaining_expr(items);
+            filters.push(format!(
+                "{}{}{}:enable='{}'",
+                drawtext(&format!(
+                    "text='%{{eif\\:trunc({}/60)\\:d\\:2}}\\:%{{eif\\:mod(trunc({}),60)\\:d\\:2}}'",
+                    left, left
+                )),
+                boxed,
+                anchored(clock_anchor),
+                between_expr(items)
+            ));
+        }
+        _ => {}
+    }
+    filters
+}
+
+/// Everything the text layers take from the settings; a change needs a new encoder
+pub fn text_settings_key(settings: &Settings) -> String {
+    format!(
+        "{:?}",
+        (
+            (
+                settings.ticker_enabled,
+                settings.ticker_speed,
+                &settings.ticker_position
+            ),
+            (
+                settings.text_enabled,
+                &settings.text_anchor,
+                &settings.text_font_path
+            ),
+            (settings.text_font_size, &settings.text_color),
+            (settings.text_box, &settings.text_box_color),
+            (settings.upnext_enabled, settings.upnext_lead),
+            (&settings.clock_mode, &settings.clock_anchor),
+        )
+    )
+}
+
+/// Seconds left in whichever item window contains `t`
+fn remaining_expr(items: &[(f64, f64)]) -> String {
+    let terms = items
+        .iter()
+        .map(|(start, end)| format!("gte(t,{:.3})*lt(t,{:.3})*({:.3}-t)", start, end, end))
+        .collect::<Vec<_>>();
+    format!("({})", terms.join("+"))
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.trim().is_empty())
+}
+
+/// A ticker is one line: lines of the submitted text are joined with a separator
+pub fn ticker_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("   •   ")
+}
+
+/// Replace a text layer file in one step so drawtext never reads it half written
+pub fn write_text_file(dir: &Path, file: &str, text: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let tmp = dir.join(format!(".{}.tmp", file));
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(&tmp, dir.join(file))
+}
+
+/// `enable` expression that is true inside any of `windows`
+pub fn between_expr(windows: &[(f64, f64)]) -> String {
+    windows
+        .iter()
+        .map(|(start, end)| format!("between(t,{:.3},{:.3})", start, end))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// overlay `x:y` for an anchor corner and its margins in pixels
+pub fn overlay_position(anchor: &str, x: i32, y: i32) -> String {
+    match anchor {
+        "top-left" => format!("{}:{}", x, y),
+        "bottom-left" => format!("{}:H-h-{}", x, y),
+        "bottom-right" => format!("W-w-{}:H-h-{}", x, y),
+        _ => format!("W-w-{}:{}", x, y), // top-right default
+    }
+}
+
+/// Input options that keep an overlay source running for the whole stream
+pub fn loop_input_args(path: &str) -> Vec<String> {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".mp4") || lower.ends_with(".webm") {
+        vec!["-stream_loop".to_string(), "-1".to_string()]
+    } else if lower.ends_with(".jpg")
+        || lower.ends_with(".jpeg")
+        || lower.ends_with(".png")
+        || lower.ends_with(".webp")
+    {
+        vec!["-loop".to_string(), "1".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(duration: f64, events: serde_json::Value) -> PlaylistItem {
+        serde_json::from_value(serde_json::json!({
+            "source": "/media/a.mp4",
+            "duration": duration,
+            "events": events
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_for_sequence_windows() {
+        let a = item(
+            100.0,
+            serde_json::json!([
+                {"type": "graphic", "path": "/assets/bug.png", "offset": 5, "duration": 30},
+                {"type": "graphic", "path": "/assets/old.png", "offset": 0, "duration": 5}
+            ]),
+        );
+        let b = item(60.0, serde_json::json!([{"type": "hide_logo"}]));
+        let c = item(
+            20.0,
+            serde_json::json!([{"type": "graphic", "path": "/assets/end.png", "offset": 10, "duration": 30}]),
+        );
+
+        let layers = EventLayers::for_sequence(&[&a, &b, &c], 10.0, None);
+        // Started 10s into `a`: the first bug is cut at the head, the second is over
+        assert_eq!(layers.graphics.len(), 2);
+        assert_eq!(layers.graphics[0].path, "/assets/bug.png");
+        assert_eq!(
+            (layers.graphics[0].start, layers.graphics[0].end),
+            (0.0, 25.0)
+        );
+        assert_eq!(layers.graphics[0].anchor, "top-right");
+        // Clipped to the end of its item
+        assert_eq!(
+            (layers.graphics[1].start, layers.graphics[1].end),
+            (160.0, 170.0)
+        );
+        assert_eq!(layers.logo_hidden, vec![(90.0, 150.0)]);
+        assert_eq!(
+            layers.items,
+            vec![(0.0, 90.0), (90.0, 150.0), (150.0, 170.0)]
+        );
+    }
+
+    #[test]
+    fn test_for_sequence_transitions() {
+        let a = item(100.0, serde_json::json!([]));
+        let mut b = item(60.0, serde_json::json!([]));
+        b.transition = serde_json::from_value(serde_json::json!({"type": "cut"})).unwrap();
+        let c = item(20.0, serde_json::json!([]));
+        let fade =
+            serde_json::from_value(serde_json::json!({"type": "fade", "duration": 4})).unwrap();
+
+        let layers = EventLayers::for_sequence(&[&a, &b, &c], 10.0, Some(&fade));
+        assert_eq!(
+            layers.items,
+            vec![(0.0, 90.0), (90.0, 146.0), (146.0, 166.0)]
+        );
+        assert_eq!(
+            layers.transitions,
+            vec![TimedTransition {
+                kind: playlist::TransitionKind::Fade,
+                cut: 150.0,
+                start: 146.0,
+                duration: 4.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_between_expr() {
+        assert_eq!(
+            between_expr(&[(0.0, 25.0), (90.0, 150.5)]),
+            "between(t,0.000,25.000)+between(t,90.000,150.500)"
+        );
+        assert_eq!(overlay_position("bottom-left", 10, 20), "10:H-h-20");
+    }
+
+    #[test]
+    fn test_remaining_expr() {
+        assert_eq!(
+            remaining_expr(&[(0.0, 90.0), (90.0, 150.0)]),
+            "(gte(t,0.000)*lt(t,90.000)*(90.000-t)+gte(t,90.000)*lt(t,150.000)*(150.000-t))"
+        );
+    }
+
+    #[test]
+    fn test_ticker_line() {
+        assert_eq!(
+            ticker_line("Breaking news\n\n  Weather: sun  \n"),
+            "Breaking news   •   Weather: sun"
+        );
+    }
+}
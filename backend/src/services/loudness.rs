@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// Target when the channel does not set one (EBU R128)
+pub const DEFAULT_TARGET_LUFS: f64 = -23.0;
+/// Boosts stop where the clip's true peak would pass this ceiling
+pub const TRUE_PEAK_CEILING: f64 = -1.0;
+const MAX_BOOST_DB: f64 = 12.0;
+const MAX_CUT_DB: f64 = 30.0;
+
+/// loudnorm measurement of a clip, kept under `media.metadata.loudness`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Loudness {
+    /// Integrated loudness, LUFS
+    pub integrated: f64,
+    /// True peak, dBTP
+    pub true_peak: f64,
+    /// Loudness range, LU
+    pub lra: f64,
+}
+
+/// Measurement from the JSON block `loudnorm=print_format=json` writes at the end of stderr;
+/// None for silent or unreadable audio
+pub fn parse_loudnorm(stderr: &str) -> Option<Loudness> {
+    let start = stderr.rfind("\"input_i\"")?;
+    let open = stderr[..start].rfind('{')?;
+    let close = open + stderr[open..].find('}')?;
+    let json: serde_json::Value = serde_json::from_str(&stderr[open..=close]).ok()?;
+    let field = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+    };
+    Some(Loudness {
+        integrated: field("input_i")?,
+        true_peak: field("input_tp")?,
+        lra: field("input_lra").unwrap_or(0.0),
+    })
+}
+
+/// Gain in dB that brings a clip to `target` LUFS; boosts are held back so the true peak
+/// stays under the ceiling
+pub fn gain_db(measured: &Loudness, target: f64) -> f64 {
+    let mut gain = target - measured.integrated;
+    if gain > 0.0 {
+        gain = gain.min((TRUE_PEAK_CEILING - measured.true_peak).max(0.0));
+    }
+    gain.clamp(-MAX_CUT_DB, MAX_BOOST_DB)
+}
+
+/// `volume` expression (with `eval=frame`) applying each window's linear gain, unity elsewhere
+pub fn gain_expr(gains: &[(f64, f64, f64)]) -> String {
+    let mut expr = "1".to_string();
+    for (start, end, gain) in gains {
+        expr.push_str(&format!(
+            "+gte(t,{:.3})*lt(t,{:.3})*{:.4}",
+            start,
+            end,
+            gain - 1.0
+        ));
+    }
+    expr
+}
+
+/// dB to a linear factor
+pub fn linear(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_loudnorm() {
+        let stderr = r#"size=N/A time=00:01:00.00 bitrate=N/A speed= 120x
+[Parsed_loudnorm_0 @ 0x55d0c8a4c0c0]
+{
+	"input_i" : "-17.52",
+	"input_tp" : "-0.31",
+	"input_lra" : "6.40",
+	"input_thresh" : "-27.81",
+	"output_i" : "-23.02",
+	"output_tp" : "-5.60",
+	"output_lra" : "5.90",
+	"output_thresh" : "-33.27",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.02"
+}
+"#;
+        assert_eq!(
+            parse_loudnorm(stderr),
+            Some(Loudness {
+                integrated: -17.52,
+                true_peak: -0.31,
+                lra: 6.40
+            })
+        );
+        assert_eq!(
+            parse_loudnorm(&stderr.replace("\"-17.52\"", "\"-inf\"")),
+            None
+        );
+        assert_eq!(parse_loudnorm("no audio"), None);
+    }
+
+    #[test]
+    fn test_gain_db() {
+        let loud = Loudness {
+            integrated: -17.5,
+            true_peak: -0.3,
+            lra: 6.4,
+        };
+        assert!((gain_db(&loud, -23.0) + 5.5).abs() < 1e-9);
+        // Quiet clip: boost limited by its true peak (-4 dBTP -> 3 dB of headroom)
+        let quiet = Loudness {
+            integrated: -30.0,
+            true_peak: -4.0,
+            lra: 3.0,
+        };
+        assert!((gain_db(&quiet, -23.0) - 3.0).abs() < 1e-9);
+        assert_eq!(
+            gain_expr(&[(0.0, 90.0, 0.5)]),
+            "1+gte(t,0.000)*lt(t,90.000)*-0.5000"
+        );
+    }
+}
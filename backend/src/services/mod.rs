@@ -1,6 +1,18 @@
+pub mod as_run;
 pub mod auth;
+pub mod broadcast_day;
+pub mod channels;
+pub mod clock;
 pub mod database;
 pub mod engine;
+pub mod events;
 pub mod ffmpeg;
+pub mod filler;
+pub mod graphics;
+pub mod loudness;
 pub mod metadata_fetcher;
+pub mod program_feed;
+pub mod scte35;
 pub mod startup;
+pub mod timeline;
+pub mod transitions;
@@ -0,0 +1,629 @@
+use crate::services::ffmpeg::FFmpegService;
+use crate::services::scte35::{
+    parse_pat, payload_start, pid_of, pmt_streams, read_pcr, PTS_MASK, TICKS_PER_SEC, TS_PACKET,
+};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Arc;
+use std::time::Instant;
+
+// Chunks in flight between the encoders and the publisher (~1.5s of a 5 Mb/s feed)
+const FEED_QUEUE: usize = 64;
+// Frame spacing assumed until the stream shows its own (25 fps)
+const DEFAULT_FRAME_TICKS: u64 = 3600;
+
+/// Publisher of a channel's master path (and its HLS preview) that outlives its encoders.
+/// Encoders write MPEG-TS to their stdout; the feed forwards the one on air and cuts to a
+/// newly attached one at its first video keyframe, with timestamps carried on, so MediaMTX
+/// and the relays reading the path never see the publisher change. The publisher only
+/// remuxes, so it is opened for one output format (see `FFmpegService::master_format`): a
+/// source encoding another is refused and the one on air stays.
+pub struct ProgramFeed {
+    publisher: Child,
+    args: Vec<String>,
+    format: Option<String>,
+    sender: SyncSender<Chunk>,
+    last_source: u64,
+    live: Arc<AtomicU64>,
+    refused: Arc<AtomicU64>,
+    running: Arc<AtomicBool>,
+}
+
+enum Chunk {
+    /// Put this source on air at its next keyframe
+    Queue(u64),
+    Data(u64, Vec<u8>),
+}
+
+impl ProgramFeed {
+    /// Start publishing with `args` (see `FFmpegService::feed_publisher_args`) the sources
+    /// encoding `format`
+    pub fn start(args: Vec<String>, format: String) -> Result<Self, String> {
+        let mut publisher = FFmpegService::new().start_feed_publisher(&args)?;
+        let mut stdin = publisher.stdin.take().ok_or("No publisher stdin")?;
+        if let Some(stderr) = publisher.stderr.take() {
+            std::thread::spawn(move || {
+                use std::io::BufRead;
+                for line in std::io::BufReader::new(stderr)
+                    .lines()
+                    .map_while(Result::ok)
+                {
+                    log::warn!("[Program Feed] {}", line);
+                }
+            });
+        }
+
+        let (sender, receiver) = mpsc::sync_channel::<Chunk>(FEED_QUEUE);
+        let live = Arc::new(AtomicU64::new(0));
+        let refused = Arc::new(AtomicU64::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+        let (on_air, turned_away, alive) = (live.clone(), refused.clone(), running.clone());
+        std::thread::spawn(move || {
+            let mut splicer = Splicer::default();
+            let mut out = Vec::with_capacity(TS_PACKET * 348);
+            for chunk in receiver {
+                match chunk {
+                    Chunk::Queue(source) => splicer.queue(source),
+                    Chunk::Data(source, data) => {
+                        let now = Instant::now();
+                        for packet in data.chunks_exact(TS_PACKET) {
+                            if splicer.push(source, packet, now, &mut out) {
+                                on_air.store(source, Ordering::SeqCst);
+                            }
+                        }
+                        if let Some(source) = splicer.take_refused() {
+                            log::error!(
+                                "Program feed: source {} changes the stream format, cut refused",
+                                source
+                            );
+                            turned_away.store(source, Ordering::SeqCst);
+                        }
+                        if out.is_empty() {
+                            continue;
+                        }
+                        if let Err(e) = stdin.write_all(&out).and_then(|_| stdin.flush()) {
+                            log::warn!("Program feed publisher stopped: {}", e);
+                            break;
+                        }
+                        out.clear();
+                    }
+                }
+            }
+            alive.store(false, Ordering::SeqCst);
+        });
+
+        Ok(ProgramFeed {
+            publisher,
+            args,
+            format: Some(format),
+            sender,
+            last_source: 0,
+            live,
+            refused,
+            running,
+        })
+    }
+
+    /// Read an encoder's stdout; it goes on air at its first keyframe, replacing the
+    /// source on air. Returns its id for `is_live`.
+    pub fn attach(&mut self, encoder: &mut Child) -> Result<u64, String> {
+        let stdout = encoder.stdout.take().ok_or("Encoder has no stdout")?;
+        self.last_source += 1;
+        let source = self.last_source;
+        self.sender
+            .send(Chunk::Queue(source))
+            .map_err(|_| "Program feed publisher stopped".to_string())?;
+        let sender = self.sender.clone();
+        std::thread::spawn(move || read_source(source, stdout, sender));
+        Ok(source)
+    }
+
+    /// Whether `source` (or one attached after it) is on air
+    pub fn is_live(&self, source: u64) -> bool {
+        self.live.load(Ordering::SeqCst) >= source
+    }
+
+    /// Latest source refused for its stream format, once
+    pub fn take_refused(&self) -> Option<u64> {
+        Some(self.refused.swap(0, Ordering::SeqCst)).filter(|s| *s > 0)
+    }
+
+    pub fn is_alive(&mut self) -> bool {
+        self.running.load(Ordering::SeqCst) && matches!(self.publisher.try_wait(), Ok(None))
+    }
+
+    /// Whether the feed publishes with these arguments
+    pub fn publishes(&self, args: &[String]) -> bool {
+        self.args == args
+    }
+
+    /// Whether `source` is the last encoder attached
+    pub fn is_latest(&self, source: u64) -> bool {
+        self.last_source == source
+    }
+
+    /// Output format the feed was opened for; None once `expire` found it wrong
+    pub fn format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+
+    /// Have the next encoder reopen the feed, whatever its format
+    pub fn expire(&mut self) {
+        self.format = None;
+    }
+}
+
+impl Drop for ProgramFeed {
+    fn drop(&mut self) {
+        self.publisher.kill().ok();
+        self.publisher.wait().ok();
+    }
+}
+
+// Whole packets of an encoder's output, until it exits or the feed is gone
+fn read_source(source: u64, mut reader: impl Read, sender: SyncSender<Chunk>) {
+    let mut buf = vec![0u8; TS_PACKET * 348];
+    let mut filled = 0;
+    loop {
+        let n = match reader.read(&mut buf[filled..]) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        filled += n;
+        let whole = filled - filled % TS_PACKET;
+        if sender
+            .send(Chunk::Data(source, buf[..whole].to_vec()))
+            .is_err()
+        {
+            return;
+        }
+        buf.copy_within(whole..filled, 0);
+        filled -= whole;
+    }
+}
+
+/// Joins MPEG-TS streams from successive encoders into one. The source on air is forwarded
+/// with its PTS/DTS/PCR shifted so they carry on from the previous source (plus any time the
+/// feed went without), and continuity counters renumbered; a queued source takes over at its
+/// first video keyframe, preceded by its PAT and PMT, unless its streams or SPS differ from
+/// the source on air (the publisher downstream cannot follow). Audio the new source starts
+/// with that would go back in time is dropped.
+#[derive(Default)]
+pub struct Splicer {
+    live: Option<u64>,
+    pending: Option<u64>,
+    /// Latest PAT and PMT of the pending source, and the streams its PMT declares
+    pat: Option<[u8; TS_PACKET]>,
+    pmt: Option<[u8; TS_PACKET]>,
+    pending_streams: Vec<(u16, u8)>,
+    /// Streams and SPS of the source on air, and the last source refused for not matching
+    format: Option<StreamFormat>,
+    refused: Option<u64>,
+    offset: u64,
+    /// Last video DTS sent and when
+    last_video: Option<(u64, Instant)>,
+    frame_ticks: Option<u64>,
+    /// Last PTS sent per PID
+    last_pts: HashMap<u16, u64>,
+    /// PIDs whose current PES is being dropped
+    skipping: HashSet<u16>,
+    continuity: HashMap<u16, u8>,
+}
+
+// What a remuxing publisher takes from the start of its input: elementary streams by PID
+// and the video's SPS. Unknown parts (nothing seen yet) match anything.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct StreamFormat {
+    streams: Vec<(u16, u8)>,
+    sps: Option<Vec<u8>>,
+}
+
+impl StreamFormat {
+    fn differs(&self, other: &StreamFormat) -> bool {
+        let streams =
+            !self.streams.is_empty() && !other.streams.is_empty() && self.streams != other.streams;
+        let sps = matches!((&self.sps, &other.sps), (Some(a), Some(b)) if a != b);
+        streams || sps
+    }
+}
+
+impl Splicer {
+    pub fn queue(&mut self, source: u64) {
+        self.pending = Some(source);
+        self.pat = None;
+        self.pmt = None;
+        self.pending_streams.clear();
+    }
+
+    /// Source whose keyframe brought streams or a frame format other than those on air, so
+    /// it was dropped instead of cut to; cleared by reading it
+    pub fn take_refused(&mut self) -> Option<u64> {
+        self.refused.take()
+    }
+
+    /// Packet of `source` read at `now`; what goes on air is appended to `out`. True when
+    /// this packet put the source on air.
+    pub fn push(&mut self, source: u64, packet: &[u8], now: Instant, out: &mut Vec<u8>) -> bool {
+        if packet.len() != TS_PACKET || packet[0] != 0x47 {
+            return false;
+        }
+        let mut cut = false;
+        if self.pending == Some(source) {
+            let Some(first) = keyframe_dts(packet) else {
+                self.note_tables(packet);
+                return false;
+            };
+            let format = StreamFormat {
+                streams: std::mem::take(&mut self.pending_streams),
+                sps: sps_of(packet),
+            };
+            if self.format.as_ref().is_some_and(|f| f.differs(&format)) {
+                self.refused = self.pending.take();
+                self.pat = None;
+                self.pmt = None;
+                return false;
+            }
+            self.offset = match self.last_video {
+                Some((last, at)) => {
+                    let gap =
+                        (now.saturating_duration_since(at).as_secs_f64() * TICKS_PER_SEC) as u64;
+                    let step = gap.max(self.frame_ticks.unwrap_or(DEFAULT_FRAME_TICKS));
+                    (last + step).wrapping_sub(first) & PTS_MASK
+                }
+                None => 0,
+            };
+            self.live = self.pending.take();
+            self.skipping.clear();
+            cut = true;
+            self.format = Some(format);
+            // Its tables went by before the keyframe; the demuxer downstream needs them first
+            for table in [self.pat.take(), self.pmt.take()].into_iter().flatten() {
+                self.forward(table, out);
+            }
+        } else if self.live != Some(source) {
+            return false;
+        }
+
+        let mut packet: [u8; TS_PACKET] = packet.try_into().unwrap_or([0; TS_PACKET]);
+        let pid = pid_of(&packet);
+        if let Some(pes) = pes_start(&packet) {
+            let pts = read_ts(&packet[pes.pts..]);
+            let shifted = (pts + self.offset) & PTS_MASK;
+            if pes.video {
+                let dts = pes.dts.map(|at| read_ts(&packet[at..])).unwrap_or(pts);
+                let dts = (dts + self.offset) & PTS_MASK;
+                if let Some((last, _)) = self.last_video {
+                    let step = dts.wrapping_sub(last) & PTS_MASK;
+                    if step > 0 && step < TICKS_PER_SEC as u64 {
+                        self.frame_ticks = Some(step);
+                    }
+                }
+                self.last_video = Some((dts, now));
+            } else if self
+                .last_pts
+                .get(&pid)
+                .is_some_and(|&last| !is_after(shifted, last))
+            {
+                self.skipping.insert(pid);
+                return cut;
+            }
+            self.skipping.remove(&pid);
+            self.last_pts.insert(pid, shifted);
+            write_ts(&mut packet[pes.pts..], shifted);
+            if let Some(at) = pes.dts {
+                let dts = (read_ts(&packet[at..]) + self.offset) & PTS_MASK;
+                write_ts(&mut packet[at..], dts);
+            }
+        } else if self.skipping.contains(&pid) {
+            return cut;
+        }
+
+        if let Some(pcr) = read_pcr(&packet) {
+            write_pcr(&mut packet, (pcr + self.offset) & PTS_MASK);
+        }
+        self.forward(packet, out);
+        cut
+    }
+
+    // PAT/PMT of the pending source, kept for its cut
+    fn note_tables(&mut self, packet: &[u8]) {
+        let Ok(packet) = <[u8; TS_PACKET]>::try_from(packet) else {
+            return;
+        };
+        let pid = pid_of(&packet);
+        if pid == 0 && parse_pat(&packet).is_some() {
+            self.pat = Some(packet);
+        } else if self.pat.as_ref().and_then(|pat| parse_pat(pat)) == Some(pid) {
+            if let Some(streams) = pmt_streams(&packet) {
+                self.pending_streams = streams;
+                self.pmt = Some(packet);
+            }
+        }
+    }
+
+    // Out with the continuity counter carried on per PID
+    fn forward(&mut self, mut packet: [u8; TS_PACKET], out: &mut Vec<u8>) {
+        let pid = pid_of(&packet);
+        if packet[3] & 0x10 != 0 && pid != 0x1FFF {
+            let cc = match self.continuity.get(&pid) {
+                Some(last) => (last + 1) & 0x0F,
+                None => packet[3] & 0x0F,
+            };
+            packet[3] = (packet[3] & 0xF0) | cc;
+            self.continuity.insert(pid, cc);
+        }
+        out.extend_from_slice(&packet);
+    }
+}
+
+// Positions of the timestamps of a PES header starting in this packet
+struct PesStart {
+    video: bool,
+    pts: usize,
+    dts: Option<usize>,
+}
+
+fn pes_start(packet: &[u8]) -> Option<PesStart> {
+    if packet[1] & 0x40 == 0 {
+        return None;
+    }
+    let p = payload_start(packet)?;
+    let header = packet.get(p..p + 19)?;
+    if header[..3] != [0, 0, 1] {
+        return None;
+    }
+    let stream_id = header[3];
+    if !(0xC0..=0xEF).contains(&stream_id) && stream_id != 0xBD {
+        return None;
+    }
+    let flags = header[7] >> 6;
+    (flags & 0b10 != 0).then_some(PesStart {
+        video: (0xE0..=0xEF).contains(&stream_id),
+        pts: p + 9,
+        dts: (flags == 0b11).then_some(p + 14),
+    })
+}
+
+// DTS (else PTS) of a packet opening a video keyframe (random access indicator set)
+fn keyframe_dts(packet: &[u8]) -> Option<u64> {
+    let random_access = packet[3] & 0x20 != 0 && packet[4] > 0 && packet[5] & 0x40 != 0;
+    let pes = pes_start(packet).filter(|p| p.video && random_access)?;
+    Some(read_ts(&packet[pes.dts.unwrap_or(pes.pts)..]))
+}
+
+// H.264 (or HEVC) SPS in the payload of a keyframe's first packet, up to the next NAL unit
+// or the end of the packet
+fn sps_of(packet: &[u8]) -> Option<Vec<u8>> {
+    let p = payload_start(packet)?;
+    let data = packet.get(p + 9 + *packet.get(p + 8)? as usize..)?;
+    let starts: Vec<usize> = data
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| *w == [0, 0, 1])
+        .map(|(i, _)| i + 3)
+        .collect();
+    starts.iter().enumerate().find_map(|(n, &at)| {
+        let header = *data.get(at)?;
+        if header & 0x1F != 7 && (header >> 1) & 0x3F != 33 {
+            return None;
+        }
+        let end = starts.get(n + 1).map_or(data.len(), |next| next - 3);
+        // Less the zero byte of a 4-byte start code after it
+        let sps = &data[at..end];
+        let len = sps.iter().rposition(|&b| b != 0).map_or(0, |last| last + 1);
+        Some(sps[..len].to_vec())
+    })
+}
+
+fn read_ts(b: &[u8]) -> u64 {
+    (((b[0] as u64 >> 1) & 0x07) << 30)
+        | ((b[1] as u64) << 22)
+        | ((b[2] as u64 >> 1) << 15)
+        | ((b[3] as u64) << 7)
+        | (b[4] as u64 >> 1)
+}
+
+// Keeps the 4-bit prefix and the marker bits
+fn write_ts(b: &mut [u8], ts: u64) {
+    b[0] = (b[0] & 0xF0) | (((ts >> 30) & 0x07) as u8) << 1 | 1;
+    b[1] = (ts >> 22) as u8;
+    b[2] = (((ts >> 15) & 0x7F) as u8) << 1 | 1;
+    b[3] = (ts >> 7) as u8;
+    b[4] = ((ts & 0x7F) as u8) << 1 | 1;
+}
+
+fn write_pcr(packet: &mut [u8], base: u64) {
+    packet[6] = (base >> 25) as u8;
+    packet[7] = (base >> 17) as u8;
+    packet[8] = (base >> 9) as u8;
+    packet[9] = (base >> 1) as u8;
+    packet[10] = (packet[10] & 0x7F) | ((base & 1) as u8) << 7;
+}
+
+// `a` comes after `b` on the 33-bit clock
+fn is_after(a: u64, b: u64) -> bool {
+    let diff = a.wrapping_sub(b) & PTS_MASK;
+    diff != 0 && diff < 1 << 32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // PES start packet on `pid` with PTS/DTS, optionally a keyframe with a PCR
+    fn pes_packet(pid: u16, stream_id: u8, dts: u64, keyframe: bool, cc: u8) -> Vec<u8> {
+        let mut p = vec![0x47, 0x40 | (pid >> 8) as u8, pid as u8];
+        if keyframe {
+            p.extend([0x30 | cc, 7, 0x50]);
+            p.extend([0; 6]);
+            write_pcr(&mut p[..], dts);
+        } else {
+            p.push(0x10 | cc);
+        }
+        p.extend([0, 0, 1, stream_id, 0, 0, 0x80, 0xC0, 10]);
+        let at = p.len();
+        p.extend([0x31, 0, 1, 0, 1, 0x11, 0, 1, 0, 1]);
+        write_ts(&mut p[at..], dts + 3600);
+        write_ts(&mut p[at + 5..], dts);
+        p.resize(TS_PACKET, 0xFF);
+        p
+    }
+
+    fn psi_packet(pid: u16, mut section: Vec<u8>) -> Vec<u8> {
+        let crc = crate::services::scte35::crc32_mpeg2(&section);
+        section.extend_from_slice(&crc.to_be_bytes());
+        let mut p = vec![0x47, 0x40 | (pid >> 8) as u8, pid as u8, 0x10, 0];
+        p.extend(section);
+        p.resize(TS_PACKET, 0xFF);
+        p
+    }
+
+    // Program 1 with its PMT on 0x1000
+    fn pat_packet() -> Vec<u8> {
+        psi_packet(0, vec![0x00, 0xB0, 13, 0, 1, 0xC1, 0, 0, 0, 1, 0xF0, 0x00])
+    }
+
+    // Video of `video_type` on 0x100 and AAC on 0x101
+    fn pmt_packet(video_type: u8) -> Vec<u8> {
+        psi_packet(
+            0x1000,
+            vec![
+                0x02, 0xB0, 23, 0, 1, 0xC1, 0, 0, 0xE1, 0x00, 0xF0, 0x00, video_type, 0xE1, 0x00,
+                0xF0, 0x00, 0x0F, 0xE1, 0x01, 0xF0, 0x00,
+            ],
+        )
+    }
+
+    // Keyframe whose access unit starts with an SPS and a PPS
+    fn keyframe_with_sps(dts: u64, sps: &[u8]) -> Vec<u8> {
+        let mut p = pes_packet(0x100, 0xE0, dts, true, 0);
+        let mut nals = vec![0, 0, 0, 1, 0x67];
+        nals.extend_from_slice(sps);
+        nals.extend([0, 0, 0, 1, 0x68, 0xCE]);
+        p[31..31 + nals.len()].copy_from_slice(&nals);
+        p
+    }
+
+    fn dts_of(packet: &[u8]) -> u64 {
+        let pes = pes_start(packet).unwrap();
+        read_ts(&packet[pes.dts.unwrap()..])
+    }
+
+    #[test]
+    fn test_splicer_carries_timestamps_across_sources() {
+        let mut splicer = Splicer::default();
+        let mut out = Vec::new();
+        let t0 = Instant::now();
+
+        splicer.queue(1);
+        assert!(!splicer.push(1, &pes_packet(0x100, 0xE0, 1000, false, 0), t0, &mut out));
+        assert!(out.is_empty());
+        assert!(splicer.push(1, &pes_packet(0x100, 0xE0, 1000, true, 0), t0, &mut out));
+        splicer.push(1, &pes_packet(0x100, 0xE0, 4600, false, 1), t0, &mut out);
+        splicer.push(1, &pes_packet(0x101, 0xC0, 4000, false, 5), t0, &mut out);
+        splicer.push(1, &pes_packet(0x101, 0xC0, 12000, false, 6), t0, &mut out);
+        assert_eq!(out.len(), 4 * TS_PACKET);
+
+        // Second encoder starts its clock over; it is held back until its keyframe
+        splicer.queue(2);
+        out.clear();
+        splicer.push(1, &pes_packet(0x100, 0xE0, 8200, false, 2), t0, &mut out);
+        splicer.push(2, &pes_packet(0x101, 0xC0, 100, false, 0), t0, &mut out);
+        assert_eq!(out.len(), TS_PACKET);
+        out.clear();
+        assert!(splicer.push(2, &pes_packet(0x100, 0xE0, 500, true, 0), t0, &mut out));
+        splicer.push(1, &pes_packet(0x100, 0xE0, 11800, false, 3), t0, &mut out);
+        // Audio that would land before what already went out is dropped
+        splicer.push(2, &pes_packet(0x101, 0xC0, 200, false, 1), t0, &mut out);
+        splicer.push(2, &pes_packet(0x101, 0xC0, 9000, false, 2), t0, &mut out);
+        assert_eq!(out.len(), 2 * TS_PACKET);
+
+        let (video, audio) = out.split_at(TS_PACKET);
+        assert_eq!(dts_of(video), 8200 + 3600);
+        assert_eq!(read_pcr(video), Some(8200 + 3600));
+        assert_eq!(dts_of(audio), 9000 + 8200 + 3600 - 500);
+        // Continuity counters carry on per PID
+        assert_eq!(video[3] & 0x0F, 3);
+        assert_eq!(audio[3] & 0x0F, 7);
+
+        // After a gap with nothing on air, the clock moves on by the gap
+        splicer.queue(3);
+        out.clear();
+        let later = t0 + Duration::from_secs(2);
+        splicer.push(3, &pes_packet(0x100, 0xE0, 0, true, 0), later, &mut out);
+        assert_eq!(dts_of(&out), 8200 + 3600 + 180_000);
+    }
+
+    // Cut to `source` with these tables and SPS; what went out, and whether it was refused
+    fn cut_to(
+        splicer: &mut Splicer,
+        source: u64,
+        pmt: &[u8],
+        sps: &[u8],
+        t0: Instant,
+    ) -> (Vec<u8>, bool) {
+        let mut out = Vec::new();
+        splicer.queue(source);
+        splicer.push(source, &pat_packet(), t0, &mut out);
+        splicer.push(source, pmt, t0, &mut out);
+        assert!(out.is_empty());
+        let keyframe = keyframe_with_sps(source * 10_000, sps);
+        let cut = splicer.push(source, &keyframe, t0, &mut out);
+        let refused = splicer.take_refused() == Some(source);
+        assert_ne!(cut, refused);
+        (out, refused)
+    }
+
+    #[test]
+    fn test_splicer_hands_over_between_encoders_of_one_format() {
+        let mut splicer = Splicer::default();
+        let t0 = Instant::now();
+        let hd = [0x64, 0x00, 0x28, 0xAC];
+
+        let (_, refused) = cut_to(&mut splicer, 1, &pmt_packet(0x1B), &hd, t0);
+        assert!(!refused);
+        let mut out = Vec::new();
+        splicer.push(1, &pes_packet(0x100, 0xE0, 13_600, false, 1), t0, &mut out);
+
+        // A reconfigured encoder of the same format takes over, nothing to reopen for
+        let (out, refused) = cut_to(&mut splicer, 2, &pmt_packet(0x1B), &hd, t0);
+        assert!(!refused);
+        // Its tables go out ahead of its keyframe, which carries on from the last frame
+        assert_eq!(out.len(), 3 * TS_PACKET);
+        assert_eq!(pid_of(&out[..]), 0);
+        assert_eq!(pid_of(&out[TS_PACKET..]), 0x1000);
+        assert_eq!(dts_of(&out[2 * TS_PACKET..]), 13_600 + 3600);
+        let mut more = Vec::new();
+        assert!(!splicer.push(1, &pes_packet(0x100, 0xE0, 17_200, false, 2), t0, &mut more));
+        assert!(more.is_empty());
+        assert!(splicer.take_refused().is_none());
+    }
+
+    #[test]
+    fn test_splicer_refuses_a_format_change() {
+        let mut splicer = Splicer::default();
+        let t0 = Instant::now();
+        let (hd, sd) = ([0x64, 0x00, 0x28, 0xAC], [0x64, 0x00, 0x1E, 0xAC]);
+
+        assert!(!cut_to(&mut splicer, 1, &pmt_packet(0x1B), &hd, t0).1);
+        // A new frame size changes the SPS: the source on air stays
+        let (out, refused) = cut_to(&mut splicer, 2, &pmt_packet(0x1B), &sd, t0);
+        assert!(refused && out.is_empty());
+        let mut out = Vec::new();
+        splicer.push(1, &pes_packet(0x100, 0xE0, 13_600, false, 1), t0, &mut out);
+        splicer.push(2, &pes_packet(0x100, 0xE0, 23_600, false, 1), t0, &mut out);
+        assert_eq!(out.len(), TS_PACKET);
+        assert_eq!(dts_of(&out), 13_600);
+        // So does another video codec; the refusal is read once
+        assert!(cut_to(&mut splicer, 3, &pmt_packet(0x24), &hd, t0).1);
+        assert!(splicer.take_refused().is_none());
+        assert_eq!(
+            sps_of(&keyframe_with_sps(0, &sd)),
+            Some(vec![0x67, 0x64, 0x00, 0x1E, 0xAC])
+        );
+    }
+}
@@ -0,0 +1,631 @@
+use crate::models::playlist::CueType;
+use crate::services::timeline::{EntryKind, TimelineEntry};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+/// PID carrying the SCTE-35 sections in the MPEG-TS outputs
+pub const SCTE35_PID: u16 = 0x1F4;
+
+pub(crate) const TS_PACKET: usize = 188;
+pub(crate) const PTS_MASK: u64 = (1 << 33) - 1;
+pub(crate) const TICKS_PER_SEC: f64 = 90_000.0;
+// Cues are sent this long before their splice point (SCTE 67 asks for at least 4s)
+const PREROLL_SECS: f64 = 4.0;
+// FFmpeg's default mpegts mux delay: PTS of what is being muxed runs ~0.7s ahead of the PCR
+const MUX_DELAY_TICKS: u64 = 63_000;
+
+/// A splice point in wall-clock time
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpliceCue {
+    pub at: DateTime<Utc>,
+    pub event_id: u32,
+    /// true for a cue-out (leave the network feed), false for the return cue-in
+    pub out_of_network: bool,
+    /// Break length, cue-outs only
+    pub duration: Option<f64>,
+}
+
+/// Upcoming splice points of a channel, shared with the processes that mux them
+pub type CueSchedule = Arc<Mutex<Vec<SpliceCue>>>;
+
+fn seconds(s: f64) -> Duration {
+    Duration::milliseconds((s * 1000.0).round() as i64)
+}
+
+/// Splice points of a resolved rundown, ordered by time. Cue-outs with a duration get their
+/// cue-in; event ids not given in the playlist are derived from the airtime.
+pub fn schedule_from(rundown: &[TimelineEntry]) -> Vec<SpliceCue> {
+    let mut cues = Vec::new();
+    for entry in rundown.iter().filter(|e| e.kind == EntryKind::Clip) {
+        let Some(item) = entry.item.as_ref() else {
+            continue;
+        };
+        // Wall-clock time of the item's airtime position 0
+        let item_start = entry.start - seconds(entry.clip_offset);
+        let mut open_event = None;
+
+        for cue in item.cue_points() {
+            let at = item_start + seconds(cue.offset);
+            match cue.kind {
+                CueType::Out => {
+                    let event_id = cue.event_id.unwrap_or(at.timestamp() as u32);
+                    if at >= entry.start && at < entry.end {
+                        cues.push(SpliceCue {
+                            at,
+                            event_id,
+                            out_of_network: true,
+                            duration: cue.duration,
+                        });
+                    }
+                    open_event = Some(event_id);
+                    if let Some(duration) = cue.duration {
+                        let back = at + seconds(duration);
+                        if back > entry.start {
+                            cues.push(SpliceCue {
+                                at: back,
+                                event_id,
+                                out_of_network: false,
+                                duration: None,
+                            });
+                        }
+                        open_event = None;
+                    }
+                }
+                CueType::In => {
+                    let event_id = cue.event_id.or(open_event).unwrap_or(at.timestamp() as u32);
+                    if at > entry.start && at <= entry.end {
+                        cues.push(SpliceCue {
+                            at,
+                            event_id,
+                            out_of_network: false,
+                            duration: None,
+                        });
+                    }
+                    open_event = None;
+                }
+            }
+        }
+    }
+
+    // An item split across entries (preemption) yields its cue-in more than once
+    cues.sort_by_key(|c| c.at);
+    cues.dedup_by(|a, b| a.event_id == b.event_id && a.out_of_network == b.out_of_network);
+    cues
+}
+
+/// CRC-32/MPEG-2, as used by PSI and SCTE-35 sections
+pub fn crc32_mpeg2(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// 33-bit time preceded by a flag bit and six reserved bits (splice_time, break_duration)
+fn time_field(ticks: u64) -> [u8; 5] {
+    let t = ticks & PTS_MASK;
+    [
+        0xFE | (t >> 32) as u8,
+        (t >> 24) as u8,
+        (t >> 16) as u8,
+        (t >> 8) as u8,
+        t as u8,
+    ]
+}
+
+/// splice_info_section carrying a splice_insert() (SCTE 35, 9.7.3); without `pts` the
+/// splice is immediate
+pub fn splice_insert(
+    event_id: u32,
+    out_of_network: bool,
+    pts: Option<u64>,
+    duration: Option<f64>,
+) -> Vec<u8> {
+    let mut command = event_id.to_be_bytes().to_vec();
+    command.push(0x7F); // not a cancel
+    command.push(
+        ((out_of_network as u8) << 7)
+            | 0x40 // program splice
+            | ((duration.is_some() as u8) << 5)
+            | ((pts.is_none() as u8) << 4)
+            | 0x0F,
+    );
+    if let Some(pts) = pts {
+        command.extend_from_slice(&time_field(pts));
+    }
+    if let Some(duration) = duration {
+        // auto_return: the break ends by itself after `duration`
+        command.extend_from_slice(&time_field((duration * TICKS_PER_SEC) as u64));
+    }
+    command.extend_from_slice(&[0x00, 0x01]); // unique_program_id
+    command.extend_from_slice(&[0x00, 0x00]); // avail_num, avails_expected
+
+    // protocol_version .. splice_command_type, the command, descriptor loop, CRC
+    let section_length = 11 + command.len() + 2 + 4;
+    let mut section = vec![
+        0xFC,
+        0x30 | ((section_length >> 8) as u8 & 0x0F),
+        section_length as u8,
+        0x00, // protocol_version
+        0x00, // not encrypted, pts_adjustment = 0
+        0x00,
+        0x00,
+        0x00,
+        0x00,
+        0x00, // cw_index
+        0xFF, // tier 0xFFF
+        0xF0 | ((command.len() >> 8) as u8 & 0x0F),
+        command.len() as u8,
+        0x05, // splice_insert
+    ];
+    section.extend_from_slice(&command);
+    section.extend_from_slice(&[0x00, 0x00]); // no descriptors
+    let crc = crc32_mpeg2(&section);
+    section.extend_from_slice(&crc.to_be_bytes());
+    section
+}
+
+pub(crate) fn pid_of(packet: &[u8]) -> u16 {
+    (((packet[1] & 0x1F) as u16) << 8) | packet[2] as u16
+}
+
+pub(crate) fn payload_start(packet: &[u8]) -> Option<usize> {
+    match packet[3] & 0x30 {
+        0x10 => Some(4),
+        0x30 => Some(5 + packet[4] as usize).filter(|&p| p < TS_PACKET),
+        _ => None,
+    }
+}
+
+// Start of the PSI section in a packet that begins one
+fn section_start(packet: &[u8]) -> Option<usize> {
+    if packet[1] & 0x40 == 0 {
+        return None;
+    }
+    let payload = payload_start(packet)?;
+    let start = payload + 1 + packet[payload] as usize;
+    (start + 3 <= TS_PACKET).then_some(start)
+}
+
+fn section_len(packet: &[u8], start: usize) -> usize {
+    ((((packet[start + 1] & 0x0F) as usize) << 8) | packet[start + 2] as usize) + 3
+}
+
+// PMT PID of the first program in a PAT packet
+pub(crate) fn parse_pat(packet: &[u8]) -> Option<u16> {
+    let start = section_start(packet)?;
+    let end = (start + section_len(packet, start)).min(TS_PACKET);
+    if packet[start] != 0x00 || end < start + 12 {
+        return None;
+    }
+    packet[start + 8..end - 4]
+        .chunks_exact(4)
+        .find(|p| p[0] != 0 || p[1] != 0)
+        .map(|p| (((p[2] & 0x1F) as u16) << 8) | p[3] as u16)
+}
+
+pub(crate) fn read_pcr(packet: &[u8]) -> Option<u64> {
+    if packet[3] & 0x20 == 0 || packet[4] < 7 || packet[5] & 0x10 == 0 {
+        return None;
+    }
+    let b = &packet[6..11];
+    Some(
+        ((b[0] as u64) << 25)
+            | ((b[1] as u64) << 17)
+            | ((b[2] as u64) << 9)
+            | ((b[3] as u64) << 1)
+            | ((b[4] as u64) >> 7),
+    )
+}
+
+// Elementary streams (PID, stream type) a PMT packet declares; None when the PMT does not
+// fit one packet
+pub(crate) fn pmt_streams(packet: &[u8]) -> Option<Vec<(u16, u8)>> {
+    let start = section_start(packet)?;
+    let len = section_len(packet, start);
+    if packet[start] != 0x02 || start + len > TS_PACKET || len < 16 {
+        return None;
+    }
+    let section = &packet[start..start + len];
+    let info_len = (((section[10] & 0x0F) as usize) << 8) | section[11] as usize;
+    let streams = section.get(12 + info_len..len - 4)?;
+
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while pos + 5 <= streams.len() {
+        let pid = (((streams[pos + 1] & 0x1F) as u16) << 8) | streams[pos + 2] as u16;
+        found.push((pid, streams[pos]));
+        pos += 5 + ((((streams[pos + 3] & 0x0F) as usize) << 8) | streams[pos + 4] as usize);
+    }
+    Some(found)
+}
+
+/// PMT packet with the SCTE-35 stream (and its CUEI registration) added, plus the PCR PID.
+/// None when the PMT does not fit one packet or already declares the PID.
+pub fn rewrite_pmt(packet: &[u8]) -> Option<(Vec<u8>, u16)> {
+    let start = section_start(packet)?;
+    let len = section_len(packet, start);
+    if packet[start] != 0x02 || start + len > TS_PACKET || len < 16 {
+        return None;
+    }
+    let section = &packet[start..start + len];
+    let pcr_pid = (((section[8] & 0x1F) as u16) << 8) | section[9] as u16;
+    let info_len = (((section[10] & 0x0F) as usize) << 8) | section[11] as usize;
+    let streams = section.get(12 + info_len..len - 4)?;
+
+    let mut pos = 0;
+    while pos + 5 <= streams.len() {
+        let pid = (((streams[pos + 1] & 0x1F) as u16) << 8) | streams[pos + 2] as u16;
+        if pid == SCTE35_PID {
+            return None;
+        }
+        pos += 5 + ((((streams[pos + 3] & 0x0F) as usize) << 8) | streams[pos + 4] as usize);
+    }
+
+    let registration = [0x05, 0x04, b'C', b'U', b'E', b'I'];
+    let new_info_len = info_len + registration.len();
+    let mut new_section = section[..10].to_vec();
+    new_section.push(0xF0 | (new_info_len >> 8) as u8);
+    new_section.push(new_info_len as u8);
+    new_section.extend_from_slice(&section[12..12 + info_len]);
+    new_section.extend_from_slice(&registration);
+    new_section.extend_from_slice(streams);
+    new_section.extend_from_slice(&[
+        0x86, // SCTE-35
+        0xE0 | (SCTE35_PID >> 8) as u8,
+        SCTE35_PID as u8,
+        0xF0,
+        0x00,
+    ]);
+    let new_len = new_section.len() + 4 - 3;
+    new_section[1] = (section[1] & 0xF0) | ((new_len >> 8) as u8 & 0x0F);
+    new_section[2] = new_len as u8;
+    let crc = crc32_mpeg2(&new_section);
+    new_section.extend_from_slice(&crc.to_be_bytes());
+
+    let payload = payload_start(packet)?;
+    if payload + 1 + new_section.len() > TS_PACKET {
+        return None;
+    }
+    let mut out = packet[..payload].to_vec();
+    out.push(0x00); // pointer_field
+    out.extend_from_slice(&new_section);
+    out.resize(TS_PACKET, 0xFF);
+    Some((out, pcr_pid))
+}
+
+/// Rewrites an MPEG-TS stream on the fly: declares the SCTE-35 PID in the PMT and sends a
+/// splice_insert ahead of every cue of the schedule. Wall-clock cue times are mapped onto
+/// the stream clock through the last PCR seen.
+pub struct TsInjector {
+    schedule: CueSchedule,
+    pmt_pid: Option<u16>,
+    pcr_pid: Option<u16>,
+    sent: HashSet<(u32, bool)>,
+    continuity: u8,
+}
+
+impl TsInjector {
+    pub fn new(schedule: CueSchedule) -> Self {
+        TsInjector {
+            schedule,
+            pmt_pid: None,
+            pcr_pid: None,
+            sent: HashSet::new(),
+            continuity: 0,
+        }
+    }
+
+    /// Feed one 188-byte packet; what to write in its place is appended to `out`
+    pub fn push(&mut self, packet: &[u8], out: &mut Vec<u8>) {
+        if packet.len() != TS_PACKET || packet[0] != 0x47 {
+            out.extend_from_slice(packet);
+            return;
+        }
+        let pid = pid_of(packet);
+        if pid == 0 {
+            if let Some(pmt_pid) = parse_pat(packet) {
+                self.pmt_pid = Some(pmt_pid);
+            }
+        } else if Some(pid) == self.pmt_pid {
+            if let Some((pmt, pcr_pid)) = rewrite_pmt(packet) {
+                self.pcr_pid = Some(pcr_pid);
+                out.extend_from_slice(&pmt);
+                return;
+            }
+        }
+
+        out.extend_from_slice(packet);
+        if Some(pid) == self.pcr_pid {
+            if let Some(pcr) = read_pcr(packet) {
+                self.send_due(pcr, Utc::now(), out);
+            }
+        }
+    }
+
+    // Sections for the cues entering their preroll window
+    fn send_due(&mut self, pcr: u64, now: DateTime<Utc>, out: &mut Vec<u8>) {
+        let schedule = self.schedule.lock().unwrap().clone();
+        self.sent.retain(|key| {
+            schedule
+                .iter()
+                .any(|c| (c.event_id, c.out_of_network) == *key)
+        });
+
+        for cue in schedule {
+            let lead = (cue.at - now).num_milliseconds() as f64 / 1000.0;
+            let key = (cue.event_id, cue.out_of_network);
+            if !(-1.0..=PREROLL_SECS).contains(&lead) || self.sent.contains(&key) {
+                continue;
+            }
+            let pts = pcr + MUX_DELAY_TICKS + (lead.max(0.0) * TICKS_PER_SEC) as u64;
+            log::info!(
+                "📺 SCTE-35 splice {} (event {}) in {:.1}s",
+                if cue.out_of_network { "OUT" } else { "IN" },
+                cue.event_id,
+                lead
+            );
+            let section = splice_insert(
+                cue.event_id,
+                cue.out_of_network,
+                Some(pts & PTS_MASK),
+                cue.duration,
+            );
+            self.write_section(&section, out);
+            self.sent.insert(key);
+        }
+    }
+
+    fn write_section(&mut self, section: &[u8], out: &mut Vec<u8>) {
+        let start = out.len();
+        out.extend_from_slice(&[
+            0x47,
+            0x40 | (SCTE35_PID >> 8) as u8,
+            SCTE35_PID as u8,
+            0x10 | self.continuity,
+            0x00, // pointer_field
+        ]);
+        out.extend_from_slice(section);
+        out.resize(start + TS_PACKET, 0xFF);
+        self.continuity = (self.continuity + 1) & 0x0F;
+    }
+}
+
+/// Copy MPEG-TS from `reader` to `writer` through a `TsInjector` until either side closes
+pub fn pump(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    schedule: CueSchedule,
+) -> std::io::Result<()> {
+    let mut injector = TsInjector::new(schedule);
+    let mut buf = vec![0u8; TS_PACKET * 348];
+    let mut filled = 0;
+    let mut out = Vec::with_capacity(buf.len() + TS_PACKET * 4);
+
+    loop {
+        let n = reader.read(&mut buf[filled..])?;
+        if n == 0 {
+            return Ok(());
+        }
+        filled += n;
+        let whole = filled - filled % TS_PACKET;
+        for packet in buf[..whole].chunks_exact(TS_PACKET) {
+            injector.push(packet, &mut out);
+        }
+        writer.write_all(&out)?;
+        writer.flush()?;
+        out.clear();
+        buf.copy_within(whole..filled, 0);
+        filled -= whole;
+    }
+}
+
+// Open break (its cue-out) at `at`
+fn open_break(cues: &[SpliceCue], at: DateTime<Utc>) -> Option<&SpliceCue> {
+    cues.iter()
+        .take_while(|c| c.at < at)
+        .last()
+        .filter(|c| c.out_of_network)
+}
+
+/// HLS media playlist with `#EXT-X-CUE-OUT` / `#EXT-X-CUE-OUT-CONT` / `#EXT-X-CUE-IN` on
+/// the segments around each break. Segments are placed in time by their
+/// `#EXT-X-PROGRAM-DATE-TIME`; `cues` must be ordered by time.
+pub fn decorate_playlist(m3u8: &str, cues: &[SpliceCue]) -> String {
+    let mut out = String::with_capacity(m3u8.len() + 256);
+    let mut clock: Option<DateTime<Utc>> = None;
+
+    for line in m3u8.lines() {
+        if let Some(pdt) = line.strip_prefix("#EXT-X-PROGRAM-DATE-TIME:") {
+            clock = DateTime::parse_from_str(pdt.trim(), "%Y-%m-%dT%H:%M:%S%.f%z")
+                .map(|t| t.with_timezone(&Utc))
+                .ok()
+                .or(clock);
+        } else if let Some(inf) = line.strip_prefix("#EXTINF:") {
+            let length = inf
+                .split(',')
+                .next()
+                .and_then(|d| d.trim().parse::<f64>().ok())
+                .unwrap_or(0.0);
+            if let Some(start) = clock {
+                let end = start + seconds(length);
+                let inside: Vec<&SpliceCue> = cues
+                    .iter()
+                    .filter(|c| c.at >= start && c.at < end)
+                    .collect();
+
+                if let Some(open) = open_break(cues, start) {
+                    if !inside.iter().any(|c| !c.out_of_network) {
+                        let elapsed = (start - open.at).num_milliseconds() as f64 / 1000.0;
+                        out.push_str(&match open.duration {
+                            Some(d) => format!(
+                                "#EXT-X-CUE-OUT-CONT:ElapsedTime={:.3},Duration={:.3}\n",
+                                elapsed, d
+                            ),
+                            None => format!("#EXT-X-CUE-OUT-CONT:ElapsedTime={:.3}\n", elapsed),
+                        });
+                    }
+                }
+                for cue in inside {
+                    out.push_str(&match (cue.out_of_network, cue.duration) {
+                        (true, Some(d)) => format!("#EXT-X-CUE-OUT:DURATION={:.3}\n", d),
+                        (true, None) => "#EXT-X-CUE-OUT\n".to_string(),
+                        (false, _) => "#EXT-X-CUE-IN\n".to_string(),
+                    });
+                }
+                clock = Some(end);
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::playlist::PlaylistItem;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 5, 4, h, m, s).unwrap()
+    }
+
+    fn entry(item: serde_json::Value, start: DateTime<Utc>) -> TimelineEntry {
+        let item: PlaylistItem = serde_json::from_value(item).unwrap();
+        let end = start + seconds(item.effective_duration());
+        TimelineEntry {
+            kind: EntryKind::Clip,
+            start,
+            end,
+            schedule_id: None,
+            occurrence_date: None,
+            occurrence_start: None,
+            playlist_id: None,
+            playlist_name: None,
+            priority: 0,
+            item_index: Some(0),
+            item: Some(item),
+            title: None,
+            clip_offset: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_crc32_mpeg2() {
+        assert_eq!(crc32_mpeg2(b"123456789"), 0x0376_E6E7);
+    }
+
+    #[test]
+    fn test_splice_insert_section() {
+        let section = splice_insert(42, true, Some(900_000), Some(30.0));
+        assert_eq!(section[0], 0xFC);
+        let len = (((section[1] & 0x0F) as usize) << 8) | section[2] as usize;
+        assert_eq!(section.len(), len + 3);
+        assert_eq!(section[13], 0x05);
+        assert_eq!(&section[14..18], &42u32.to_be_bytes());
+        // out_of_network, program splice, duration, timed
+        assert_eq!(section[19], 0xEF);
+        // A section followed by its own CRC checks to zero
+        assert_eq!(crc32_mpeg2(&section), 0);
+    }
+
+    #[test]
+    fn test_rewrite_pmt_declares_scte35() {
+        // PMT on PID 0x1000: PCR PID 0x100, one H.264 stream on 0x100
+        let mut section = vec![
+            0x02, 0xB0, 0x00, 0x00, 0x01, 0xC1, 0x00, 0x00, 0xE1, 0x00, 0xF0, 0x00, 0x1B, 0xE1,
+            0x00, 0xF0, 0x00,
+        ];
+        section[2] = (section.len() + 4 - 3) as u8;
+        let crc = crc32_mpeg2(&section);
+        section.extend_from_slice(&crc.to_be_bytes());
+        let mut packet = vec![0x47, 0x50, 0x00, 0x10, 0x00];
+        packet.extend_from_slice(&section);
+        packet.resize(TS_PACKET, 0xFF);
+
+        let (pmt, pcr_pid) = rewrite_pmt(&packet).unwrap();
+        assert_eq!(pcr_pid, 0x100);
+        assert_eq!(pmt.len(), TS_PACKET);
+        let len = section_len(&pmt, 5);
+        assert_eq!(crc32_mpeg2(&pmt[5..5 + len]), 0);
+        assert!(pmt[5..5 + len].windows(4).any(|w| w == b"CUEI"));
+        assert_eq!(&pmt[5 + len - 9..5 + len - 6], &[0x86, 0xE1, 0xF4]);
+        // Already declared: left alone
+        assert!(rewrite_pmt(&pmt).is_none());
+    }
+
+    #[test]
+    fn test_schedule_from_break_item_and_cues() {
+        let rundown = vec![
+            entry(
+                serde_json::json!({"id": "a", "path": "/a.mp4", "duration": 600.0,
+                    "cues": [{"type": "out", "offset": 300.0, "event_id": 7},
+                             {"type": "in", "offset": 360.0}]}),
+                at(10, 0, 0),
+            ),
+            entry(
+                serde_json::json!({"id": "b", "path": "/slate.mp4", "duration": 120.0,
+                    "media_type": "break"}),
+                at(10, 10, 0),
+            ),
+        ];
+        let cues = schedule_from(&rundown);
+        assert_eq!(cues.len(), 4);
+        assert_eq!((cues[0].at, cues[0].event_id), (at(10, 5, 0), 7));
+        assert!(cues[0].out_of_network);
+        assert_eq!((cues[1].at, cues[1].event_id), (at(10, 6, 0), 7));
+        assert!(!cues[1].out_of_network);
+        assert_eq!(cues[2].at, at(10, 10, 0));
+        assert_eq!(cues[2].duration, Some(120.0));
+        assert_eq!(cues[3].at, at(10, 12, 0));
+        assert_eq!(cues[3].event_id, cues[2].event_id);
+    }
+
+    #[test]
+    fn test_decorate_playlist() {
+        let cues = vec![
+            SpliceCue {
+                at: at(10, 0, 3),
+                event_id: 1,
+                out_of_network: true,
+                duration: Some(4.0),
+            },
+            SpliceCue {
+                at: at(10, 0, 7),
+                event_id: 1,
+                out_of_network: false,
+                duration: None,
+            },
+        ];
+        let m3u8 = "#EXTM3U\n#EXT-X-TARGETDURATION:2\n\
+            #EXT-X-PROGRAM-DATE-TIME:2026-05-04T10:00:00.000+0000\n#EXTINF:2.000000,\nstream0.ts\n\
+            #EXT-X-PROGRAM-DATE-TIME:2026-05-04T10:00:02.000+0000\n#EXTINF:2.000000,\nstream1.ts\n\
+            #EXT-X-PROGRAM-DATE-TIME:2026-05-04T10:00:04.000+0000\n#EXTINF:2.000000,\nstream2.ts\n\
+            #EXT-X-PROGRAM-DATE-TIME:2026-05-04T10:00:06.000+0000\n#EXTINF:2.000000,\nstream3.ts\n";
+        let tags: Vec<String> = decorate_playlist(m3u8, &cues)
+            .lines()
+            .filter(|l| l.starts_with("#EXT-X-CUE"))
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                "#EXT-X-CUE-OUT:DURATION=4.000",
+                "#EXT-X-CUE-OUT-CONT:ElapsedTime=1.000,Duration=4.000",
+                "#EXT-X-CUE-IN",
+            ]
+        );
+    }
+}
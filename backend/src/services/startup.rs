@@ -163,6 +163,33 @@ pub async fn ensure_default_assets(pool: &PgPool) -> Result<(), String> {
     .execute(pool)
     .await;
 
+    // 4. PLAYLIST DURATIONS (used by schedule conflict detection and the engine)
+    let playlists =
+        sqlx::query_as::<_, crate::models::playlist::Playlist>("SELECT * FROM playlists")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    for playlist in playlists {
+        let total = playlist
+            .items()
+            .map(|items| crate::models::playlist::total_effective_duration(&items))
+            .unwrap_or(0.0);
+        if (total - playlist.total_duration).abs() > 0.001 {
+            let _ = sqlx::query("UPDATE playlists SET total_duration = $1 WHERE id = $2")
+                .bind(total)
+                .bind(playlist.id)
+                .execute(pool)
+                .await;
+        }
+    }
+
+    // 5. AS-RUN LOG (clips left on air by the previous run)
+    match crate::services::as_run::close_dangling(pool).await {
+        Ok(n) if n > 0 => log::info!("📋 Marked {} open as-run entries as interrupted", n),
+        Ok(_) => {}
+        Err(e) => log::warn!("Could not close open as-run entries: {}", e),
+    }
+
     log::info!("✨ System restoration check complete.");
     Ok(())
 }
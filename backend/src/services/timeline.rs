@@ -0,0 +1,556 @@
+use crate::models::playlist::{self, PlaylistItem, Transition};
+use crate::services::broadcast_day::Occurrence;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// What a timeline entry puts on air
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    /// A playlist item from a schedule block
+    Clip,
+    /// Nothing scheduled: the engine fills it with filler
+    Gap,
+}
+
+/// One resolved stretch of airtime with absolute (UTC) start and end
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub kind: EntryKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub schedule_id: Option<Uuid>,
+    /// Date of the schedule occurrence (key for exceptions and the offset ledger)
+    pub occurrence_date: Option<NaiveDate>,
+    pub occurrence_start: Option<DateTime<Utc>>,
+    pub playlist_id: Option<Uuid>,
+    pub playlist_name: Option<String>,
+    pub priority: i32,
+    pub item_index: Option<usize>,
+    pub item: Option<PlaylistItem>,
+    pub title: Option<String>,
+    /// Seconds into the item's airtime at `start` (joined mid-clip or resumed after preemption)
+    pub clip_offset: f64,
+}
+
+impl TimelineEntry {
+    /// Seconds into the item's airtime at `at`
+    pub fn position_at(&self, at: DateTime<Utc>) -> f64 {
+        self.clip_offset + seconds(at - self.start)
+    }
+
+    fn gap(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        TimelineEntry {
+            kind: EntryKind::Gap,
+            start,
+            end,
+            schedule_id: None,
+            occurrence_date: None,
+            occurrence_start: None,
+            playlist_id: None,
+            playlist_name: None,
+            priority: 0,
+            item_index: None,
+            item: None,
+            title: None,
+            clip_offset: 0.0,
+        }
+    }
+}
+
+/// Everything the resolver needs, gathered up front so resolution itself has no side effects
+#[derive(Debug, Default)]
+pub struct TimelineInput {
+    pub occurrences: Vec<Occurrence>,
+    /// Parsed items per playlist id
+    pub playlists: HashMap<Uuid, Vec<PlaylistItem>>,
+    /// Offset ledger: the skips and pause holds of each occurrence
+    pub shifts: HashMap<(Uuid, NaiveDate), Vec<Shift>>,
+    /// Channel transition for items without their own
+    pub default_transition: Option<Transition>,
+}
+
+/// One ledger entry: seconds an occurrence moved ahead (skip) or was held back (pause, negative)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shift {
+    /// When it took effect: the skip, or the resume ending the hold (which began `seconds`
+    /// earlier). None applies to the whole occurrence.
+    pub from: Option<DateTime<Utc>>,
+    pub seconds: f64,
+}
+
+// A stretch of a schedule occurrence with its real airtime once the ledger is applied
+struct Block<'a> {
+    occ: &'a Occurrence,
+    items: &'a [PlaylistItem],
+    /// Wall-clock time of playlist position 0
+    content_start: DateTime<Utc>,
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+fn seconds(d: Duration) -> f64 {
+    d.num_milliseconds() as f64 / 1000.0
+}
+
+fn millis(s: f64) -> Duration {
+    Duration::milliseconds((s * 1000.0).round() as i64)
+}
+
+fn blocks(input: &TimelineInput) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    for occ in &input.occurrences {
+        let Some(items) = input.playlists.get(&occ.schedule.playlist_id) else {
+            continue;
+        };
+        let duration = millis(playlist::sequence_duration(
+            items,
+            input.default_transition.as_ref(),
+        ));
+        let mut shifts = input.shifts.get(&occ.key()).cloned().unwrap_or_default();
+        shifts.sort_by_key(|s| s.from);
+
+        // Each shift ends the stretch before it (a hold at the pause, a skip where it
+        // happened) and the occurrence carries on from it with the new offset
+        let mut offset = 0.0;
+        let mut begin = occ.start;
+        let mut stretch = |offset: f64, begin: DateTime<Utc>, until: Option<DateTime<Utc>>| {
+            let content_start = occ.start - millis(offset);
+            let end = content_start + duration;
+            let end = until.map_or(end, |until| until.min(end));
+            let begin = begin.max(content_start);
+            if begin < end {
+                blocks.push(Block {
+                    occ,
+                    items,
+                    content_start,
+                    begin,
+                    end,
+                });
+            }
+        };
+        for shift in shifts {
+            let Some(at) = shift.from else {
+                offset += shift.seconds;
+                continue;
+            };
+            let until = if shift.seconds < 0.0 {
+                at + millis(shift.seconds)
+            } else {
+                at
+            };
+            stretch(offset, begin, Some(until));
+            begin = begin.max(at);
+            offset += shift.seconds;
+        }
+        stretch(offset, begin, None);
+    }
+    blocks
+}
+
+/// Resolve the rundown between `from` and `to`.
+///
+/// Where blocks overlap the highest priority wins, ties go to the later start; a
+/// preempted block picks up again (at wall-clock position) once the winner ends.
+/// Unscheduled stretches come back as gap entries, so the result covers the whole window.
+pub fn resolve(
+    input: &TimelineInput,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<TimelineEntry> {
+    let mut entries = Vec::new();
+    if from >= to {
+        return entries;
+    }
+
+    let blocks = blocks(input);
+
+    let mut cuts: Vec<DateTime<Utc>> = blocks
+        .iter()
+        .flat_map(|b| [b.begin, b.end])
+        .filter(|t| *t > from && *t < to)
+        .chain([from, to])
+        .collect();
+    cuts.sort();
+    cuts.dedup();
+
+    // Winner of each stretch between cuts, merged while it does not change
+    let mut segments: Vec<(Option<usize>, DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for w in cuts.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let winner = blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, blk)| blk.begin <= a && a < blk.end)
+            .max_by(|(_, x), (_, y)| {
+                x.occ
+                    .schedule
+                    .priority
+                    .cmp(&y.occ.schedule.priority)
+                    .then(x.occ.start.cmp(&y.occ.start))
+            })
+            .map(|(i, _)| i);
+        match segments.last_mut() {
+            Some(last) if last.0 == winner => last.2 = b,
+            _ => segments.push((winner, a, b)),
+        }
+    }
+
+    for (winner, a, b) in segments {
+        let Some(i) = winner else {
+            entries.push(TimelineEntry::gap(a, b));
+            continue;
+        };
+        let block = &blocks[i];
+        // An item hands over to the next one when the transition into it begins
+        let transitions = playlist::transitions(block.items, input.default_transition.as_ref());
+        let mut clip_start = block.content_start;
+        for (index, item) in block.items.iter().enumerate() {
+            let overlap = transitions
+                .get(index + 1)
+                .copied()
+                .flatten()
+                .map_or(0.0, |t| t.duration);
+            let clip_end = clip_start + millis(item.effective_duration() - overlap);
+            let start = clip_start.max(a);
+            let end = clip_end.min(b);
+            if start < end {
+                entries.push(clip_entry(block, index, item, start, end, clip_start));
+            }
+            if clip_end >= b {
+                break;
+            }
+            clip_start = clip_end;
+        }
+    }
+
+    entries
+}
+
+fn clip_entry(
+    block: &Block,
+    index: usize,
+    item: &PlaylistItem,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    clip_start: DateTime<Utc>,
+) -> TimelineEntry {
+    let s = &block.occ.schedule;
+    let title = item
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("title"))
+        .and_then(|t| t.as_str())
+        .map(|t| t.to_string())
+        .or_else(|| item.filename.clone());
+    TimelineEntry {
+        kind: EntryKind::Clip,
+        start,
+        end,
+        schedule_id: Some(s.id),
+        occurrence_date: Some(block.occ.date),
+        occurrence_start: Some(block.occ.start),
+        playlist_id: Some(s.playlist_id),
+        playlist_name: s.playlist_name.clone(),
+        priority: s.priority,
+        item_index: Some(index),
+        item: Some(item.clone()),
+        title,
+        clip_offset: seconds(start - clip_start),
+    }
+}
+
+/// Ledger keys whose occurrence is off the air for good by `at`: every stretch of it,
+/// offsets applied, has ended (or it no longer airs at all)
+pub fn ended(input: &TimelineInput, at: DateTime<Utc>) -> Vec<(Uuid, NaiveDate)> {
+    let airing: HashSet<_> = blocks(input)
+        .iter()
+        .filter(|b| b.end > at)
+        .map(|b| b.occ.key())
+        .collect();
+    input
+        .shifts
+        .keys()
+        .filter(|key| !airing.contains(key))
+        .copied()
+        .collect()
+}
+
+/// Entry on air at `at`
+pub fn at(entries: &[TimelineEntry], at: DateTime<Utc>) -> Option<&TimelineEntry> {
+    entries.iter().find(|e| e.start <= at && at < e.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schedule::Schedule;
+    use chrono::{NaiveTime, Utc};
+
+    fn item(name: &str, duration: f64) -> PlaylistItem {
+        serde_json::from_value(serde_json::json!({
+            "id": name,
+            "filename": name,
+            "path": format!("/media/{}", name),
+            "duration": duration,
+        }))
+        .unwrap()
+    }
+
+    fn occurrence(playlist_id: Uuid, hms: (u32, u32, u32), priority: i32) -> Occurrence {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let start_time = NaiveTime::from_hms_opt(hms.0, hms.1, hms.2).unwrap();
+        Occurrence {
+            schedule: Schedule {
+                id: Uuid::new_v4(),
+                channel_id: Uuid::nil(),
+                playlist_id,
+                date,
+                start_time: Some(start_time),
+                repeat_pattern: None,
+                created_at: Utc::now(),
+                recurrence: None,
+                priority,
+                playlist_name: None,
+                playlist_duration: None,
+            },
+            date,
+            start: date.and_time(start_time).and_utc(),
+            end: None,
+        }
+    }
+
+    fn t(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 1, 10)
+            .unwrap()
+            .and_hms_opt(h, m, s)
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn test_resolves_clips_and_gaps() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        input
+            .playlists
+            .insert(pid, vec![item("a", 60.0), item("b", 30.0)]);
+        input.occurrences.push(occurrence(pid, (10, 0, 0), 0));
+
+        let entries = resolve(&input, t(9, 59, 0), t(10, 5, 0));
+        let kinds: Vec<_> = entries.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EntryKind::Gap,
+                EntryKind::Clip,
+                EntryKind::Clip,
+                EntryKind::Gap
+            ]
+        );
+        assert_eq!(entries[2].start, t(10, 1, 0));
+        assert_eq!(entries[2].end, t(10, 1, 30));
+
+        let on_air = at(&entries, t(10, 0, 45)).unwrap();
+        assert_eq!(on_air.title.as_deref(), Some("a"));
+        assert!((on_air.position_at(t(10, 0, 45)) - 45.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_priority_preempts_and_resumes() {
+        let (long, news) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut input = TimelineInput::default();
+        input.playlists.insert(long, vec![item("movie", 3600.0)]);
+        input.playlists.insert(news, vec![item("news", 600.0)]);
+        input.occurrences.push(occurrence(long, (10, 0, 0), 0));
+        input.occurrences.push(occurrence(news, (10, 30, 0), 5));
+
+        let entries = resolve(&input, t(10, 0, 0), t(11, 0, 0));
+        let titles: Vec<_> = entries.iter().map(|e| e.title.clone().unwrap()).collect();
+        assert_eq!(titles, vec!["movie", "news", "movie"]);
+        // The movie picks up at its wall-clock position after the break
+        assert!((entries[2].clip_offset - 2400.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_transitions_overlap_items() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        let mut b = item("b", 60.0);
+        b.transition = serde_json::from_value(serde_json::json!({"type": "cut"})).unwrap();
+        input
+            .playlists
+            .insert(pid, vec![item("a", 60.0), b, item("c", 60.0)]);
+        input.occurrences.push(occurrence(pid, (10, 0, 0), 0));
+        input.default_transition =
+            serde_json::from_value(serde_json::json!({"type": "crossfade", "duration": 2}))
+                .unwrap();
+
+        let entries = resolve(&input, t(10, 0, 0), t(10, 5, 0));
+        // a -> b is a cut (the item's own choice), b -> c crossfades over 2 s
+        assert_eq!(entries[1].start, t(10, 1, 0));
+        assert_eq!(entries[2].start, t(10, 1, 58));
+        assert_eq!(entries[2].end, t(10, 2, 58));
+        assert_eq!(entries[3].kind, EntryKind::Gap);
+    }
+
+    #[test]
+    fn test_offsets_shift_the_block() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        input
+            .playlists
+            .insert(pid, vec![item("a", 60.0), item("b", 60.0)]);
+        let occ = occurrence(pid, (10, 0, 0), 0);
+        let shift = Shift {
+            from: None,
+            seconds: 60.0,
+        };
+        input.shifts.insert(occ.key(), vec![shift]);
+        input.occurrences.push(occ);
+
+        let entries = resolve(&input, t(10, 0, 0), t(10, 2, 0));
+        assert_eq!(entries[0].title.as_deref(), Some("b"));
+        assert_eq!(entries[0].end, t(10, 1, 0));
+        assert_eq!(entries[1].kind, EntryKind::Gap);
+    }
+
+    #[test]
+    fn test_pause_holds_back_from_the_resume_point() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        input
+            .playlists
+            .insert(pid, vec![item("a", 60.0), item("b", 60.0)]);
+        let occ = occurrence(pid, (10, 0, 0), 0);
+        // Paused 10:00:30, resumed 10:01:00
+        let hold = Shift {
+            from: Some(t(10, 1, 0)),
+            seconds: -30.0,
+        };
+        input.shifts.insert(occ.key(), vec![hold]);
+        input.occurrences.push(occ);
+
+        let entries = resolve(&input, t(10, 0, 0), t(10, 3, 0));
+        assert_eq!(entries[0].title.as_deref(), Some("a"));
+        assert_eq!(
+            (entries[0].start, entries[0].end),
+            (t(10, 0, 0), t(10, 0, 30))
+        );
+        assert_eq!(entries[1].kind, EntryKind::Gap);
+        assert_eq!(entries[1].end, t(10, 1, 0));
+        assert_eq!(entries[2].title.as_deref(), Some("a"));
+        assert!((entries[2].clip_offset - 30.0).abs() < 0.001);
+        assert_eq!(entries[2].end, t(10, 1, 30));
+        assert_eq!(entries[3].title.as_deref(), Some("b"));
+        assert_eq!(entries[3].end, t(10, 2, 30));
+        assert_eq!(entries[4].kind, EntryKind::Gap);
+    }
+
+    // Title, start, end and clip offset of an entry
+    type Row<'a> = (Option<&'a str>, DateTime<Utc>, DateTime<Utc>, f64);
+
+    fn rundown(entries: &[TimelineEntry]) -> Vec<Row<'_>> {
+        entries
+            .iter()
+            .map(|e| (e.title.as_deref(), e.start, e.end, e.clip_offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_each_pause_holds_from_its_own_resume() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        input
+            .playlists
+            .insert(pid, vec![item("a", 60.0), item("b", 60.0), item("c", 60.0)]);
+        let occ = occurrence(pid, (10, 0, 0), 0);
+        // Paused 10:00:30-10:01:00, then 10:01:30-10:02:00
+        let holds = vec![
+            Shift {
+                from: Some(t(10, 2, 0)),
+                seconds: -30.0,
+            },
+            Shift {
+                from: Some(t(10, 1, 0)),
+                seconds: -30.0,
+            },
+        ];
+        input.shifts.insert(occ.key(), holds);
+        input.occurrences.push(occ);
+
+        let entries = resolve(&input, t(10, 0, 0), t(10, 5, 0));
+        assert_eq!(
+            rundown(&entries),
+            vec![
+                (Some("a"), t(10, 0, 0), t(10, 0, 30), 0.0),
+                (None, t(10, 0, 30), t(10, 1, 0), 0.0),
+                (Some("a"), t(10, 1, 0), t(10, 1, 30), 30.0),
+                (None, t(10, 1, 30), t(10, 2, 0), 0.0),
+                (Some("b"), t(10, 2, 0), t(10, 3, 0), 0.0),
+                (Some("c"), t(10, 3, 0), t(10, 4, 0), 0.0),
+                (None, t(10, 4, 0), t(10, 5, 0), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_then_pause() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        input
+            .playlists
+            .insert(pid, vec![item("a", 60.0), item("b", 60.0), item("c", 60.0)]);
+        let occ = occurrence(pid, (10, 0, 0), 0);
+        // Skipped the last 40 s of a at 10:00:20, paused 10:00:40-10:01:10
+        let shifts = vec![
+            Shift {
+                from: Some(t(10, 0, 20)),
+                seconds: 40.0,
+            },
+            Shift {
+                from: Some(t(10, 1, 10)),
+                seconds: -30.0,
+            },
+        ];
+        input.shifts.insert(occ.key(), shifts);
+        input.occurrences.push(occ);
+
+        let entries = resolve(&input, t(10, 0, 0), t(10, 3, 0));
+        assert_eq!(
+            rundown(&entries),
+            vec![
+                (Some("a"), t(10, 0, 0), t(10, 0, 20), 0.0),
+                (Some("b"), t(10, 0, 20), t(10, 0, 40), 0.0),
+                (None, t(10, 0, 40), t(10, 1, 10), 0.0),
+                (Some("b"), t(10, 1, 10), t(10, 1, 50), 20.0),
+                (Some("c"), t(10, 1, 50), t(10, 2, 50), 0.0),
+                (None, t(10, 2, 50), t(10, 3, 0), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_survives_day_start_while_the_block_airs() {
+        let pid = Uuid::new_v4();
+        let mut input = TimelineInput::default();
+        input.playlists.insert(pid, vec![item("a", 3600.0)]);
+        // 05:30 block skipped ahead 5 min at 05:50, still on air when the day rolls at 06:00
+        let occ = occurrence(pid, (5, 30, 0), 0);
+        let skip = Shift {
+            from: Some(t(5, 50, 0)),
+            seconds: 300.0,
+        };
+        input.shifts.insert(occ.key(), vec![skip]);
+        input.occurrences.push(occ);
+
+        assert!(ended(&input, t(6, 5, 0)).is_empty());
+        let entries = resolve(&input, t(6, 5, 0), t(7, 0, 0));
+        assert!((entries[0].clip_offset - 2400.0).abs() < 0.001);
+        assert_eq!(entries[0].end, t(6, 25, 0));
+
+        assert_eq!(ended(&input, t(6, 25, 0)).len(), 1);
+    }
+}
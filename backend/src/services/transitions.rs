@@ -0,0 +1,122 @@
+use crate::models::playlist::TransitionKind;
+
+/// Transition between two items of a running concat sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedTransition {
+    pub kind: TransitionKind,
+    /// Time in the concat input where the incoming item begins
+    pub cut: f64,
+    /// Encoder time the overlap begins (the incoming item's start on air)
+    pub start: f64,
+    pub duration: f64,
+}
+
+/// Filters taking the concat input apart at each cut and putting it back together through
+/// `xfade`/`acrossfade`, ending in `[v_prog]` and `[a_prog]`.
+///
+/// Every overlap makes the programme shorter than its input, so the encoder runs without
+/// `-re` and the pieces are paced by `realtime`/`arealtime` on the way out instead.
+pub fn filter_graph(
+    video_in: &str,
+    audio_in: &str,
+    transitions: &[TimedTransition],
+    resolution: &str,
+    fps: &str,
+) -> String {
+    let pieces = transitions.len() + 1;
+    let cuts = transitions
+        .iter()
+        .map(|t| format!("{:.3}", t.cut))
+        .collect::<Vec<_>>()
+        .join("|");
+    let labels = |prefix: &str| {
+        (0..pieces)
+            .map(|n| format!("[{}{}]", prefix, n))
+            .collect::<String>()
+    };
+
+    // xfade wants both inputs alike: same size, rate and pixel format
+    let mut graph = format!(
+        "[{}]scale={},fps={},format=yuv420p,setsar=1,segment=timestamps={}{};",
+        video_in,
+        resolution,
+        fps,
+        cuts,
+        labels("vs")
+    );
+    graph.push_str(&format!(
+        "{}aresample=48000,aformat=channel_layouts=stereo,asegment=timestamps={}{};",
+        audio_in,
+        cuts,
+        labels("as")
+    ));
+    for n in 0..pieces {
+        graph.push_str(&format!(
+            "[vs{n}]setpts=PTS-STARTPTS[vp{n}];[as{n}]asetpts=PTS-STARTPTS[ap{n}];"
+        ));
+    }
+
+    let (mut video, mut audio) = ("vp0".to_string(), "ap0".to_string());
+    for (n, t) in transitions.iter().enumerate() {
+        let next = n + 1;
+        let (picture, curve) = match t.kind {
+            TransitionKind::Fade => ("fadeblack", ":c1=exp:c2=exp"),
+            _ => ("fade", ""),
+        };
+        graph.push_str(&format!(
+            "[{}][vp{}]xfade=transition={}:duration={:.3}:offset={:.3}[vx{}];",
+            video,
+            next,
+            picture,
+            t.duration,
+            t.start.max(0.0),
+            next
+        ));
+        graph.push_str(&format!(
+            "[{}][ap{}]acrossfade=d={:.3}{}[ax{}];",
+            audio, next, t.duration, curve, next
+        ));
+        video = format!("vx{}", next);
+        audio = format!("ax{}", next);
+    }
+    graph.push_str(&format!(
+        "[{}]realtime[v_prog];[{}]arealtime[a_prog];",
+        video, audio
+    ));
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_graph() {
+        let transitions = [
+            TimedTransition {
+                kind: TransitionKind::Crossfade,
+                cut: 60.0,
+                start: 59.0,
+                duration: 1.0,
+            },
+            TimedTransition {
+                kind: TransitionKind::Fade,
+                cut: 120.0,
+                start: 117.0,
+                duration: 2.0,
+            },
+        ];
+        let graph = filter_graph("0:v", "[0:a]", &transitions, "1920x1080", "25");
+        assert!(graph.starts_with(
+            "[0:v]scale=1920x1080,fps=25,format=yuv420p,setsar=1,segment=timestamps=60.000|120.000[vs0][vs1][vs2];"
+        ));
+        assert!(graph.contains("asegment=timestamps=60.000|120.000[as0][as1][as2];"));
+        assert!(
+            graph.contains("[vp0][vp1]xfade=transition=fade:duration=1.000:offset=59.000[vx1];")
+        );
+        assert!(graph
+            .contains("[vx1][vp2]xfade=transition=fadeblack:duration=2.000:offset=117.000[vx2];"));
+        assert!(graph.contains("[ax1][ap2]acrossfade=d=2.000:c1=exp:c2=exp[ax2];"));
+        assert!(graph.ends_with("[vx2]realtime[v_prog];[ax2]arealtime[a_prog];"));
+    }
+}
@@ -49,6 +49,7 @@ import {
   DoneAll as DoneAllIcon,
   MoreVert as MoreIcon,
   Movie as MovieIcon,
+  Collections as SlideshowIcon,
 } from '@mui/icons-material';
 import {
   DndContext,
@@ -68,7 +69,7 @@ import {
 import { CSS } from '@dnd-kit/utilities';
 import { playlistAPI, mediaAPI } from '../services/api';
 
-function SortableClip({ clip, onRemove, isSelected, onToggleSelection }) {
+function SortableClip({ clip, onRemove, onTransitionChange, onKenBurnsChange, onVisualChange, isSelected, onToggleSelection }) {
   const {
     attributes,
     listeners,
@@ -150,6 +151,42 @@ function SortableClip({ clip, onRemove, isSelected, onToggleSelection }) {
             {clip.is_filler && (
               <Chip label="FILLER" size="small" variant="outlined" color="warning" sx={{ height: 16, fontSize: '0.55rem', fontWeight: 800 }} />
             )}
+            {clip.slideshow && (
+              <Chip label={`SLIDESHOW • ${clip.slideshow.image_duration}S/IMAGEM`} size="small" variant="outlined" color="secondary" sx={{ height: 16, fontSize: '0.55rem', fontWeight: 800 }} />
+            )}
+            {isStill(clip) && (
+              <FormControlLabel
+                control={<Checkbox checked={!!clip.ken_burns} onChange={(e) => onKenBurnsChange(clip.id, e.target.checked)} size="small" sx={{ p: 0.25 }} />}
+                label={<Typography variant="caption" sx={{ fontSize: '0.6rem', fontWeight: 700, opacity: 0.7 }}>KEN BURNS</Typography>}
+                sx={{ m: 0 }}
+              />
+            )}
+            {clip.media_type === 'audio' && (
+              <Select
+                variant="standard"
+                displayEmpty
+                value={clip.visual?.visualizer || ''}
+                onChange={(e) => onVisualChange(clip.id, e.target.value)}
+                sx={{ fontSize: '0.65rem', fontWeight: 700, opacity: 0.7, '&:before, &:after': { display: 'none' } }}
+              >
+                <MenuItem value="">IMAGEM: PADRÃO DO CANAL</MenuItem>
+                <MenuItem value="none">IMAGEM: SÓ FUNDO + TÍTULO</MenuItem>
+                <MenuItem value="waves">IMAGEM: ONDAS + TÍTULO</MenuItem>
+                <MenuItem value="spectrum">IMAGEM: ESPECTRO + TÍTULO</MenuItem>
+              </Select>
+            )}
+            <Select
+              variant="standard"
+              displayEmpty
+              value={clip.transition?.type || ''}
+              onChange={(e) => onTransitionChange(clip.id, e.target.value)}
+              sx={{ fontSize: '0.65rem', fontWeight: 700, opacity: 0.7, '&:before, &:after': { display: 'none' } }}
+            >
+              <MenuItem value="">ENTRADA: PADRÃO DO CANAL</MenuItem>
+              <MenuItem value="cut">ENTRADA: CORTE</MenuItem>
+              <MenuItem value="fade">ENTRADA: FADE PARA PRETO</MenuItem>
+              <MenuItem value="crossfade">ENTRADA: CROSSFADE</MenuItem>
+            </Select>
           </Stack>
         </Box>
 
@@ -169,6 +206,20 @@ function SortableClip({ clip, onRemove, isSelected, onToggleSelection }) {
   );
 }
 
+// Seconds an image stays on air when added; ffprobe reports a frame's worth
+const IMAGE_DURATION = 10;
+
+function isStill(clip) {
+  return clip.media_type === 'image' || !!clip.slideshow;
+}
+
+// Seconds a clip overlaps the previous one; like the engine, never more than half of either
+function transitionOverlap(clip, prev) {
+  const t = clip.transition;
+  if (!prev || !t || t.type === 'cut') return 0;
+  return Math.max(0, Math.min(t.duration ?? 1, (Number(prev.duration) || 0) / 2, (Number(clip.duration) || 0) / 2));
+}
+
 function formatDuration(seconds) {
   if (!seconds) return '00:00:00';
   const hrs = Math.floor(seconds / 3600);
@@ -249,9 +300,9 @@ export default function PlaylistEditor() {
 
   const calculateTimings = (clipsList) => {
     let offset = 0;
-    return clipsList.map(clip => {
+    return clipsList.map((clip, index) => {
       const duration = Number(clip.duration) || 0;
-      const start = offset;
+      const start = offset - transitionOverlap(clip, clipsList[index - 1]);
       const end = offset + duration;
       offset = end;
       return {
@@ -277,6 +328,8 @@ export default function PlaylistEditor() {
   const [folders, setFolders] = useState([]);
   const [selectedFolder, setSelectedFolder] = useState('root');
   const [useFillersOnly, setUseFillersOnly] = useState(false);
+  const [slideshowDialogOpen, setSlideshowDialogOpen] = useState(false);
+  const [slideshowForm, setSlideshowForm] = useState({ folder_id: '', image_duration: 5, duration: 60, audio: '', ken_burns: false });
 
   const sensors = useSensors(
     useSensor(PointerSensor),
@@ -360,6 +413,7 @@ export default function PlaylistEditor() {
     // Create unique instance with UUID to allow same file multiple times
     const uniqueClip = {
       ...media,
+      duration: media.media_type === 'image' ? IMAGE_DURATION : media.duration,
       id: `${media.id}-${Date.now()}-${Math.random().toString(36).substr(2, 9)}`, // Unique instance ID
       media_id: media.id, // Original media ID for reference
     };
@@ -372,6 +426,7 @@ export default function PlaylistEditor() {
     const selectedMedia = availableMedia.filter(m => selectedMediaIds.includes(m.id));
     const newClips = selectedMedia.map(media => ({
       ...media,
+      duration: media.media_type === 'image' ? IMAGE_DURATION : media.duration,
       id: `${media.id}-${Date.now()}-${Math.random().toString(36).substr(2, 9)}`,
       media_id: media.id,
     }));
@@ -380,6 +435,27 @@ export default function PlaylistEditor() {
     setSelectedMediaIds([]);
   };
 
+  const handleAddSlideshow = () => {
+    const folder = folders.find(f => f.id === slideshowForm.folder_id);
+    if (!folder) {
+      showWarning('Escolha a pasta de imagens do slideshow');
+      return;
+    }
+    setClips([...clips, {
+      id: `slideshow-${Date.now()}-${Math.random().toString(36).substr(2, 9)}`,
+      filename: `Slideshow ${folder.name}`,
+      duration: Number(slideshowForm.duration) || 60,
+      media_type: 'slideshow',
+      ken_burns: slideshowForm.ken_burns,
+      slideshow: {
+        folder_id: folder.id,
+        image_duration: Number(slideshowForm.image_duration) || 5,
+        audio: slideshowForm.audio || null,
+      },
+    }]);
+    setSlideshowDialogOpen(false);
+  };
+
   const toggleMediaSelection = (mediaId) => {
     setSelectedMediaIds(prev => 
       prev.includes(mediaId) ? prev.filter(id => id !== mediaId) : [...prev, mediaId]
@@ -391,6 +467,23 @@ export default function PlaylistEditor() {
     setSelectedClipIds(prev => prev.filter(i => i !== id));
   };
 
+  const handleTransitionChange = (id, type) => {
+    setClips(clips.map((clip) => (clip.id === id
+      ? { ...clip, transition: type ? { type, duration: clip.transition?.duration ?? 1 } : undefined }
+      : clip)));
+  };
+
+  // Audio clips: '' follows the channel, otherwise the clip's own visualizer with its title
+  const handleVisualChange = (id, visualizer) => {
+    setClips(clips.map((clip) => (clip.id === id
+      ? { ...clip, visual: visualizer ? { ...clip.visual, visualizer, now_playing: true } : undefined }
+      : clip)));
+  };
+
+  const handleKenBurnsChange = (id, enabled) => {
+    setClips(clips.map((clip) => (clip.id === id ? { ...clip, ken_burns: enabled } : clip)));
+  };
+
   const toggleSelection = (id) => {
     setSelectedClipIds(prev => 
       prev.includes(id) ? prev.filter(i => i !== id) : [...prev, id]
@@ -428,6 +521,13 @@ export default function PlaylistEditor() {
         is_filler: clip.is_filler || false,
         start_time: clip.start_time,
         end_time: clip.end_time,
+        cues: clip.cues,
+        events: clip.events,
+        transition: clip.transition,
+        media_type: clip.media_type,
+        ken_burns: clip.ken_burns || undefined,
+        slideshow: clip.slideshow,
+        visual: clip.visual,
       })),
     };
 
@@ -476,7 +576,7 @@ export default function PlaylistEditor() {
         const media = availableMedia.find(m => m.path === item.source);
         return {
           id: `clip-${index}`,
-          filename: item.filename || (media ? media.filename : item.source.split('/').pop()),
+          filename: item.filename || (media ? media.filename : (item.source || '').split('/').pop()),
           path: item.source,
           duration: item.duration,
           start_time: item.start_time,
@@ -484,6 +584,12 @@ export default function PlaylistEditor() {
           metadata: item.metadata || null,
           is_filler: item.is_filler || false,
           media_type: item.media_type || (media ? media.media_type : 'video'),
+          cues: item.cues,
+          events: item.events,
+          transition: item.transition,
+          ken_burns: item.ken_burns || false,
+          slideshow: item.slideshow,
+          visual: item.visual,
         };
       });
       setClips(loadedClips, true);
@@ -502,7 +608,7 @@ export default function PlaylistEditor() {
   };
 
   const handleRunAutomation = async () => {
-    const totalSecs = clips.reduce((sum, clip) => sum + (clip.duration || 0), 0);
+    const totalSecs = clips.reduce((sum, clip, i) => sum + (clip.duration || 0) - transitionOverlap(clip, clips[i - 1]), 0);
     const gap = targetDuration - totalSecs;
 
     if (gap <= 0) {
@@ -636,7 +742,7 @@ export default function PlaylistEditor() {
     }
   };
 
-  const totalDuration = clips.reduce((sum, clip) => sum + (clip.duration || 0), 0);
+  const totalDuration = clips.reduce((sum, clip, i) => sum + (clip.duration || 0) - transitionOverlap(clip, clips[i - 1]), 0);
   const targetDuration = 24 * 3600; // 24 hours
 
 
@@ -817,14 +923,24 @@ export default function PlaylistEditor() {
               <Box>
                 <Typography variant="overline" sx={{ fontWeight: 800, color: 'primary.main' }}>GRID DE SEQUÊNCIA ({clips.length} CLIPS)</Typography>
               </Box>
-              <Button
-                variant="outlined"
-                startIcon={<AddIcon />}
-                onClick={() => setMediaDialogOpen(true)}
-                sx={{ borderRadius: 2, fontWeight: 800, fontSize: '0.7rem' }}
-              >
-                ADICIONAR CLIP
-              </Button>
+              <Stack direction="row" spacing={1}>
+                <Button
+                  variant="outlined"
+                  startIcon={<SlideshowIcon />}
+                  onClick={() => setSlideshowDialogOpen(true)}
+                  sx={{ borderRadius: 2, fontWeight: 800, fontSize: '0.7rem' }}
+                >
+                  SLIDESHOW
+                </Button>
+                <Button
+                  variant="outlined"
+                  startIcon={<AddIcon />}
+                  onClick={() => setMediaDialogOpen(true)}
+                  sx={{ borderRadius: 2, fontWeight: 800, fontSize: '0.7rem' }}
+                >
+                  ADICIONAR CLIP
+                </Button>
+              </Stack>
             </Box>
 
             <Box sx={{ flexGrow: 1, overflowY: 'auto', p: 2 }}>
@@ -850,6 +966,9 @@ export default function PlaylistEditor() {
                           key={clip.id}
                           clip={clip}
                           onRemove={handleRemoveClip}
+                          onTransitionChange={handleTransitionChange}
+                          onKenBurnsChange={handleKenBurnsChange}
+                          onVisualChange={handleVisualChange}
                           isSelected={selectedClipIds.includes(clip.id)}
                           onToggleSelection={toggleSelection}
                         />
@@ -872,7 +991,7 @@ export default function PlaylistEditor() {
         PaperProps={{ className: 'glass-panel', sx: { backgroundImage: 'none', border: '1px solid rgba(255,255,255,0.1)' } }}
       >
         <DialogTitle sx={{ fontWeight: 800, color: 'primary.main', borderBottom: '1px solid rgba(255,255,255,0.05)' }}>
-            SELECIONAR MEDIA ({availableMedia.filter(m => m.media_type === 'video' || m.media_type === 'audio').length} DISPONÍVEIS)
+            SELECIONAR MEDIA ({availableMedia.filter(m => m.media_type === 'video' || m.media_type === 'audio' || m.media_type === 'image').length} DISPONÍVEIS)
         </DialogTitle>
         <DialogContent sx={{ p: 0 }}>
           {selectedMediaIds.length > 0 && (
@@ -881,7 +1000,7 @@ export default function PlaylistEditor() {
             </Alert>
           )}
           <List sx={{ px: 2 }}>
-            {availableMedia.filter((m) => m.media_type === 'video' || m.media_type === 'audio').map((media) => {
+            {availableMedia.filter((m) => m.media_type === 'video' || m.media_type === 'audio' || m.media_type === 'image').map((media) => {
               const isSelected = selectedMediaIds.includes(media.id);
               return (
                 <ListItemButton
@@ -897,7 +1016,7 @@ export default function PlaylistEditor() {
                   <Checkbox checked={isSelected} sx={{ color: isSelected ? 'primary.main' : 'rgba(255,255,255,0.2)' }} />
                   <ListItemText
                     primary={media.filename.toUpperCase()}
-                    secondary={`${media.media_type.toUpperCase()} • ${formatDuration(media.duration)}`}
+                    secondary={`${media.media_type.toUpperCase()} • ${formatDuration(media.media_type === 'image' ? IMAGE_DURATION : media.duration)}`}
                     primaryTypographyProps={{ sx: { fontWeight: 800, fontSize: '0.8rem' } }}
                     secondaryTypographyProps={{ sx: { fontSize: '0.65rem', opacity: 0.6 } }}
                   />
@@ -920,6 +1039,76 @@ export default function PlaylistEditor() {
         </DialogActions>
       </Dialog>
 
+      {/* Slideshow Dialog */}
+      <Dialog
+        open={slideshowDialogOpen}
+        onClose={() => setSlideshowDialogOpen(false)}
+        maxWidth="sm"
+        fullWidth
+        PaperProps={{ className: 'glass-panel', sx: { backgroundImage: 'none', border: '1px solid rgba(255,255,255,0.1)' } }}
+      >
+        <DialogTitle sx={{ fontWeight: 800, color: 'primary.main', display: 'flex', alignItems: 'center', gap: 1 }}>
+            <SlideshowIcon /> NOVO SLIDESHOW
+        </DialogTitle>
+        <DialogContent dividers sx={{ borderColor: 'rgba(255,255,255,0.05)' }}>
+            <Typography variant="caption" sx={{ opacity: 0.6, fontWeight: 600, mb: 3, display: 'block' }}>
+                AS IMAGENS DA PASTA PASSAM POR ORDEM DE NOME, REPETINDO ATÉ COMPLETAR A DURAÇÃO.
+            </Typography>
+            <Stack spacing={2}>
+                <FormControl fullWidth variant="standard">
+                    <InputLabel shrink sx={{ fontWeight: 800, fontSize: '0.7rem' }}>PASTA DE IMAGENS</InputLabel>
+                    <Select
+                        value={slideshowForm.folder_id}
+                        onChange={(e) => setSlideshowForm({ ...slideshowForm, folder_id: e.target.value })}
+                        sx={{ fontWeight: 800, fontSize: '0.8rem' }}
+                    >
+                        {folders.map(f => (
+                            <MenuItem key={f.id} value={f.id}>{f.name.toUpperCase()}</MenuItem>
+                        ))}
+                    </Select>
+                </FormControl>
+                <Stack direction="row" spacing={2}>
+                    <TextField
+                        fullWidth label="SEGUNDOS POR IMAGEM" type="number" variant="standard"
+                        value={slideshowForm.image_duration}
+                        onChange={(e) => setSlideshowForm({ ...slideshowForm, image_duration: e.target.value })}
+                        inputProps={{ min: 1, step: 1 }}
+                        InputLabelProps={{ shrink: true, sx: { fontWeight: 800, fontSize: '0.7rem' } }}
+                    />
+                    <TextField
+                        fullWidth label="DURAÇÃO TOTAL (S)" type="number" variant="standard"
+                        value={slideshowForm.duration}
+                        onChange={(e) => setSlideshowForm({ ...slideshowForm, duration: e.target.value })}
+                        inputProps={{ min: 1, step: 1 }}
+                        InputLabelProps={{ shrink: true, sx: { fontWeight: 800, fontSize: '0.7rem' } }}
+                    />
+                </Stack>
+                <FormControl fullWidth variant="standard">
+                    <InputLabel shrink sx={{ fontWeight: 800, fontSize: '0.7rem' }}>ÁUDIO DE FUNDO</InputLabel>
+                    <Select
+                        displayEmpty
+                        value={slideshowForm.audio}
+                        onChange={(e) => setSlideshowForm({ ...slideshowForm, audio: e.target.value })}
+                        sx={{ fontWeight: 800, fontSize: '0.8rem' }}
+                    >
+                        <MenuItem value="">SEM ÁUDIO (SILÊNCIO)</MenuItem>
+                        {availableMedia.filter(m => m.media_type === 'audio').map(m => (
+                            <MenuItem key={m.id} value={m.path}>{m.filename.toUpperCase()}</MenuItem>
+                        ))}
+                    </Select>
+                </FormControl>
+                <FormControlLabel
+                    control={<Checkbox checked={slideshowForm.ken_burns} onChange={(e) => setSlideshowForm({ ...slideshowForm, ken_burns: e.target.checked })} size="small" />}
+                    label={<Typography variant="caption" sx={{ fontWeight: 700, opacity: 0.8 }}>EFEITO KEN BURNS (ZOOM LENTO)</Typography>}
+                />
+            </Stack>
+        </DialogContent>
+        <DialogActions sx={{ p: 3 }}>
+            <Button onClick={() => setSlideshowDialogOpen(false)} sx={{ fontWeight: 800 }}>CANCELAR</Button>
+            <Button variant="contained" onClick={handleAddSlideshow} sx={{ borderRadius: 2, fontWeight: 800, px: 4 }}>ADICIONAR</Button>
+        </DialogActions>
+      </Dialog>
+
       {/* Create Playlist Dialog */}
       <Dialog 
         open={createDialogOpen} 
@@ -2,7 +2,7 @@ import { useState, useEffect } from 'react';
 import { useNavigate, useSearchParams } from 'react-router-dom';
 import ReactPlayer from 'react-player';
 import { useNotification } from '../contexts/NotificationContext';
-import { authAPI, settingsAPI, protectedAPI, playoutAPI } from '../services/api';
+import { authAPI, settingsAPI, protectedAPI, playoutAPI, graphicsAPI } from '../services/api';
 import {
   Box,
   Typography,
@@ -307,6 +307,29 @@ export default function Settings() {
     udpEnabled: false,
     hlsEnabled: false,
     dayStart: '06:00',
+    timezone: '',
+    scte35Enabled: false,
+    loudnessTarget: -23,
+    transitionType: 'cut',
+    transitionDuration: 1,
+    audioBackgroundPath: '',
+    audioVisualizer: 'none',
+    audioNowPlaying: false,
+    tickerEnabled: false,
+    tickerSpeed: 120,
+    tickerPosition: 'bottom',
+    textEnabled: false,
+    textAnchor: 'top-left',
+    textFontPath: '',
+    textFontSize: 36,
+    textColor: 'white',
+    textBox: true,
+    textBoxColor: 'black@0.6',
+    upnextEnabled: false,
+    upnextLead: 10,
+    upnextLabel: 'Up next',
+    clockMode: 'off',
+    clockAnchor: 'bottom-right',
     defaultImagePath: '',
     defaultVideoPath: '',
     version: '',
@@ -366,9 +389,12 @@ export default function Settings() {
   });
   const [releaseHistory, setReleaseHistory] = useState([]);
   const [udpConfirmOpen, setUdpConfirmOpen] = useState(false);
+  const [tickerText, setTickerText] = useState('');
+  const [staticText, setStaticText] = useState('');
 
   useEffect(() => {
     fetchSettings();
+    fetchTextLayers();
     fetchProtectedAssets();
     fetchUsers();
     fetchReleaseHistory();
@@ -451,6 +477,29 @@ export default function Settings() {
         logoPosition: data.logo_position || 'top-right',
         epgUrl: data.epg_url || '',
         dayStart: data.day_start || '06:00',
+        timezone: data.timezone || '',
+        scte35Enabled: data.scte35_enabled || false,
+        loudnessTarget: data.loudness_target ?? -23,
+        transitionType: data.transition_type || 'cut',
+        transitionDuration: data.transition_duration ?? 1,
+        audioBackgroundPath: data.audio_background_path || '',
+        audioVisualizer: data.audio_visualizer || 'none',
+        audioNowPlaying: data.audio_now_playing || false,
+        tickerEnabled: data.ticker_enabled || false,
+        tickerSpeed: data.ticker_speed ?? 120,
+        tickerPosition: data.ticker_position || 'bottom',
+        textEnabled: data.text_enabled || false,
+        textAnchor: data.text_anchor || 'top-left',
+        textFontPath: data.text_font_path || '',
+        textFontSize: data.text_font_size ?? 36,
+        textColor: data.text_color || 'white',
+        textBox: data.text_box ?? true,
+        textBoxColor: data.text_box_color || 'black@0.6',
+        upnextEnabled: data.upnext_enabled || false,
+        upnextLead: data.upnext_lead ?? 10,
+        upnextLabel: data.upnext_label || 'Up next',
+        clockMode: data.clock_mode || 'off',
+        clockAnchor: data.clock_anchor || 'bottom-right',
         defaultImagePath: data.default_image_path || '',
         defaultVideoPath: data.default_video_path || '',
         version: data.system_version || '2.1.1-PRO', 
@@ -496,6 +545,31 @@ export default function Settings() {
     }
   };
 
+  const fetchTextLayers = async () => {
+    try {
+      const [ticker, text] = await Promise.all([graphicsAPI.getTicker(), graphicsAPI.getText()]);
+      setTickerText(ticker.data.text || '');
+      setStaticText(text.data.text || '');
+    } catch (error) {
+      console.error('Failed to fetch text layers:', error);
+    }
+  };
+
+  // Text goes on air at the next frame, no need to save settings
+  const publishText = async (layer) => {
+    try {
+      if (layer === 'ticker') {
+        await graphicsAPI.updateTicker(tickerText);
+      } else {
+        await graphicsAPI.updateText(staticText);
+      }
+      showSuccess('Texto atualizado no ar!');
+    } catch (error) {
+      console.error('Failed to update text layer:', error);
+      showError('Erro ao atualizar texto');
+    }
+  };
+
   const fetchProtectedAssets = async () => {
     try {
       const response = await protectedAPI.list();
@@ -639,6 +713,29 @@ export default function Settings() {
       } else if (tabValue === 2) { // Playout Tab
         payload = {
           day_start: settings.dayStart,
+          timezone: settings.timezone,
+          scte35_enabled: settings.scte35Enabled,
+          loudness_target: parseFloat(settings.loudnessTarget),
+          transition_type: settings.transitionType,
+          transition_duration: parseFloat(settings.transitionDuration),
+          audio_background_path: settings.audioBackgroundPath,
+          audio_visualizer: settings.audioVisualizer,
+          audio_now_playing: settings.audioNowPlaying,
+          ticker_enabled: settings.tickerEnabled,
+          ticker_speed: settings.tickerSpeed,
+          ticker_position: settings.tickerPosition,
+          text_enabled: settings.textEnabled,
+          text_anchor: settings.textAnchor,
+          text_font_path: settings.textFontPath,
+          text_font_size: settings.textFontSize,
+          text_color: settings.textColor,
+          text_box: settings.textBox,
+          text_box_color: settings.textBoxColor,
+          upnext_enabled: settings.upnextEnabled,
+          upnext_lead: settings.upnextLead,
+          upnext_label: settings.upnextLabel,
+          clock_mode: settings.clockMode,
+          clock_anchor: settings.clockAnchor,
           overlay_enabled: settings.overlay_enabled,
           channel_name: settings.channelName,
           overlay_opacity: settings.overlayOpacity,
@@ -1186,6 +1283,94 @@ export default function Settings() {
                                 InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
                             />
                         </Grid>
+                        <Grid item xs={12} md={6}>
+                            <TextField
+                                fullWidth
+                                label="FUSO HORÁRIO DO CANAL (IANA)"
+                                placeholder="Europe/Lisbon"
+                                helperText="Vazio = fuso do servidor"
+                                value={settings.timezone}
+                                onChange={(e) => setSettings({ ...settings, timezone: e.target.value })}
+                                InputLabelProps={{ shrink: true }}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <FormControlLabel
+                                control={
+                                    <Switch
+                                        checked={settings.scte35Enabled}
+                                        onChange={(e) => setSettings({ ...settings, scte35Enabled: e.target.checked })}
+                                    />
+                                }
+                                label="MARCADORES SCTE-35 (SRT/UDP) E CUE TAGS HLS"
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <TextField
+                                fullWidth
+                                type="number"
+                                label="ALVO DE LOUDNESS (LUFS)"
+                                helperText="EBU R128: -23 · streaming: -16"
+                                value={settings.loudnessTarget}
+                                onChange={(e) => setSettings({ ...settings, loudnessTarget: e.target.value })}
+                                inputProps={{ min: -40, max: -5, step: 0.5 }}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <FormControl fullWidth>
+                                <InputLabel>TRANSIÇÃO ENTRE CLIPS</InputLabel>
+                                <Select value={settings.transitionType} label="TRANSIÇÃO ENTRE CLIPS" onChange={(e) => setSettings({ ...settings, transitionType: e.target.value })} sx={{ bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 }}>
+                                    <MenuItem value="cut">Corte</MenuItem>
+                                    <MenuItem value="fade">Fade para preto</MenuItem>
+                                    <MenuItem value="crossfade">Crossfade</MenuItem>
+                                </Select>
+                            </FormControl>
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <TextField
+                                fullWidth
+                                type="number"
+                                label="DURAÇÃO DA TRANSIÇÃO (S)"
+                                disabled={settings.transitionType === 'cut'}
+                                value={settings.transitionDuration}
+                                onChange={(e) => setSettings({ ...settings, transitionDuration: e.target.value })}
+                                inputProps={{ min: 0, max: 10, step: 0.5 }}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <TextField
+                                fullWidth
+                                label="FUNDO DOS CLIPS DE ÁUDIO (IMAGEM OU VÍDEO EM LOOP)"
+                                helperText="Vazio: imagem padrão, senão preto"
+                                value={settings.audioBackgroundPath}
+                                onChange={(e) => setSettings({ ...settings, audioBackgroundPath: e.target.value })}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <FormControl fullWidth>
+                                <InputLabel>VISUALIZADOR DE ÁUDIO</InputLabel>
+                                <Select value={settings.audioVisualizer} label="VISUALIZADOR DE ÁUDIO" onChange={(e) => setSettings({ ...settings, audioVisualizer: e.target.value })} sx={{ bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 }}>
+                                    <MenuItem value="none">Nenhum</MenuItem>
+                                    <MenuItem value="waves">Ondas</MenuItem>
+                                    <MenuItem value="spectrum">Espectro</MenuItem>
+                                </Select>
+                            </FormControl>
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <FormControlLabel
+                                control={
+                                    <Switch
+                                        checked={settings.audioNowPlaying}
+                                        onChange={(e) => setSettings({ ...settings, audioNowPlaying: e.target.checked })}
+                                    />
+                                }
+                                label="A TOCAR: ARTISTA - TÍTULO"
+                            />
+                        </Grid>
                         <Grid item xs={12} md={6}>
                             <TextField
                                 fullWidth
@@ -1309,6 +1494,177 @@ export default function Settings() {
                     </Box>
                 </Paper>
 
+                <Paper className="glass-panel" sx={{ p: 4, mb: 4 }}>
+                    <Box sx={{ mb: 4 }}>
+                        <Typography variant="h6" className="neon-text" sx={{ fontWeight: 800 }}>TICKER & TEXTO</Typography>
+                        <Typography variant="caption" color="text.secondary" sx={{ fontWeight: 600 }}>RODAPÉ DE NOTÍCIAS E TEXTO FIXO NO OUTPUT</Typography>
+                    </Box>
+                    <Grid container spacing={3}>
+                        <Grid item xs={12} md={6}>
+                            <FormControlLabel
+                                control={<Switch checked={settings.tickerEnabled} onChange={(e) => setSettings({ ...settings, tickerEnabled: e.target.checked })} />}
+                                label="TICKER (CRAWL)"
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <FormControlLabel
+                                control={<Switch checked={settings.textEnabled} onChange={(e) => setSettings({ ...settings, textEnabled: e.target.checked })} />}
+                                label="TEXTO FIXO"
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={9}>
+                            <TextField
+                                fullWidth
+                                multiline
+                                label="TEXTO DO TICKER (UMA NOTÍCIA POR LINHA)"
+                                value={tickerText}
+                                onChange={(e) => setTickerText(e.target.value)}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <Button fullWidth variant="contained" onClick={() => publishText('ticker')} sx={{ height: 56, borderRadius: 3, fontWeight: 800 }}>PUBLICAR</Button>
+                        </Grid>
+                        <Grid item xs={12} md={9}>
+                            <TextField
+                                fullWidth
+                                multiline
+                                label="TEXTO FIXO"
+                                value={staticText}
+                                onChange={(e) => setStaticText(e.target.value)}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <Button fullWidth variant="contained" onClick={() => publishText('text')} sx={{ height: 56, borderRadius: 3, fontWeight: 800 }}>PUBLICAR</Button>
+                        </Grid>
+                        <Grid item xs={12} md={4}>
+                            <FormControl fullWidth>
+                                <InputLabel>POSIÇÃO DO TICKER</InputLabel>
+                                <Select value={settings.tickerPosition} label="POSIÇÃO DO TICKER" onChange={(e) => setSettings({ ...settings, tickerPosition: e.target.value })} sx={{ bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 }}>
+                                    <MenuItem value="bottom">Rodapé</MenuItem>
+                                    <MenuItem value="top">Topo</MenuItem>
+                                </Select>
+                            </FormControl>
+                        </Grid>
+                        <Grid item xs={12} md={4}>
+                            <TextField
+                                fullWidth
+                                type="number"
+                                label="VELOCIDADE (PX/S)"
+                                value={settings.tickerSpeed}
+                                onChange={(e) => setSettings({ ...settings, tickerSpeed: parseInt(e.target.value) || 120 })}
+                                inputProps={{ min: 10, max: 1000 }}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={4}>
+                            <FormControl fullWidth>
+                                <InputLabel>POSIÇÃO DO TEXTO FIXO</InputLabel>
+                                <Select value={settings.textAnchor} label="POSIÇÃO DO TEXTO FIXO" onChange={(e) => setSettings({ ...settings, textAnchor: e.target.value })} sx={{ bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 }}>
+                                    <MenuItem value="top-left">Superior Esquerdo</MenuItem>
+                                    <MenuItem value="top-right">Superior Direito</MenuItem>
+                                    <MenuItem value="bottom-left">Inferior Esquerdo</MenuItem>
+                                    <MenuItem value="bottom-right">Inferior Direito</MenuItem>
+                                </Select>
+                            </FormControl>
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <TextField
+                                fullWidth
+                                label="FONTE (CAMINHO .TTF)"
+                                placeholder="Vazio = Sans"
+                                value={settings.textFontPath}
+                                onChange={(e) => setSettings({ ...settings, textFontPath: e.target.value })}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <TextField
+                                fullWidth
+                                type="number"
+                                label="TAMANHO"
+                                value={settings.textFontSize}
+                                onChange={(e) => setSettings({ ...settings, textFontSize: parseInt(e.target.value) || 36 })}
+                                inputProps={{ min: 8, max: 200 }}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={3}>
+                            <TextField
+                                fullWidth
+                                label="COR"
+                                value={settings.textColor}
+                                onChange={(e) => setSettings({ ...settings, textColor: e.target.value })}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <FormControlLabel
+                                control={<Switch checked={settings.textBox} onChange={(e) => setSettings({ ...settings, textBox: e.target.checked })} />}
+                                label="CAIXA DE FUNDO"
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <TextField
+                                fullWidth
+                                label="COR DA CAIXA"
+                                helperText="Ex.: black@0.6"
+                                value={settings.textBoxColor}
+                                onChange={(e) => setSettings({ ...settings, textBoxColor: e.target.value })}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={4}>
+                            <FormControlLabel
+                                control={<Switch checked={settings.upnextEnabled} onChange={(e) => setSettings({ ...settings, upnextEnabled: e.target.checked })} />}
+                                label="A SEGUIR (LOWER THIRD)"
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={4}>
+                            <TextField
+                                fullWidth
+                                label="ETIQUETA"
+                                value={settings.upnextLabel}
+                                onChange={(e) => setSettings({ ...settings, upnextLabel: e.target.value })}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={4}>
+                            <TextField
+                                fullWidth
+                                type="number"
+                                label="SEGUNDOS ANTES DA TRANSIÇÃO"
+                                value={settings.upnextLead}
+                                onChange={(e) => setSettings({ ...settings, upnextLead: parseInt(e.target.value) || 10 })}
+                                inputProps={{ min: 1, max: 300 }}
+                                InputProps={{ sx: { bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 } }}
+                            />
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <FormControl fullWidth>
+                                <InputLabel>RELÓGIO</InputLabel>
+                                <Select value={settings.clockMode} label="RELÓGIO" onChange={(e) => setSettings({ ...settings, clockMode: e.target.value })} sx={{ bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 }}>
+                                    <MenuItem value="off">Desligado</MenuItem>
+                                    <MenuItem value="clock">Hora local do canal</MenuItem>
+                                    <MenuItem value="countdown">Contagem decrescente do clip</MenuItem>
+                                </Select>
+                            </FormControl>
+                        </Grid>
+                        <Grid item xs={12} md={6}>
+                            <FormControl fullWidth>
+                                <InputLabel>POSIÇÃO DO RELÓGIO</InputLabel>
+                                <Select value={settings.clockAnchor} label="POSIÇÃO DO RELÓGIO" onChange={(e) => setSettings({ ...settings, clockAnchor: e.target.value })} sx={{ bgcolor: 'rgba(0,0,0,0.2)', borderRadius: 3 }}>
+                                    <MenuItem value="top-left">Superior Esquerdo</MenuItem>
+                                    <MenuItem value="top-right">Superior Direito</MenuItem>
+                                    <MenuItem value="bottom-left">Inferior Esquerdo</MenuItem>
+                                    <MenuItem value="bottom-right">Inferior Direito</MenuItem>
+                                </Select>
+                            </FormControl>
+                        </Grid>
+                    </Grid>
+                </Paper>
+
                 <Paper className="glass-panel" sx={{ p: 4 }}>
                     <Box sx={{ mb: 4 }}>
                         <Typography variant="h6" className="neon-text" sx={{ fontWeight: 800 }}>PRESETS DE QUALIDADE</Typography>
@@ -123,6 +123,14 @@ export const settingsAPI = {
   resetAll: () => api.post('/settings/reset-all'),
 };
 
+// On-air text layers (ticker / static text)
+export const graphicsAPI = {
+  getTicker: () => api.get('/graphics/ticker'),
+  updateTicker: (text) => api.put('/graphics/ticker', { text }),
+  getText: () => api.get('/graphics/text'),
+  updateText: (text) => api.put('/graphics/text', { text }),
+};
+
 // Template endpoints
 export const templateAPI = {
   list: () => api.get('/templates'),